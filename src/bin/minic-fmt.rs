@@ -0,0 +1,59 @@
+use std::fs::{read_to_string, write};
+use std::process::exit;
+use clap::{Arg, ArgAction, Command};
+use rust_mini_c::parser::printer::format_source;
+
+fn main() {
+    let matches = Command::new("minic-fmt")
+        .version("1.0")
+        .about("Mini-c formatter")
+        .arg(
+            Arg::new("files")
+                .required(true)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("write")
+                .long("write")
+                .short('w')
+                .help("Formats the files in place instead of printing them")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .help("Fails if some file isn't formatted, without changing it")
+                .action(ArgAction::SetTrue),
+        )
+        .get_matches();
+
+    let in_place = matches.get_flag("write");
+    let check = matches.get_flag("check");
+    let mut unformatted = false;
+
+    for path in matches.get_many::<String>("files").expect("required") {
+        let content = read_to_string(path).expect("Failed to read file");
+        let formatted = match format_source(&content) {
+            Ok(formatted) => formatted,
+            Err(err) => {
+                eprintln!("{}: failed to parse file: {:?}", path, err);
+                exit(2);
+            }
+        };
+
+        if check {
+            if formatted != content {
+                println!("{}", path);
+                unformatted = true;
+            }
+        } else if in_place {
+            write(path, formatted).expect("Failed to write file");
+        } else {
+            print!("{}", formatted);
+        }
+    }
+
+    if unformatted {
+        exit(1);
+    }
+}
@@ -0,0 +1,11 @@
+use std::io::{stdin, stdout};
+use std::process::exit;
+use rust_mini_c::lsp::Server;
+
+fn main() {
+    let mut server = Server::new(stdin().lock(), stdout().lock());
+    if let Err(error) = server.run() {
+        eprintln!("minic-lsp: {}", error);
+        exit(1);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use crate::coloring::structure::Coloring;
 use crate::common::StackOffset;
 use crate::ertl::structure::register::{ALLOCATABLE, PhysicalRegister, Register};
@@ -14,7 +14,7 @@ pub type ColoringResult<T> = Result<T, ColoringError>;
 
 pub fn color_graph(graph: &InterferenceGraph) -> ColoringResult<Coloring> {
     let mut allocatable = init_allocatable(graph)?;
-    let mut colors = HashMap::new();
+    let mut colors = BTreeMap::new();
     let mut count_on_stack = 0;
 
     let mut todo = BTreeSet::new();
@@ -72,9 +72,9 @@ pub fn color_graph(graph: &InterferenceGraph) -> ColoringResult<Coloring> {
 
 fn search_one_color_with_preference(
     todo: &BTreeSet<PseudoRegister>,
-    allocatable: &HashMap<PseudoRegister, HashSet<PhysicalRegister>>,
+    allocatable: &HashMap<PseudoRegister, BTreeSet<PhysicalRegister>>,
     interference_graph: &InterferenceGraph,
-    current_allocation: &HashMap<PseudoRegister, Operand>,
+    current_allocation: &BTreeMap<PseudoRegister, Operand>,
 ) -> Option<(PseudoRegister, PhysicalRegister)> {
     for reg in todo {
         let allocatable_colors = allocatable.get(reg).unwrap();
@@ -107,7 +107,7 @@ fn search_one_color_with_preference(
 
 fn search_one_color(
     todo: &BTreeSet<PseudoRegister>,
-    allocatable: &HashMap<PseudoRegister, HashSet<PhysicalRegister>>,
+    allocatable: &HashMap<PseudoRegister, BTreeSet<PhysicalRegister>>,
 ) -> Option<(PseudoRegister, PhysicalRegister)> {
     for reg in todo {
         let colors = allocatable.get(reg).unwrap();
@@ -122,9 +122,9 @@ fn search_one_color(
 
 fn search_pref_with_known_color(
     todo: &BTreeSet<PseudoRegister>,
-    allocatable: &HashMap<PseudoRegister, HashSet<PhysicalRegister>>,
+    allocatable: &HashMap<PseudoRegister, BTreeSet<PhysicalRegister>>,
     interference_graph: &InterferenceGraph,
-    current_allocation: &HashMap<PseudoRegister, Operand>,
+    current_allocation: &BTreeMap<PseudoRegister, Operand>,
 ) -> Option<(PseudoRegister, PhysicalRegister)> {
     for reg in todo {
         for pref in &interference_graph.arcs.get(&Register::Pseudo(reg.clone())).unwrap().prefs {
@@ -152,7 +152,7 @@ fn search_pref_with_known_color(
 
 fn search_any_color(
     todo: &BTreeSet<PseudoRegister>,
-    allocatable: &HashMap<PseudoRegister, HashSet<PhysicalRegister>>,
+    allocatable: &HashMap<PseudoRegister, BTreeSet<PhysicalRegister>>,
 ) -> Option<(PseudoRegister, PhysicalRegister)> {
     for reg in todo {
         for color in allocatable.get(reg).unwrap() {
@@ -162,13 +162,13 @@ fn search_any_color(
     None
 }
 
-fn init_allocatable(graph: &InterferenceGraph) -> ColoringResult<HashMap<PseudoRegister, HashSet<PhysicalRegister>>> {
+fn init_allocatable(graph: &InterferenceGraph) -> ColoringResult<HashMap<PseudoRegister, BTreeSet<PhysicalRegister>>> {
     let mut result = HashMap::new();
 
     for (reg, arc) in &graph.arcs {
         match reg {
             Register::Pseudo(reg) => {
-                let mut allocatable = HashSet::from(ALLOCATABLE);
+                let mut allocatable = BTreeSet::from(ALLOCATABLE);
                 for intf in &arc.intfs {
                     match intf {
                         Register::Pseudo(_) => {}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use derive_new::new;
 use crate::ltl::structure::Operand;
@@ -8,7 +8,7 @@ pub type Color = Operand;
 
 #[derive(Debug, new)]
 pub struct Coloring {
-    pub colors: HashMap<PseudoRegister, Color>,
+    pub colors: BTreeMap<PseudoRegister, Color>,
     pub count_on_stack: u16,
 }
 
@@ -1,5 +1,8 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
+use std::io::{Cursor, Read};
+use itertools::Itertools;
 
 pub type Value = i64;
 pub type Ident<'a> = &'a str;
@@ -8,6 +11,45 @@ pub type StackOffset = i16;
 pub const MAIN: &str = "main";
 pub const PUTCHAR: &str = "putchar";
 pub const MALLOC: &str = "malloc";
+pub const PRINTF: &str = "printf";
+pub const GETCHAR: &str = "getchar";
+
+/// What `getchar` gives once the input is exhausted.
+pub const EOF: Value = -1;
+
+pub const FUN_ADDRESS_BASE: Value = 1 << 40;
+pub const STRING_ADDRESS_BASE: Value = 1 << 41;
+
+/// A position in the source text, both counted from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    /// Finds where `ident` is in `source`. Identifiers of the syntax tree are slices of the source,
+    /// so their address is enough.
+    pub fn of(source: &str, ident: &str) -> Option<Location> {
+        let offset = (ident.as_ptr() as usize).checked_sub(source.as_ptr() as usize)?;
+        let before = source.get(..offset)?;
+        let line = before.matches('\n').count() + 1;
+        let column = before[before.rfind('\n').map_or(0, |x| x + 1)..].chars().count() + 1;
+        Some(Location { line, column })
+    }
+}
+
+/// Functions in the order of the source file, whose slices their names are, so that outputs don't
+/// depend on the order of a `HashMap`.
+pub fn in_source_order<'a, 'b, T>(funs: &'b HashMap<Ident<'a>, T>) -> std::vec::IntoIter<(&'b Ident<'a>, &'b T)> {
+    funs.iter().sorted_by_key(|(name, _)| name.as_ptr())
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
 
 #[derive(Clone)]
 pub struct Stdout {
@@ -36,6 +78,50 @@ impl Stdout {
     }
 }
 
+/// Input of the program, only read as `getchar` asks for it.
+pub struct Stdin {
+    input: RefCell<Box<dyn Read>>,
+}
+
+impl Stdin {
+    pub fn new(input: impl Into<Vec<u8>>) -> Stdin {
+        Stdin::from_reader(Cursor::new(input.into()))
+    }
+
+    pub fn from_reader(reader: impl Read + 'static) -> Stdin {
+        Stdin { input: RefCell::new(Box::new(reader)) }
+    }
+
+    /// The next byte of the input, or `EOF`.
+    pub fn getchar(&self) -> Value {
+        let mut byte = [0];
+        match self.input.borrow_mut().read_exact(&mut byte) {
+            Ok(()) => Value::from(byte[0]),
+            Err(_) => EOF,
+        }
+    }
+}
+
+impl Default for Stdin {
+    fn default() -> Self {
+        Stdin::new(vec![])
+    }
+}
+
+/// How an interpreter saw the program end, printed as its output.
+#[derive(Clone, Debug)]
+pub struct Execution {
+    pub stdout: Stdout,
+    /// Value returned by `main`, the exit status of the program.
+    pub status: Value,
+}
+
+impl Display for Execution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.stdout)
+    }
+}
+
 pub mod bool {
     use crate::common::Value;
 
@@ -58,4 +144,140 @@ pub mod bool {
             if *self { 1 } else { 0 }
         }
     }
+}
+
+/// Integer operations computed as by the instructions the back end emits, on two's complement.
+pub mod arith {
+    use crate::common::Value;
+
+    /// Why `idivq` traps.
+    #[derive(Debug)]
+    pub enum DivisionError {
+        DivisionByZero,
+        /// The quotient doesn't fit, that is for `Value::MIN / -1`.
+        Overflow,
+    }
+
+    /// Quotient and remainder as `idivq` computes them.
+    pub fn idiv(dividend: Value, divisor: Value) -> Result<(Value, Value), DivisionError> {
+        if divisor == 0 {
+            return Err(DivisionError::DivisionByZero);
+        }
+
+        match (dividend.checked_div(divisor), dividend.checked_rem(divisor)) {
+            (Some(quotient), Some(remainder)) => Ok((quotient, remainder)),
+            _ => Err(DivisionError::Overflow),
+        }
+    }
+}
+
+/// Cost of running a program, counted by the interpreters.
+pub mod profile {
+    use std::collections::HashMap;
+    use std::fmt::{Display, Formatter};
+    use itertools::Itertools;
+    use crate::common::Ident;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct FunProfile {
+        pub calls: u64,
+        /// Statements or instructions run in the function itself, not in its callees.
+        pub steps: u64,
+    }
+
+    #[derive(Debug, Default, Clone)]
+    pub struct Profile<'a> {
+        funs: HashMap<Ident<'a>, FunProfile>,
+    }
+
+    impl<'a> Profile<'a> {
+        pub fn call(&mut self, fun: Ident<'a>) {
+            self.funs.entry(fun).or_default().calls += 1;
+        }
+
+        pub fn step(&mut self, fun: Ident<'a>) {
+            self.funs.entry(fun).or_default().steps += 1;
+        }
+
+        pub fn funs(&self) -> &HashMap<Ident<'a>, FunProfile> {
+            &self.funs
+        }
+
+        /// Counts of the function, zero if it never ran.
+        pub fn fun(&self, fun: &str) -> FunProfile {
+            self.funs.get(fun).copied().unwrap_or_default()
+        }
+
+        pub fn steps(&self) -> u64 {
+            self.funs.values().map(|fun| fun.steps).sum()
+        }
+    }
+
+    /// Prints a table of the functions, the costliest first.
+    impl Display for Profile<'_> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            let width = self.funs.keys().map(|name| name.len()).chain([8]).max().unwrap_or_default();
+            writeln!(f, "{:<width$} {:>12} {:>12}", "function", "calls", "steps", width = width)?;
+            for (name, fun) in self.funs.iter().sorted_by_key(|(name, fun)| (std::cmp::Reverse(fun.steps), **name)) {
+                writeln!(f, "{:<width$} {:>12} {:>12}", name, fun.calls, fun.steps, width = width)?;
+            }
+            write!(f, "{:<width$} {:>12} {:>12}", "total", self.funs.values().map(|fun| fun.calls).sum::<u64>(), self.steps(), width = width)
+        }
+    }
+}
+
+pub mod printf {
+    use crate::common::Value;
+
+    pub fn unescape(raw: &str) -> String {
+        let mut result = String::new();
+        let mut chars = raw.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                result.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('0') => result.push('\0'),
+                Some(c) => result.push(c),
+                None => result.push('\\'),
+            }
+        }
+
+        result
+    }
+
+    /// Formats like libc `printf` would with 64 bits arguments : `%d` and `%x`
+    /// only see the low 32 bits, `%c` the low 8 bits.
+    pub fn format<F>(format: &str, args: &[Value], string_at: F) -> Result<String, &'static str>
+        where F: Fn(Value) -> Option<String> {
+        let mut result = String::new();
+        let mut args = args.iter();
+        let mut chars = format.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                result.push(c);
+                continue;
+            }
+            let conversion = chars.next().ok_or("Unterminated printf conversion")?;
+            if conversion == '%' {
+                result.push('%');
+                continue;
+            }
+            let arg = *args.next().ok_or("Missing printf argument")?;
+            match conversion {
+                'd' => result.push_str(&(arg as i32).to_string()),
+                'x' => result.push_str(&format!("{:x}", arg as u32)),
+                'c' => result.push(arg as u8 as char),
+                's' => result.push_str(&string_at(arg).ok_or("Invalid printf string argument")?),
+                _ => return Err("Unsupported printf conversion")
+            }
+        }
+
+        Ok(result)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,39 @@
+use std::fmt::{Display, Formatter};
+use crate::common::arith::DivisionError;
+use crate::common::Value;
+use crate::ertl::structure::Label;
+use crate::ertl::structure::register::PhysicalRegister;
+
+#[derive(Debug)]
+pub enum ErtlInterpreterError {
+    FunctionDoesNotExist(String),
+    NoSuchInstruction(Label),
+    UnallocatedMemory(Value),
+    InvalidFunctionAddress(Value),
+    /// A `ret` popped something else than the address pushed by the call.
+    InvalidReturnAddress(Value),
+    /// A function returned without restoring a register its caller relies on.
+    RegisterNotRestored(String, PhysicalRegister),
+    DivisionByZero,
+    /// The quotient of `Value::MIN / -1` doesn't fit, which makes `idivq` trap.
+    DivisionOverflow,
+    StackOverflow,
+    /// The program ran for that many instructions without ending.
+    TooManySteps(u64),
+    Other(&'static str),
+}
+
+impl Display for ErtlInterpreterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl From<DivisionError> for ErtlInterpreterError {
+    fn from(error: DivisionError) -> Self {
+        match error {
+            DivisionError::DivisionByZero => ErtlInterpreterError::DivisionByZero,
+            DivisionError::Overflow => ErtlInterpreterError::DivisionOverflow,
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::{BTreeMap, HashMap};
+use itertools::Itertools;
+use crate::common::arith::idiv;
+use crate::common::{FUN_ADDRESS_BASE, GETCHAR, Ident, MALLOC, PRINTF, printf, PUTCHAR, STRING_ADDRESS_BASE, Stdin, Stdout, Value};
+use crate::common::bool::{Bool, ToCBool};
+use crate::ertl::interpreter::ErtlInterpreterResult;
+use crate::ertl::interpreter::error::ErtlInterpreterError;
+use crate::ertl::structure::{MbBranch, Mbinop, MuBranch, Munop};
+use crate::ertl::structure::register::{CALLEE_SAVED, CALLER_SAVED, PARAMETERS, PhysicalRegister, RESULT, TMP_1, TMP_2};
+
+const WORD_SIZE: Value = 8;
+/// The stack grows down from `STACK_TOP` to `STACK_TOP - STACK_SIZE`.
+const STACK_TOP: Value = 1 << 32;
+const STACK_SIZE: Value = 8 << 20;
+const HEAP_BASE: Value = 1 << 20;
+/// Pushed by a call, the only address a return may pop.
+const RETURN_ADDRESS: Value = FUN_ADDRESS_BASE - WORD_SIZE;
+/// Left by the library functions in the registers they don't have to preserve, and found in the
+/// registers at the start.
+const CLOBBERED: Value = 0xdead_beef;
+
+/// Physical registers and memory of an x86-64 machine, with the C library functions mini-C calls.
+/// Memory is a map from the address of each written word.
+pub struct Machine<'a> {
+    pub stdout: Stdout,
+    stdin: Stdin,
+    registers: [Value; 16],
+    memory: HashMap<Value, Value>,
+    /// Blocks given by `malloc`, by address, with their size.
+    blocks: BTreeMap<Value, Value>,
+    heap_top: Value,
+    strings: Vec<String>,
+    fun_addresses: Vec<Ident<'a>>,
+}
+
+impl<'a> Machine<'a> {
+    pub fn new<I: Iterator<Item=Ident<'a>>>(funs: I, strings: &[Ident<'a>], stdin: Stdin) -> Machine<'a> {
+        let mut machine = Machine {
+            stdout: Stdout::new(),
+            stdin,
+            registers: [CLOBBERED; 16],
+            memory: HashMap::new(),
+            blocks: BTreeMap::new(),
+            heap_top: HEAP_BASE,
+            strings: strings.iter().map(|x| printf::unescape(x)).collect(),
+            fun_addresses: funs.chain([PUTCHAR, GETCHAR, MALLOC, PRINTF]).sorted().dedup().collect(),
+        };
+        machine.set(&PhysicalRegister::Rsp, STACK_TOP);
+        machine
+    }
+
+    pub fn get(&self, register: &PhysicalRegister) -> Value {
+        self.registers[register.clone() as usize]
+    }
+
+    pub fn set(&mut self, register: &PhysicalRegister, value: Value) {
+        self.registers[register.clone() as usize] = value;
+    }
+
+    fn is_allocated(&self, address: Value) -> bool {
+        let on_stack = self.get(&PhysicalRegister::Rsp) <= address && address < STACK_TOP;
+        let on_heap = self.blocks
+            .range(..=address)
+            .next_back()
+            .is_some_and(|(start, size)| address < start + size);
+
+        on_stack || on_heap
+    }
+
+    pub fn load(&self, address: Value) -> ErtlInterpreterResult<Value> {
+        if !self.is_allocated(address) {
+            return Err(ErtlInterpreterError::UnallocatedMemory(address));
+        }
+        Ok(*self.memory.get(&address).unwrap_or(&0))
+    }
+
+    pub fn store(&mut self, address: Value, value: Value) -> ErtlInterpreterResult<()> {
+        if !self.is_allocated(address) {
+            return Err(ErtlInterpreterError::UnallocatedMemory(address));
+        }
+        self.memory.insert(address, value);
+        Ok(())
+    }
+
+    /// Moves `%rsp` by `offset`, checking the stack doesn't grow past its size.
+    pub fn move_stack(&mut self, offset: Value) -> ErtlInterpreterResult<()> {
+        let rsp = self.get(&PhysicalRegister::Rsp) + offset;
+        if rsp < STACK_TOP - STACK_SIZE {
+            return Err(ErtlInterpreterError::StackOverflow);
+        }
+        self.set(&PhysicalRegister::Rsp, rsp);
+        Ok(())
+    }
+
+    pub fn push(&mut self, value: Value) -> ErtlInterpreterResult<()> {
+        self.move_stack(-WORD_SIZE)?;
+        self.store(self.get(&PhysicalRegister::Rsp), value)
+    }
+
+    pub fn pop(&mut self) -> ErtlInterpreterResult<Value> {
+        let value = self.load(self.get(&PhysicalRegister::Rsp))?;
+        self.move_stack(WORD_SIZE)?;
+        Ok(value)
+    }
+
+    pub fn push_return_address(&mut self) -> ErtlInterpreterResult<()> {
+        self.push(RETURN_ADDRESS)
+    }
+
+    pub fn pop_return_address(&mut self) -> ErtlInterpreterResult<()> {
+        match self.pop()? {
+            RETURN_ADDRESS => Ok(()),
+            address => Err(ErtlInterpreterError::InvalidReturnAddress(address)),
+        }
+    }
+
+    /// Registers a function must give back as it found them, and their values.
+    pub fn preserved(&self) -> Vec<(PhysicalRegister, Value)> {
+        CALLEE_SAVED
+            .iter()
+            .chain(&[PhysicalRegister::Rbp, PhysicalRegister::Rsp])
+            .map(|register| (register.clone(), self.get(register)))
+            .collect()
+    }
+
+    pub fn check_preserved(&self, fun: Ident, preserved: &[(PhysicalRegister, Value)]) -> ErtlInterpreterResult<()> {
+        match preserved.iter().find(|(register, value)| self.get(register) != *value) {
+            Some((register, _)) => Err(ErtlInterpreterError::RegisterNotRestored(String::from(fun), register.clone())),
+            None => Ok(()),
+        }
+    }
+
+    pub fn fun_address(&self, name: Ident<'a>) -> ErtlInterpreterResult<Value> {
+        self.fun_addresses
+            .binary_search(&name)
+            .map(|index| FUN_ADDRESS_BASE + index as Value)
+            .map_err(|_| ErtlInterpreterError::FunctionDoesNotExist(String::from(name)))
+    }
+
+    pub fn fun_at(&self, address: Value) -> ErtlInterpreterResult<Ident<'a>> {
+        address.checked_sub(FUN_ADDRESS_BASE)
+            .and_then(|offset| usize::try_from(offset).ok())
+            .and_then(|index| self.fun_addresses.get(index))
+            .cloned()
+            .ok_or(ErtlInterpreterError::InvalidFunctionAddress(address))
+    }
+
+    pub fn is_library(name: Ident) -> bool {
+        [PUTCHAR, GETCHAR, MALLOC, PRINTF].contains(&name)
+    }
+
+    /// Calls a function of the C library, which leaves garbage in the caller-saved registers.
+    pub fn call_library(&mut self, name: Ident) -> ErtlInterpreterResult<()> {
+        let result = match name {
+            PUTCHAR => {
+                let c = self.get(&PARAMETERS[0]);
+                self.stdout.putchar(c as u8 as char);
+                c
+            }
+            GETCHAR => self.stdin.getchar(),
+            MALLOC => self.malloc(self.get(&PARAMETERS[0])),
+            PRINTF => self.printf()?,
+            _ => return Err(ErtlInterpreterError::FunctionDoesNotExist(String::from(name))),
+        };
+
+        for register in CALLER_SAVED.iter().chain(&[TMP_1, TMP_2]) {
+            self.set(register, CLOBBERED);
+        }
+        self.set(&RESULT, result);
+        Ok(())
+    }
+
+    /// Result of `op` on `value_2`, with `value_1` as the other operand.
+    pub fn binop(&mut self, op: &Mbinop, value_1: Value, value_2: Value) -> ErtlInterpreterResult<Value> {
+        Ok(match op {
+            Mbinop::MMov => value_1,
+            Mbinop::MAdd => value_2.wrapping_add(value_1),
+            Mbinop::MSub => value_2.wrapping_sub(value_1),
+            Mbinop::MMul => value_2.wrapping_mul(value_1),
+            Mbinop::MDiv => {
+                // `idivq` leaves the remainder in `%rdx`
+                let (quotient, remainder) = idiv(value_2, value_1)?;
+                self.set(&PhysicalRegister::Rdx, remainder);
+                quotient
+            }
+            Mbinop::MSete => (value_2 == value_1).to_minic_bool(),
+            Mbinop::MSetne => (value_2 != value_1).to_minic_bool(),
+            Mbinop::Msetl => (value_2 < value_1).to_minic_bool(),
+            Mbinop::Msetle => (value_2 <= value_1).to_minic_bool(),
+            Mbinop::Msetg => (value_2 > value_1).to_minic_bool(),
+            Mbinop::Msetge => (value_2 >= value_1).to_minic_bool(),
+        })
+    }
+
+    fn malloc(&mut self, size: Value) -> Value {
+        let address = self.heap_top;
+        let size = (size.max(1) + WORD_SIZE - 1) / WORD_SIZE * WORD_SIZE;
+        self.blocks.insert(address, size);
+        self.heap_top += size;
+        address
+    }
+
+    fn string_at(&self, address: Value) -> Option<String> {
+        address.checked_sub(STRING_ADDRESS_BASE)
+            .and_then(|offset| usize::try_from(offset).ok())
+            .and_then(|index| self.strings.get(index))
+            .cloned()
+    }
+
+    /// The arguments after the format are in the other parameter registers, then on the stack.
+    fn printf(&mut self) -> ErtlInterpreterResult<Value> {
+        let format = self.string_at(self.get(&PARAMETERS[0]))
+            .ok_or(ErtlInterpreterError::Other("Invalid printf format"))?;
+
+        let count = format.matches('%').count();
+        let rsp = self.get(&PhysicalRegister::Rsp);
+        let mut args = PARAMETERS[1..].iter().map(|register| self.get(register)).collect::<Vec<_>>();
+        for index in 0..count.saturating_sub(args.len()) {
+            let address = rsp + index as Value * WORD_SIZE;
+            if address >= STACK_TOP {
+                break;
+            }
+            args.push(self.load(address)?);
+        }
+
+        let output = printf::format(&format, &args, |address| self.string_at(address))
+            .map_err(ErtlInterpreterError::Other)?;
+
+        for c in output.chars() {
+            self.stdout.putchar(c);
+        }
+
+        Ok(output.len() as Value)
+    }
+}
+
+pub fn unop(op: &Munop, value: Value) -> Value {
+    match op {
+        Munop::Maddi(c) => value.wrapping_add(*c),
+        Munop::Msetei(c) => (value == *c).to_minic_bool(),
+        Munop::Msetnei(c) => (value != *c).to_minic_bool(),
+    }
+}
+
+pub fn unary_branch(op: &MuBranch, value: Value) -> bool {
+    match op {
+        MuBranch::MJz => !value.to_bool(),
+        MuBranch::MJnz => value.to_bool(),
+        MuBranch::MJlei(c) => value <= *c,
+        MuBranch::MJgi(c) => value > *c,
+    }
+}
+
+pub fn binary_branch(op: &MbBranch, value_1: Value, value_2: Value) -> bool {
+    match op {
+        MbBranch::MJl => value_1 < value_2,
+        MbBranch::MJle => value_1 <= value_2,
+    }
+}
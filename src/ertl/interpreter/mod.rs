@@ -0,0 +1,175 @@
+pub mod error;
+pub mod machine;
+
+use std::collections::HashMap;
+use crate::common::{Execution, Ident, MAIN, STRING_ADDRESS_BASE, Stdin, Value};
+use crate::ertl::interpreter::error::ErtlInterpreterError;
+use crate::ertl::interpreter::machine::{binary_branch, Machine, unary_branch, unop};
+use crate::ertl::structure::{File, Fun, Instr, Label, Munop};
+use crate::ertl::structure::register::{PhysicalRegister, Register, RESULT};
+use crate::rtl::structure::register::PseudoRegister;
+
+pub type ErtlInterpreterResult<T> = Result<T, ErtlInterpreterError>;
+
+/// A function being executed : its pseudo-registers, and the next instruction.
+struct Activation<'a, 'b> {
+    fun: &'b Fun<'a>,
+    pseudos: HashMap<PseudoRegister, Value>,
+    label: Label,
+    /// Registers the function must restore before returning.
+    preserved: Vec<(PhysicalRegister, Value)>,
+}
+
+enum Step<'a> {
+    Next(Label),
+    Call(Ident<'a>, Label),
+    Return,
+}
+
+/// Runs the program on simulated physical registers and stack, so that the calling conventions
+/// are checked : a function must restore `%rbp`, `%rsp` and the callee-saved registers.
+pub fn interp_ertl_file(file: &File) -> ErtlInterpreterResult<Execution> {
+    interp_ertl_file_with(file, Stdin::default())
+}
+
+/// Runs the program on the input.
+pub fn interp_ertl_file_with(file: &File, stdin: Stdin) -> ErtlInterpreterResult<Execution> {
+    let mut machine = Machine::new(file.funs.keys().cloned(), &file.strings, stdin);
+    let main = file.funs.get(MAIN).ok_or(ErtlInterpreterError::FunctionDoesNotExist(String::from(MAIN)))?;
+
+    let preserved = machine.preserved();
+    machine.push_return_address()?;
+    let mut stack = vec![Activation::new(main, preserved)];
+
+    while let Some(activation) = stack.last_mut() {
+        let instr = activation.fun.body.instrs
+            .get(&activation.label)
+            .ok_or(ErtlInterpreterError::NoSuchInstruction(activation.label.clone()))?;
+
+        match activation.interp_instr(&mut machine, instr)? {
+            Step::Next(label) => activation.label = label,
+            Step::Call(name, label) if Machine::is_library(name) => {
+                machine.call_library(name)?;
+                activation.label = label;
+            }
+            Step::Call(name, label) => {
+                let fun = file.funs.get(name).ok_or(ErtlInterpreterError::FunctionDoesNotExist(String::from(name)))?;
+                activation.label = label;
+                let preserved = machine.preserved();
+                machine.push_return_address()?;
+                stack.push(Activation::new(fun, preserved));
+            }
+            Step::Return => {
+                machine.pop_return_address()?;
+                machine.check_preserved(activation.fun.name, &activation.preserved)?;
+                stack.pop();
+            }
+        }
+    }
+
+    Ok(Execution { status: machine.get(&RESULT), stdout: machine.stdout })
+}
+
+impl<'a, 'b> Activation<'a, 'b> {
+    fn new(fun: &'b Fun<'a>, preserved: Vec<(PhysicalRegister, Value)>) -> Activation<'a, 'b> {
+        Activation { fun, pseudos: HashMap::new(), label: fun.entry.clone(), preserved }
+    }
+
+    fn get(&self, machine: &Machine, register: &Register) -> Value {
+        match register {
+            Register::Pseudo(register) => *self.pseudos.get(register).unwrap_or(&0),
+            Register::Physical(register) => machine.get(register),
+        }
+    }
+
+    fn put(&mut self, machine: &mut Machine, register: &Register, value: Value) {
+        match register {
+            Register::Pseudo(register) => {
+                self.pseudos.insert(register.clone(), value);
+            }
+            Register::Physical(register) => machine.set(register, value),
+        }
+    }
+
+    fn interp_instr(&mut self, machine: &mut Machine<'a>, instr: &Instr<'a>) -> ErtlInterpreterResult<Step<'a>> {
+        match instr {
+            Instr::EConst(c, r, l) => {
+                self.put(machine, r, *c);
+                Ok(Step::Next(l.clone()))
+            }
+            Instr::ELoad(address, offset, r, l) => {
+                let value = machine.load(self.get(machine, address) + *offset as Value)?;
+                self.put(machine, r, value);
+                Ok(Step::Next(l.clone()))
+            }
+            Instr::EStore(r, address, offset, l) => {
+                machine.store(self.get(machine, address) + *offset as Value, self.get(machine, r))?;
+                Ok(Step::Next(l.clone()))
+            }
+            Instr::EMUnop(op, r, l) => {
+                match (op, r) {
+                    (Munop::Maddi(c), Register::Physical(PhysicalRegister::Rsp)) => machine.move_stack(*c)?,
+                    _ => {
+                        let value = unop(op, self.get(machine, r));
+                        self.put(machine, r, value)
+                    }
+                }
+                Ok(Step::Next(l.clone()))
+            }
+            Instr::EMBinop(op, r1, r2, l) => {
+                let value = machine.binop(op, self.get(machine, r1), self.get(machine, r2))?;
+                self.put(machine, r2, value);
+                Ok(Step::Next(l.clone()))
+            }
+            Instr::EMuBranch(op, r, l1, l2) => {
+                let jump = unary_branch(op, self.get(machine, r));
+                Ok(Step::Next(if jump { l1.clone() } else { l2.clone() }))
+            }
+            Instr::EMbBranch(op, r1, r2, l1, l2) => {
+                let jump = binary_branch(op, self.get(machine, r1), self.get(machine, r2));
+                Ok(Step::Next(if jump { l1.clone() } else { l2.clone() }))
+            }
+            Instr::ECall(name, _, l) => Ok(Step::Call(name, l.clone())),
+            Instr::ECallStar(callee, _, l) => Ok(Step::Call(machine.fun_at(self.get(machine, callee))?, l.clone())),
+            Instr::EFunAddr(name, r, l) => {
+                let address = machine.fun_address(name)?;
+                self.put(machine, r, address);
+                Ok(Step::Next(l.clone()))
+            }
+            Instr::EString(index, r, l) => {
+                self.put(machine, r, STRING_ADDRESS_BASE + *index as Value);
+                Ok(Step::Next(l.clone()))
+            }
+            Instr::EGoto(l) => Ok(Step::Next(l.clone())),
+            Instr::EAllocFrame(l) => {
+                machine.push(machine.get(&PhysicalRegister::Rbp))?;
+                machine.set(&PhysicalRegister::Rbp, machine.get(&PhysicalRegister::Rsp));
+                machine.move_stack(-(self.fun.frame.size() as Value))?;
+                Ok(Step::Next(l.clone()))
+            }
+            Instr::EDeleteFrame(l) => {
+                machine.set(&PhysicalRegister::Rsp, machine.get(&PhysicalRegister::Rbp));
+                let rbp = machine.pop()?;
+                machine.set(&PhysicalRegister::Rbp, rbp);
+                Ok(Step::Next(l.clone()))
+            }
+            Instr::EGetParam(index, r, l) => {
+                // Above the saved `%rbp` and the return address
+                let address = machine.get(&PhysicalRegister::Rbp) + (*index as Value - 6) * 8 + 16;
+                let value = machine.load(address)?;
+                self.put(machine, r, value);
+                Ok(Step::Next(l.clone()))
+            }
+            Instr::EPushParam(r, l) => {
+                machine.push(self.get(machine, r))?;
+                Ok(Step::Next(l.clone()))
+            }
+            Instr::EStackAddr(offset, r, l) => {
+                let address = machine.get(&PhysicalRegister::Rbp) + *offset as Value;
+                self.put(machine, r, address);
+                Ok(Step::Next(l.clone()))
+            }
+            Instr::EReturn => Ok(Step::Return),
+        }
+    }
+}
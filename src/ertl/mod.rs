@@ -1,39 +1,53 @@
 pub mod structure;
 pub mod error;
+pub mod interpreter;
 
-use std::collections::{HashMap, HashSet};
-use itertools::enumerate;
-use crate::common::{StackOffset, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use itertools::{enumerate, Itertools};
+use rayon::prelude::*;
+use crate::common::{in_source_order, StackOffset, Value};
 use crate::ertl::error::ErtlError;
 use crate::ertl::structure::{Graph, File, Fun, Instr, Label};
+use crate::ertl::structure::frame::Frame;
 use crate::ertl::structure::register::{CALLEE_SAVED, PARAMETERS, PhysicalRegister, Register, RESULT};
 use crate::rtl::structure as rtl;
 use crate::rtl::structure::{Fresh, Mbinop, Munop};
+use crate::session::Session;
 
 pub type ErtlResult<T> = Result<T, ErtlError>;
 
+/// Functions are compiled in parallel, each in a session forked for it.
 pub fn ertl_file<'a>(file: &rtl::File<'a>) -> ErtlResult<File<'a>> {
-    let mut funs = HashMap::new();
+    let funs = in_source_order(&file.funs)
+        .enumerate()
+        .map(|(index, (name, fun))| (name, fun, Arc::new(file.session.fork(index))))
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|(name, fun, session)| Ok((*name, ertl_fun(session, fun)?)))
+        .collect::<ErtlResult<HashMap<_, _>>>()?;
+
+    Ok(File::new(funs, file.strings.clone(), file.session.clone()))
+}
 
-    for (name, fun) in &file.funs {
-        funs.insert(name.clone(), ertl_fun(fun)?);
-    }
+fn ertl_fun<'a>(session: Arc<Session>, fun: &rtl::Fun<'a>) -> ErtlResult<Fun<'a>> {
+    let mut body = Graph::new(BTreeMap::new(), session.clone());
 
-    Ok(File::new(funs))
-}
+    let locals = BTreeSet::from_iter(fun.locals.values().map(|r| r.clone().into()));
 
-fn ertl_fun<'a>(fun: &rtl::Fun<'a>) -> ErtlResult<Fun<'a>> {
-    let mut body = Graph::new(HashMap::new());
+    let mut frame = Frame::default();
 
-    let locals = HashSet::from_iter(fun.locals.values().map(|r| r.clone().into()));
+    for (aggregate, size) in fun.aggregates.iter().sorted_by_key(|(aggregate, _)| aggregate.name().as_ptr()) {
+        frame.alloc_local(aggregate.clone(), *size);
+    }
 
     for (label, instr) in &fun.graph.instrs {
-        ertl_instr(&mut body, label, instr)?;
+        ertl_instr(&mut body, &frame, label, instr)?;
     }
 
     let callee_saved_regs = CALLEE_SAVED
         .iter()
-        .map(|x| { (x.clone(), Register::Pseudo(rtl::register::PseudoRegister::fresh())) })
+        .map(|x| { (x.clone(), Register::Pseudo(rtl::register::PseudoRegister::fresh(&session))) })
         .collect::<Vec<(PhysicalRegister, Register)>>();
 
     let return_lbl = body.insert(Instr::EReturn);
@@ -70,12 +84,13 @@ fn ertl_fun<'a>(fun: &rtl::Fun<'a>) -> ErtlResult<Fun<'a>> {
         fun.name,
         args_count,
         locals,
+        frame,
         alloc_frame_lbl,
         body,
     ))
 }
 
-fn ertl_instr<'a>(graph: &mut Graph<'a>, label: &Label, instr: &rtl::Instr<'a>) -> ErtlResult<()> {
+fn ertl_instr<'a>(graph: &mut Graph<'a>, frame: &Frame<'a>, label: &Label, instr: &rtl::Instr<'a>) -> ErtlResult<()> {
     match instr {
         rtl::Instr::EConst(v, r, l) => graph.insert_at_label(label, Instr::EConst(v.clone(), r.clone().into(), l.clone())),
         rtl::Instr::ELoad(addr, o, dest, l) => graph.insert_at_label(label, Instr::ELoad(addr.clone().into(), *o, dest.clone().into(), l.clone())),
@@ -90,36 +105,58 @@ fn ertl_instr<'a>(graph: &mut Graph<'a>, label: &Label, instr: &rtl::Instr<'a>)
         rtl::Instr::EMuBranch(op, r, l1, l2) => graph.insert_at_label(label, Instr::EMuBranch(op.clone(), r.clone().into(), l1.clone(), l2.clone())),
         rtl::Instr::EMbBranch(op, r1, r2, l1, l2) => graph.insert_at_label(label, Instr::EMbBranch(op.clone(), r1.clone().into(), r2.clone().into(), l1.clone(), l2.clone())),
         rtl::Instr::ECall(r, name, args, l) => {
-            let args_count = args.len() as StackOffset;
-            let args_on_stack = if args_count <= 6 { 0 } else { args_count - 6 };
-            let args_in_registers = args_count - args_on_stack;
+            ertl_call(graph, label, r, args, l, |args_in_registers, l| Instr::ECall(name.clone(), args_in_registers, l))
+        }
+        rtl::Instr::ECallStar(r, callee, args, l) => {
+            ertl_call(graph, label, r, args, l, |args_in_registers, l| Instr::ECallStar(callee.clone().into(), args_in_registers, l))
+        }
+        rtl::Instr::EFunAddr(name, r, l) => graph.insert_at_label(label, Instr::EFunAddr(name.clone(), r.clone().into(), l.clone())),
+        rtl::Instr::EString(index, r, l) => graph.insert_at_label(label, Instr::EString(*index, r.clone().into(), l.clone())),
+        rtl::Instr::EGoto(l) => graph.insert_at_label(label, Instr::EGoto(l.clone())),
+        rtl::Instr::EStackAddr(aggregate, r, l) => {
+            let offset = frame.local(aggregate).ok_or(ErtlError::Any("Aggregate has no frame slot"))?;
+            graph.insert_at_label(label, Instr::EStackAddr(offset, r.clone().into(), l.clone()))
+        }
+    }
+    Ok(())
+}
 
-            let rsp_operation_lbl = if args_on_stack > 0 {
-                graph.insert(Instr::EMUnop(Munop::Maddi((args_on_stack * 8) as Value), Register::Physical(PhysicalRegister::Rsp), l.clone()))
-            } else {
-                l.clone()
-            };
+fn ertl_call<'a, F>(graph: &mut Graph<'a>, label: &Label, r: &rtl::register::PseudoRegister, args: &Vec<rtl::register::PseudoRegister>, l: &Label, call: F)
+    where F: FnOnce(StackOffset, Label) -> Instr<'a> {
+    let args_count = args.len() as StackOffset;
+    let args_on_stack = if args_count <= 6 { 0 } else { args_count - 6 };
+    let args_in_registers = args_count - args_on_stack;
+    // Keeps %rsp 16-bytes aligned at the call, as required by the System V ABI.
+    let padding = args_on_stack % 2;
+
+    let rsp_operation_lbl = if args_on_stack > 0 {
+        graph.insert(Instr::EMUnop(Munop::Maddi(((args_on_stack + padding) * 8) as Value), Register::Physical(PhysicalRegister::Rsp), l.clone()))
+    } else {
+        l.clone()
+    };
 
-            let result_lbl = graph.insert(Instr::EMBinop(Mbinop::MMov, Register::Physical(RESULT), r.clone().into(), rsp_operation_lbl));
+    let result_lbl = graph.insert(Instr::EMBinop(Mbinop::MMov, Register::Physical(RESULT), r.clone().into(), rsp_operation_lbl));
 
-            if args_count > 0 {
-                let call_lbl = graph.insert(Instr::ECall(name.clone(), args_in_registers, result_lbl.clone()));
+    let call_lbl = graph.insert(call(args_in_registers, result_lbl));
 
-                let mut next_arg_label = call_lbl;
+    // %al holds the number of vector registers used by a variadic call : always 0 here.
+    let vector_count_lbl = graph.insert(Instr::EConst(0, Register::Physical(PhysicalRegister::Rax), call_lbl));
 
-                for (index, arg) in enumerate(args) {
-                    if index >= 6 {
-                        next_arg_label = graph.insert(Instr::EPushParam(arg.clone().into(), next_arg_label.clone()));
-                    } else {
-                        next_arg_label = graph.insert(Instr::EMBinop(Mbinop::MMov, arg.clone().into(), Register::Physical(PARAMETERS[index].clone()), next_arg_label.clone()));
-                    }
-                }
-                graph.insert_at_label(label, Instr::EGoto(next_arg_label));
+    if args_count > 0 {
+        let mut next_arg_label = vector_count_lbl;
+
+        for (index, arg) in enumerate(args) {
+            if index >= 6 {
+                next_arg_label = graph.insert(Instr::EPushParam(arg.clone().into(), next_arg_label.clone()));
             } else {
-                graph.insert_at_label(label, Instr::ECall(name.clone(), args_in_registers, result_lbl))
+                next_arg_label = graph.insert(Instr::EMBinop(Mbinop::MMov, arg.clone().into(), Register::Physical(PARAMETERS[index].clone()), next_arg_label.clone()));
             }
         }
-        rtl::Instr::EGoto(l) => graph.insert_at_label(label, Instr::EGoto(l.clone()))
+        if padding > 0 {
+            next_arg_label = graph.insert(Instr::EMUnop(Munop::Maddi(-8), Register::Physical(PhysicalRegister::Rsp), next_arg_label));
+        }
+        graph.insert_at_label(label, Instr::EGoto(next_arg_label));
+    } else {
+        graph.insert_at_label(label, Instr::EGoto(vector_count_lbl))
     }
-    Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+use std::fmt::{Display, Formatter};
+use derive_new::new;
+use crate::common::{StackOffset, Value};
+use crate::rtl::structure::BlockIdent;
+use crate::rtl::structure::graph::DisplayableBlockIdent;
+
+const WORD_SIZE: StackOffset = 8;
+const FRAME_ALIGNMENT: StackOffset = 16;
+
+/// What lives in a frame slot : a stack-allocated local, or a pseudo-register
+/// spilled by the coloring, identified by its spill index.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Slot<'a> {
+    Local(BlockIdent<'a>),
+    Spilled(StackOffset),
+}
+
+#[derive(new, Debug, Clone)]
+pub struct FrameSlot<'a> {
+    pub slot: Slot<'a>,
+    pub offset: StackOffset,
+    pub size: StackOffset,
+}
+
+/// Layout of the part of the stack frame below the saved `%rbp`.
+/// Offsets are relative to `%rbp`, so they are all negative.
+#[derive(Debug, Clone, Default)]
+pub struct Frame<'a> {
+    pub slots: Vec<FrameSlot<'a>>,
+    used: StackOffset,
+}
+
+impl<'a> Frame<'a> {
+    fn alloc(&mut self, slot: Slot<'a>, size: StackOffset) -> StackOffset {
+        let size = (size + WORD_SIZE - 1) / WORD_SIZE * WORD_SIZE;
+        self.used += size;
+        let offset = -self.used;
+        self.slots.push(FrameSlot::new(slot, offset, size));
+        offset
+    }
+
+    pub fn alloc_local(&mut self, ident: BlockIdent<'a>, size: Value) -> StackOffset {
+        self.alloc(Slot::Local(ident), size as StackOffset)
+    }
+
+    pub fn alloc_spilled(&mut self, index: StackOffset) -> StackOffset {
+        self.alloc(Slot::Spilled(index), WORD_SIZE)
+    }
+
+    fn find(&self, slot: &Slot<'a>) -> Option<StackOffset> {
+        self.slots.iter().find(|x| &x.slot == slot).map(|x| x.offset)
+    }
+
+    pub fn local(&self, ident: &BlockIdent<'a>) -> Option<StackOffset> {
+        self.find(&Slot::Local(ident.clone()))
+    }
+
+    pub fn spilled(&self, index: StackOffset) -> Option<StackOffset> {
+        self.find(&Slot::Spilled(index))
+    }
+
+    /// Number of bytes to reserve below `%rbp`, keeping `%rsp` 16-bytes aligned.
+    pub fn size(&self) -> StackOffset {
+        (self.used + FRAME_ALIGNMENT - 1) / FRAME_ALIGNMENT * FRAME_ALIGNMENT
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+}
+
+impl Display for Slot<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Slot::Local(ident) => write!(f, "{}", DisplayableBlockIdent(ident)),
+            Slot::Spilled(index) => write!(f, "spilled {}", index),
+        }
+    }
+}
+
+impl Display for FrameSlot<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}(%rbp)[{}]", self.slot, self.offset, self.size)
+    }
+}
+
+impl Display for Frame<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} bytes [", self.size())?;
+        for slot in &self.slots {
+            write!(f, "{slot},")?;
+        }
+        write!(f, "]")
+    }
+}
@@ -1,11 +1,15 @@
 pub mod register;
+pub mod frame;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 use derive_new::new;
-use crate::common::{Ident, StackOffset, Value};
+use crate::common::{in_source_order, Ident, StackOffset, Value};
+use crate::ertl::structure::frame::Frame;
 use crate::ertl::structure::register::Register;
 use crate::rtl::structure::Fresh;
+use crate::session::Session;
 use crate::utils::DisplayableSet;
 
 pub type Label = crate::rtl::structure::label::Label;
@@ -24,24 +28,30 @@ pub enum Instr<'a> {
     EMuBranch(MuBranch, Register, Label, Label),
     EMbBranch(MbBranch, Register, Register, Label, Label),
     ECall(Ident<'a>, StackOffset, Label),
+    ECallStar(Register, StackOffset, Label),
+    EFunAddr(Ident<'a>, Register, Label),
+    EString(usize, Register, Label),
     EGoto(Label),
     EAllocFrame(Label),
     EDeleteFrame(Label),
     EGetParam(StackOffset, Register, Label),
     EPushParam(Register, Label),
+    EStackAddr(StackOffset, Register, Label),
     EReturn,
 }
 
 #[derive(new, Debug)]
 pub struct Graph<'a> {
-    pub instrs: HashMap<Label, Instr<'a>>,
+    pub instrs: BTreeMap<Label, Instr<'a>>,
+    pub session: Arc<Session>,
 }
 
 #[derive(new, Debug)]
 pub struct Fun<'a> {
     pub name: Ident<'a>,
     pub argument_count: StackOffset,
-    pub locals: HashSet<Register>,
+    pub locals: BTreeSet<Register>,
+    pub frame: Frame<'a>,
     pub entry: Label,
     pub body: Graph<'a>,
 }
@@ -49,6 +59,8 @@ pub struct Fun<'a> {
 #[derive(new, Debug)]
 pub struct File<'a> {
     pub funs: HashMap<Ident<'a>, Fun<'a>>,
+    pub strings: Vec<Ident<'a>>,
+    pub session: Arc<Session>,
 }
 
 #[derive(new)]
@@ -63,7 +75,7 @@ impl<'a> Graph<'a> {
     }
 
     pub fn insert(&mut self, instr: Instr<'a>) -> Label {
-        let label = Label::fresh();
+        let label = Label::fresh(&self.session);
         self.instrs.insert(label.clone(), instr);
         label
     }
@@ -72,7 +84,7 @@ impl<'a> Graph<'a> {
 impl Display for File<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "=== ERTL =================================================")?;
-        for (_ident, fun) in &self.funs {
+        for (_ident, fun) in in_source_order(&self.funs) {
             writeln!(f, "{}", fun)?;
         }
         Ok(())
@@ -84,6 +96,7 @@ impl Display for Fun<'_> {
         writeln!(f, "{}({})", self.name, self.argument_count)?;
         writeln!(f, "\tentry: {}", self.entry)?;
         writeln!(f, "\tlocals: {}", DisplayableSet(&self.locals))?;
+        writeln!(f, "\tframe: {}", self.frame)?;
         writeln!(f, "{}", DisplayableGraph::new(&self.body, &self.entry))
     }
 }
@@ -101,11 +114,15 @@ impl DisplayableGraph<'_> {
                 | Instr::EMUnop(_, _, l)
                 | Instr::EMBinop(_, _, _, l)
                 | Instr::ECall(_, _, l)
+                | Instr::ECallStar(_, _, l)
+                | Instr::EFunAddr(_, _, l)
+                | Instr::EString(_, _, l)
                 | Instr::EGoto(l)
                 | Instr::EAllocFrame(l)
                 | Instr::EDeleteFrame(l)
                 | Instr::EGetParam(_, _, l)
-                | Instr::EPushParam(_, l) => {
+                | Instr::EPushParam(_, l)
+                | Instr::EStackAddr(_, _, l) => {
                     self.visit(visited, &l, f)?;
                 }
                 Instr::EMuBranch(_, _, l1, l2)
@@ -139,11 +156,15 @@ impl Display for Instr<'_> {
             Instr::EMuBranch(op, r, l1, l2) => write!(f, "{} {} --> {}, {}", op, r, l1, l2),
             Instr::EMbBranch(op, r1, r2, l1, l2) => write!(f, "{} {} {} --> {}, {}", op, r1, r2, l1, l2),
             Instr::ECall(name, args, l) => write!(f, "call {}({}) --> {}", name, args, l),
+            Instr::ECallStar(r, args, l) => write!(f, "call *{}({}) --> {}", r, args, l),
+            Instr::EFunAddr(name, r, l) => write!(f, "fun_addr {} {} --> {}", name, r, l),
+            Instr::EString(index, r, l) => write!(f, "string {} {} --> {}", index, r, l),
             Instr::EGoto(l) => write!(f, "goto {}", l),
             Instr::EAllocFrame(l) => write!(f, "alloc_frame --> {}", l),
             Instr::EDeleteFrame(l) => write!(f, "delete_frame --> {}", l),
             Instr::EGetParam(o, r, l) => write!(f, "{} {} --> {}", o, r, l),
             Instr::EPushParam(r, l) => write!(f, "push {} --> {}", r, l),
+            Instr::EStackAddr(o, r, l) => write!(f, "lea {}(%rbp) {} --> {}", o, r, l),
             Instr::EReturn => write!(f, "return")
         }
     }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use crate::ertl::structure::{Instr, Mbinop};
 use crate::interference::structure::InterferenceGraph;
 use crate::liveness::structure::LivenessGraph;
@@ -10,7 +10,7 @@ pub type InterferenceError = ();
 pub type LivenessResult<T> = Result<T, InterferenceError>;
 
 pub fn interference_graph(graph: &LivenessGraph) -> LivenessResult<InterferenceGraph> {
-    let mut result = InterferenceGraph::new(HashMap::new());
+    let mut result = InterferenceGraph::new(BTreeMap::new());
 
     for (_, liveness_info) in &graph.infos {
         match liveness_info.instr {
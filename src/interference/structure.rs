@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{Display, Formatter};
 use derive_new::new;
 use crate::ertl::structure::register::Register;
@@ -6,13 +6,13 @@ use crate::utils::DisplayableSet;
 
 #[derive(Debug, new)]
 pub struct RegisterArc {
-    pub prefs: HashSet<Register>,
-    pub intfs: HashSet<Register>,
+    pub prefs: BTreeSet<Register>,
+    pub intfs: BTreeSet<Register>,
 }
 
 #[derive(Debug, new)]
 pub struct InterferenceGraph {
-    pub arcs: HashMap<Register, RegisterArc>,
+    pub arcs: BTreeMap<Register, RegisterArc>,
 }
 
 impl InterferenceGraph {
@@ -38,7 +38,7 @@ impl InterferenceGraph {
     fn pref_oriented(&mut self, r1: &Register, r2: &Register) {
         match self.arcs.get_mut(r1) {
             None => {
-                let arc = RegisterArc::new(HashSet::from([r2.clone()]), HashSet::new());
+                let arc = RegisterArc::new(BTreeSet::from([r2.clone()]), BTreeSet::new());
                 self.arcs.insert(r1.clone(), arc);
             }
             Some(arc) => {
@@ -50,7 +50,7 @@ impl InterferenceGraph {
     fn intf_oriented(&mut self, r1: &Register, r2: &Register) {
         match self.arcs.get_mut(r1) {
             None => {
-                let arc = RegisterArc::new(HashSet::new(), HashSet::from([r2.clone()]));
+                let arc = RegisterArc::new(BTreeSet::new(), BTreeSet::from([r2.clone()]));
                 self.arcs.insert(r1.clone(), arc);
             }
             Some(arc) => {
@@ -14,17 +14,35 @@ pub mod interference;
 pub mod liveness;
 pub mod coloring;
 pub mod linearise;
+pub mod warning;
+pub mod lsp;
+pub mod session;
 
-use crate::common::Stdout;
+use std::cell::RefCell;
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+use std::sync::Arc;
+use crate::common::{Execution, Stdin};
+use crate::common::profile::Profile;
 use crate::ertl::{ertl_file, ErtlResult};
+use crate::ertl::interpreter::{interp_ertl_file, interp_ertl_file_with, ErtlInterpreterResult};
 use crate::linearise::{linearise, LinearisingResult};
+use crate::linearise::emulator::{emulate, emulate_with, EmulatorResult};
 use crate::linearise::x86_64::Program;
 use crate::ltl::{ltl_file, LtlResult};
+use crate::ltl::interpreter::{interp_ltl_file, interp_ltl_file_with, LtlInterpreterResult};
 use crate::parser::{parse_file, ParserResult};
 use crate::rtl::{rtl_file, RtlResult};
-use crate::rtl::interpreter::{interp_rtl_file, RtlInterpreterResult};
+use crate::rtl::interpreter::{interp_rtl_file, interp_rtl_file_with, Limits, RtlInterpreterResult};
+use crate::rtl::interpreter::debugger::Debugger;
+use crate::rtl::interpreter::error::RtlInterpreterError;
+use crate::rtl::interpreter::tracer::Tracer as RtlTracer;
+use crate::rtl::uninit::uninitialised_reads;
+use crate::session::Session;
 use crate::typer::{typ_file, TypResult};
-use crate::typer::interpreter::{interp_typed_file, TyperInterpreterResult};
+use crate::typer::interpreter::{interp_typed_file, interp_typed_file_with, TyperInterpreterResult};
+use crate::typer::interpreter::tracer::Tracer as TyperTracer;
+use crate::warning::Warning;
 
 impl parser::structure::File<'_> {
     pub fn minic_typ(&self) -> TypResult<typer::structure::File> {
@@ -34,33 +52,98 @@ impl parser::structure::File<'_> {
 
 
 impl<'a> typer::structure::File<'a> {
+    /// Compiles to RTL in a new session, shared by the following stages.
     pub fn minic_rtl(&self) -> RtlResult<rtl::structure::File> {
-        rtl_file(self)
+        rtl_file(&Arc::new(Session::new()), self)
     }
 
-    pub fn minic_interp(&'a self) -> TyperInterpreterResult<Stdout> {
+    pub fn minic_interp(&'a self) -> TyperInterpreterResult<'a, Execution> {
         interp_typed_file(self)
     }
+
+    pub fn minic_interp_with(&'a self, stdin: Stdin) -> TyperInterpreterResult<'a, Execution> {
+        interp_typed_file_with(self, stdin, Rc::new(RefCell::new(())))
+    }
+
+    /// Runs the program on the input, writing each statement run to the trace if any, and profiles it even if it fails.
+    pub fn minic_trace<W: Write + 'a>(&'a self, stdin: Stdin, trace: Option<W>) -> (TyperInterpreterResult<'a, Execution>, Profile<'a>) {
+        let tracer = Rc::new(RefCell::new(TyperTracer::new(self, trace)));
+        let result = interp_typed_file_with(self, stdin, tracer.clone());
+        let profile = tracer.borrow().profile().clone();
+        (result, profile)
+    }
 }
 
-impl rtl::structure::File<'_> {
+impl<'a> rtl::structure::File<'a> {
     pub fn minic_ertl(&self) -> ErtlResult<ertl::structure::File> {
         ertl_file(self)
     }
 
-    pub fn minic_interp(&self) -> RtlInterpreterResult<Stdout> {
+    pub fn minic_interp(&self) -> RtlInterpreterResult<Execution> {
         interp_rtl_file(self)
     }
+
+    pub fn minic_interp_with(&'a self, stdin: Stdin, limits: Limits) -> RtlInterpreterResult<Execution> {
+        interp_rtl_file_with(self, stdin, limits, Rc::new(RefCell::new(())))
+    }
+
+    /// Runs the program on the input within the limits, writing each instruction run to the trace if any, and profiles it even if it fails.
+    pub fn minic_trace<W: Write + 'a>(&'a self, stdin: Stdin, limits: Limits, trace: Option<W>) -> (RtlInterpreterResult<Execution>, Profile<'a>) {
+        let tracer = Rc::new(RefCell::new(RtlTracer::new(trace)));
+        let result = interp_rtl_file_with(self, stdin, limits, tracer.clone());
+        let profile = tracer.borrow().profile().clone();
+        (result, profile)
+    }
+
+    /// Runs the program under the debugger, reading commands from the input.
+    pub fn minic_debug<R: BufRead, W: Write>(&self, input: R, output: W) -> RtlInterpreterResult<Execution> {
+        let debugger = Rc::new(RefCell::new(Debugger::new(self, input, output)));
+        let result = interp_rtl_file_with(self, Stdin::default(), Limits::default(), debugger.clone());
+        debugger.borrow_mut()
+            .finish(&result)
+            .map_err(|_| RtlInterpreterError::Other("Failed to talk with the debugger"))?;
+        result
+    }
+
+    pub fn minic_uninit(&self) -> Vec<Warning<'_>> {
+        uninitialised_reads(self)
+    }
 }
 
 impl ertl::structure::File<'_> {
     pub fn minic_ltl(&self) -> LtlResult<ltl::structure::File> {
         ltl_file(self)
     }
+
+    pub fn minic_interp(&self) -> ErtlInterpreterResult<Execution> {
+        interp_ertl_file(self)
+    }
+
+    pub fn minic_interp_with(&self, stdin: Stdin) -> ErtlInterpreterResult<Execution> {
+        interp_ertl_file_with(self, stdin)
+    }
 }
 
 impl ltl::structure::File<'_> {
     pub fn minic_linearise(&self) -> LinearisingResult<Program> {
         linearise(self)
     }
+
+    pub fn minic_interp(&self) -> LtlInterpreterResult<Execution> {
+        interp_ltl_file(self)
+    }
+
+    pub fn minic_interp_with(&self, stdin: Stdin) -> LtlInterpreterResult<Execution> {
+        interp_ltl_file_with(self, stdin)
+    }
+}
+
+impl Program<'_> {
+    pub fn minic_emulate(&self) -> EmulatorResult<Execution> {
+        emulate(self)
+    }
+
+    pub fn minic_emulate_with(&self, stdin: Stdin, max_steps: u64) -> EmulatorResult<Execution> {
+        emulate_with(self, stdin, max_steps)
+    }
 }
\ No newline at end of file
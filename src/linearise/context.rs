@@ -1,16 +1,20 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use derive_new::new;
+use crate::common::Ident;
 use crate::ertl::structure::Label;
 use crate::linearise::x86_64::{Asm, AsmNode};
+use crate::ltl::structure::Fun;
 
 #[derive(new)]
-pub struct Context<'a> {
+pub struct Context<'a, 'b> {
     pub code: Asm<'a>,
     pub visited: HashSet<Label>,
     pub labels: HashSet<Label>,
+    /// Functions of the program, the others are in the C library.
+    pub funs: &'b HashMap<Ident<'a>, Fun<'a>>,
 }
 
-impl<'a> Context<'a> {
+impl<'a> Context<'a, '_> {
     pub fn emit_at_label(&mut self, label: Label, node: AsmNode<'a>) {
         self.code.nodes.push(AsmNode::Label(label));
         self.code.nodes.push(node);
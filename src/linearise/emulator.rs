@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+use crate::common::{Execution, Ident, MAIN, STRING_ADDRESS_BASE, Stdin, Value};
+use crate::ertl::interpreter::ErtlInterpreterResult;
+use crate::ertl::interpreter::error::ErtlInterpreterError;
+use crate::ertl::interpreter::machine::Machine;
+use crate::ertl::structure::Label;
+use crate::ertl::structure::register::{PhysicalRegister, RESULT};
+use crate::linearise::x86_64::{AsmNode, Program, Section, Size, SizedPhysicalRegister, X86Operand};
+
+/// Runs on the same machine as the ERTL and LTL interpreters.
+pub type EmulatorResult<T> = ErtlInterpreterResult<T>;
+
+/// Address of the instruction at index `0` of the text section.
+const CODE_BASE: Value = 1 << 36;
+/// Return address of `main`, popped when the program ends.
+const EXIT_ADDRESS: Value = CODE_BASE - 8;
+/// Instructions run by default before giving up on a program which doesn't terminate.
+pub const DEFAULT_MAX_STEPS: u64 = 1 << 30;
+
+/// Condition codes set by the last arithmetic instruction.
+#[derive(Default)]
+struct Flags {
+    zero: bool,
+    sign: bool,
+    overflow: bool,
+    carry: bool,
+}
+
+/// Emulates the subset of x86-64 the compiler emits, with the C library functions run by the host.
+pub fn emulate(program: &Program) -> EmulatorResult<Execution> {
+    emulate_with(program, Stdin::default(), DEFAULT_MAX_STEPS)
+}
+
+/// Runs the program on the input, for at most `max_steps` instructions.
+pub fn emulate_with(program: &Program, stdin: Stdin, max_steps: u64) -> EmulatorResult<Execution> {
+    let mut code = vec![];
+    let mut labels = HashMap::new();
+    let mut funs = HashMap::new();
+
+    for section in &program.sections {
+        if let Section::Text(asm) = section {
+            for node in &asm.nodes {
+                match node {
+                    AsmNode::Label(label) => {
+                        labels.insert(label.clone(), code.len());
+                    }
+                    AsmNode::DeclFun(name) => {
+                        funs.insert(*name, code.len());
+                    }
+                    AsmNode::Globl(_) | AsmNode::Comment(_) | AsmNode::Align(_) => {}
+                    node => code.push(node),
+                }
+            }
+        }
+    }
+
+    let strings = program.sections
+        .iter()
+        .flat_map(|section| match section {
+            Section::Data(asm) => asm.nodes.iter().filter_map(|node| match node {
+                AsmNode::String(string) => Some(*string),
+                _ => None,
+            }).collect(),
+            Section::Text(_) => vec![],
+        })
+        .collect::<Vec<_>>();
+
+    let mut emulator = Emulator {
+        machine: Machine::new(funs.keys().cloned(), &strings, stdin),
+        code,
+        labels,
+        funs,
+        flags: Flags::default(),
+    };
+    emulator.run(max_steps)?;
+
+    Ok(Execution { status: emulator.machine.get(&RESULT), stdout: emulator.machine.stdout })
+}
+
+struct Emulator<'a, 'b> {
+    machine: Machine<'a>,
+    code: Vec<&'b AsmNode<'a>>,
+    labels: HashMap<Label, usize>,
+    funs: HashMap<Ident<'a>, usize>,
+    flags: Flags,
+}
+
+impl<'a> Emulator<'a, '_> {
+    fn run(&mut self, max_steps: u64) -> EmulatorResult<()> {
+        let mut pc = *self.funs.get(MAIN).ok_or(ErtlInterpreterError::FunctionDoesNotExist(String::from(MAIN)))?;
+        self.machine.push(EXIT_ADDRESS)?;
+
+        for _ in 0..max_steps {
+            let node = self.code.get(pc).ok_or(ErtlInterpreterError::Other("Execution left the code"))?;
+            pc = match self.step(node, pc + 1)? {
+                Some(pc) => pc,
+                None => return Ok(()),
+            };
+        }
+
+        Err(ErtlInterpreterError::TooManySteps(max_steps))
+    }
+
+    fn jump(&self, label: &Label) -> EmulatorResult<usize> {
+        self.labels.get(label).cloned().ok_or(ErtlInterpreterError::NoSuchInstruction(label.clone()))
+    }
+
+    fn call(&mut self, name: Ident<'a>, next: usize) -> EmulatorResult<Option<usize>> {
+        if Machine::is_library(name) {
+            self.machine.call_library(name)?;
+            return Ok(Some(next));
+        }
+
+        let target = *self.funs.get(name).ok_or(ErtlInterpreterError::FunctionDoesNotExist(String::from(name)))?;
+        self.machine.push(CODE_BASE + next as Value)?;
+        Ok(Some(target))
+    }
+
+    /// Runs the instruction, and gives the index of the next one, `None` once `main` returns.
+    fn step(&mut self, node: &AsmNode<'a>, next: usize) -> EmulatorResult<Option<usize>> {
+        match node {
+            AsmNode::Mov(size, from, to) => {
+                let value = self.read(from)?;
+                self.write(to, size, value)?;
+            }
+            AsmNode::Movz(from_size, size, from, to) => {
+                let value = truncate(self.read(from)?, from_size, false);
+                self.write(to, size, value)?;
+            }
+            AsmNode::Movs(from_size, size, from, to) => {
+                let value = truncate(self.read(from)?, from_size, true);
+                self.write(to, size, value)?;
+            }
+            AsmNode::Lea(size, from, to) => {
+                let address = self.address(from)?;
+                self.write(to, size, address)?;
+            }
+            AsmNode::LeaRip(name, to) | AsmNode::MovGot(name, to) => {
+                let address = self.machine.fun_address(name)?;
+                self.write(to, &Size::Q, address)?;
+            }
+            AsmNode::LeaString(index, to) => self.write(to, &Size::Q, STRING_ADDRESS_BASE + *index as Value)?,
+            AsmNode::Inc(size, operand) => self.arithmetic(size, operand, 1, Value::overflowing_add)?,
+            AsmNode::Dec(size, operand) => self.arithmetic(size, operand, 1, Value::overflowing_sub)?,
+            AsmNode::Neg(size, operand) => {
+                let value = self.read(operand)?;
+                self.arithmetic(size, operand, value, |_, value| (0 as Value).overflowing_sub(value))?
+            }
+            AsmNode::Not(size, operand) => {
+                let value = self.read(operand)?;
+                self.write(operand, size, !value)?;
+            }
+            AsmNode::Add(size, from, to) => self.arithmetic(size, to, self.read(from)?, Value::overflowing_add)?,
+            AsmNode::Sub(size, from, to) => self.arithmetic(size, to, self.read(from)?, Value::overflowing_sub)?,
+            AsmNode::Imul(size, from, to) => self.arithmetic(size, to, self.read(from)?, Value::overflowing_mul)?,
+            AsmNode::And(size, from, to) => self.logic(size, to, self.read(from)?, |x, y| x & y)?,
+            AsmNode::Or(size, from, to) => self.logic(size, to, self.read(from)?, |x, y| x | y)?,
+            AsmNode::Xor(size, from, to) => self.logic(size, to, self.read(from)?, |x, y| x ^ y)?,
+            AsmNode::Shl(size, from, to) => self.logic(size, to, self.read(from)?, |x, y| x.wrapping_shl(y as u32))?,
+            AsmNode::Shr(size, from, to) => self.logic(size, to, self.read(from)?, |x, y| ((x as u64) >> (y & 63)) as Value)?,
+            AsmNode::Sar(size, from, to) => self.logic(size, to, self.read(from)?, |x, y| x >> (y & 63))?,
+            AsmNode::Cltq => {
+                let rax = self.machine.get(&PhysicalRegister::Rax);
+                self.machine.set(&PhysicalRegister::Rax, rax as i32 as Value);
+            }
+            AsmNode::Cqto => {
+                let rax = self.machine.get(&PhysicalRegister::Rax);
+                self.machine.set(&PhysicalRegister::Rdx, if rax < 0 { -1 } else { 0 });
+            }
+            AsmNode::IDivq(divisor) => {
+                let divisor = self.read(divisor)? as i128;
+                let dividend = ((self.machine.get(&PhysicalRegister::Rdx) as i128) << 64)
+                    | self.machine.get(&PhysicalRegister::Rax) as u64 as i128;
+                if divisor == 0 {
+                    return Err(ErtlInterpreterError::DivisionByZero);
+                }
+                let quotient = dividend.checked_div(divisor)
+                    .and_then(|quotient| Value::try_from(quotient).ok())
+                    .ok_or(ErtlInterpreterError::DivisionOverflow)?;
+                self.machine.set(&PhysicalRegister::Rax, quotient);
+                self.machine.set(&PhysicalRegister::Rdx, (dividend % divisor) as Value);
+            }
+            AsmNode::Cmp(size, from, to) => {
+                let (value_1, value_2) = (self.read(from)?, self.read(to)?);
+                self.compare(size, value_2, value_1);
+            }
+            AsmNode::Test(size, from, to) => {
+                let value = truncate(self.read(from)? & self.read(to)?, size, true);
+                self.flags = Flags { zero: value == 0, sign: value < 0, overflow: false, carry: false };
+            }
+            AsmNode::Sete(register) => self.set_condition(register, self.flags.zero),
+            AsmNode::Setne(register) => self.set_condition(register, !self.flags.zero),
+            AsmNode::Sets(register) => self.set_condition(register, self.flags.sign),
+            AsmNode::Setns(register) => self.set_condition(register, !self.flags.sign),
+            AsmNode::Setg(register) => self.set_condition(register, self.greater()),
+            AsmNode::Setge(register) => self.set_condition(register, self.flags.sign == self.flags.overflow),
+            AsmNode::Setl(register) => self.set_condition(register, self.flags.sign != self.flags.overflow),
+            AsmNode::Setle(register) => self.set_condition(register, !self.greater()),
+            AsmNode::Seta(register) => self.set_condition(register, !self.flags.carry && !self.flags.zero),
+            AsmNode::Setae(register) => self.set_condition(register, !self.flags.carry),
+            AsmNode::Setb(register) => self.set_condition(register, self.flags.carry),
+            AsmNode::Setbe(register) => self.set_condition(register, self.flags.carry || self.flags.zero),
+            AsmNode::Jmp(label) => return self.jump(label).map(Some),
+            AsmNode::Je(label) | AsmNode::Jz(label) => return self.branch(label, self.flags.zero, next),
+            AsmNode::Jne(label) | AsmNode::Jnz(label) => return self.branch(label, !self.flags.zero, next),
+            AsmNode::Js(label) => return self.branch(label, self.flags.sign, next),
+            AsmNode::Jns(label) => return self.branch(label, !self.flags.sign, next),
+            AsmNode::Jg(label) => return self.branch(label, self.greater(), next),
+            AsmNode::Jge(label) => return self.branch(label, self.flags.sign == self.flags.overflow, next),
+            AsmNode::Jl(label) => return self.branch(label, self.flags.sign != self.flags.overflow, next),
+            AsmNode::Jle(label) => return self.branch(label, !self.greater(), next),
+            AsmNode::Ja(label) => return self.branch(label, !self.flags.carry && !self.flags.zero, next),
+            AsmNode::Jae(label) => return self.branch(label, !self.flags.carry, next),
+            AsmNode::Jb(label) => return self.branch(label, self.flags.carry, next),
+            AsmNode::Jbe(label) => return self.branch(label, self.flags.carry || self.flags.zero, next),
+            AsmNode::JmpStar(_) => return Err(ErtlInterpreterError::Other("Unsupported indirect jump")),
+            AsmNode::Call(name) => return self.call(name, next),
+            AsmNode::CallStar(callee) => {
+                let name = self.machine.fun_at(self.read(callee)?)?;
+                return self.call(name, next);
+            }
+            AsmNode::Pushq(operand) => {
+                let value = self.read(operand)?;
+                self.machine.push(value)?;
+            }
+            AsmNode::Popq(operand) => {
+                let value = self.machine.pop()?;
+                self.write(operand, &Size::Q, value)?;
+            }
+            AsmNode::Leave => {
+                self.machine.set(&PhysicalRegister::Rsp, self.machine.get(&PhysicalRegister::Rbp));
+                let rbp = self.machine.pop()?;
+                self.machine.set(&PhysicalRegister::Rbp, rbp);
+            }
+            AsmNode::Ret => {
+                return match self.machine.pop()? {
+                    EXIT_ADDRESS => Ok(None),
+                    address => usize::try_from(address - CODE_BASE)
+                        .map(Some)
+                        .map_err(|_| ErtlInterpreterError::InvalidReturnAddress(address)),
+                };
+            }
+            AsmNode::DByte(_)
+            | AsmNode::DInt(_)
+            | AsmNode::DWord(_)
+            | AsmNode::DQuad(_)
+            | AsmNode::String(_)
+            | AsmNode::Address(_)
+            | AsmNode::Space(_)
+            | AsmNode::DeclString(_) => return Err(ErtlInterpreterError::Other("Data in the text section")),
+            AsmNode::Label(_)
+            | AsmNode::DeclFun(_)
+            | AsmNode::Globl(_)
+            | AsmNode::Comment(_)
+            | AsmNode::Align(_) => {}
+        }
+
+        Ok(Some(next))
+    }
+
+    fn address(&self, operand: &X86Operand) -> EmulatorResult<Value> {
+        match operand {
+            X86Operand::Offset(offset, base) => Ok(self.machine.get(base) + *offset as Value),
+            X86Operand::OffsetScale(offset, base, index, scale) => {
+                Ok(self.machine.get(base) + self.machine.get(index) * *scale as Value + *offset as Value)
+            }
+            X86Operand::Constant(_) | X86Operand::Register(_) => Err(ErtlInterpreterError::Other("Operand has no address")),
+        }
+    }
+
+    fn read(&self, operand: &X86Operand) -> EmulatorResult<Value> {
+        match operand {
+            X86Operand::Constant(value) => Ok(*value),
+            X86Operand::Register(register) => Ok(self.machine.get(register)),
+            operand => self.machine.load(self.address(operand)?),
+        }
+    }
+
+    /// Writes the low `size` bytes of `value` : a 32 bits write clears the upper half of a
+    /// register, smaller ones keep it.
+    fn write(&mut self, operand: &X86Operand, size: &Size, value: Value) -> EmulatorResult<()> {
+        match operand {
+            X86Operand::Constant(_) => Err(ErtlInterpreterError::Other("Write to a constant")),
+            X86Operand::Register(register) => {
+                let value = match size {
+                    Size::Q => value,
+                    Size::L => value as u32 as Value,
+                    size => {
+                        let mask = mask(size);
+                        (self.machine.get(register) & !mask) | (value & mask)
+                    }
+                };
+                self.machine.set(register, value);
+                Ok(())
+            }
+            operand => {
+                let address = self.address(operand)?;
+                let value = match size {
+                    Size::Q => value,
+                    size => (self.machine.load(address)? & !mask(size)) | (value & mask(size)),
+                };
+                self.machine.store(address, value)
+            }
+        }
+    }
+
+    fn arithmetic<F>(&mut self, size: &Size, operand: &X86Operand, value: Value, op: F) -> EmulatorResult<()>
+        where F: Fn(Value, Value) -> (Value, bool) {
+        let current = self.read(operand)?;
+        let (result, overflow) = op(current, value);
+        self.flags = Flags { zero: result == 0, sign: result < 0, overflow, carry: false };
+        self.write(operand, size, result)
+    }
+
+    fn logic<F: Fn(Value, Value) -> Value>(&mut self, size: &Size, operand: &X86Operand, value: Value, op: F) -> EmulatorResult<()> {
+        let result = op(self.read(operand)?, value);
+        self.flags = Flags { zero: result == 0, sign: result < 0, overflow: false, carry: false };
+        self.write(operand, size, result)
+    }
+
+    /// Flags of `value_1 - value_2`, compared as `size` bytes integers.
+    fn compare(&mut self, size: &Size, value_1: Value, value_2: Value) {
+        let (value_1, value_2) = (truncate(value_1, size, true), truncate(value_2, size, true));
+        let (result, overflow) = value_1.overflowing_sub(value_2);
+        let (result, overflow) = match size {
+            Size::Q => (result, overflow),
+            size => (truncate(result, size, true), truncate(result, size, true) != result),
+        };
+        let carry = truncate(value_1, size, false) < truncate(value_2, size, false);
+        self.flags = Flags { zero: result == 0, sign: result < 0, overflow, carry };
+    }
+
+    fn greater(&self) -> bool {
+        !self.flags.zero && self.flags.sign == self.flags.overflow
+    }
+
+    fn set_condition(&mut self, register: &SizedPhysicalRegister, condition: bool) {
+        let operand = X86Operand::Register(register.register.clone());
+        self.write(&operand, &register.size, condition as Value).expect("Registers can be written");
+    }
+
+    fn branch(&self, label: &Label, condition: bool, next: usize) -> EmulatorResult<Option<usize>> {
+        if condition { self.jump(label).map(Some) } else { Ok(Some(next)) }
+    }
+}
+
+fn mask(size: &Size) -> Value {
+    match size {
+        Size::B => 0xff,
+        Size::W => 0xffff,
+        Size::L => 0xffff_ffff,
+        Size::Q => -1,
+    }
+}
+
+/// The low `size` bytes of `value`, sign or zero extended.
+fn truncate(value: Value, size: &Size, signed: bool) -> Value {
+    match (size, signed) {
+        (Size::B, true) => value as i8 as Value,
+        (Size::B, false) => value as u8 as Value,
+        (Size::W, true) => value as i16 as Value,
+        (Size::W, false) => value as u16 as Value,
+        (Size::L, true) => value as i32 as Value,
+        (Size::L, false) => value as u32 as Value,
+        (Size::Q, _) => value,
+    }
+}
@@ -1,17 +1,24 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use rayon::prelude::*;
+use crate::common::{in_source_order, Ident, GETCHAR};
 use crate::ertl::structure::{Label, Mbinop, MuBranch, Munop};
 use crate::ertl::structure::register::{PhysicalRegister, TMP_1, TMP_2};
 use crate::linearise::context::Context;
 use crate::linearise::error::LinearisingError;
 use crate::linearise::x86_64::{Asm, AsmNode, Program, Section, Size, SizedPhysicalRegister, X86Operand};
-use crate::ltl::structure::{File, Graph, Instr, Operand};
+use crate::ltl::structure::{File, Fun, Graph, Instr, Operand};
 
 pub mod x86_64;
 pub mod error;
+pub mod emulator;
 mod context;
 
 pub type LinearisingResult<T> = Result<T, LinearisingError>;
 
+/// Stands for `getchar` when its address is taken : a call through a pointer doesn't know it must
+/// sign extend the 32 bits `int` returned.
+const GETCHAR_STUB: &str = "minic.getchar";
+
 pub fn linearise<'a>(file: &File<'a>) -> LinearisingResult<Program<'a>> {
     Ok(Program::new(vec![
         Section::Text(text(file)?),
@@ -20,16 +27,46 @@ pub fn linearise<'a>(file: &File<'a>) -> LinearisingResult<Program<'a>> {
 }
 
 fn text<'a>(file: &File<'a>) -> LinearisingResult<Asm<'a>> {
-    let mut context = Context::new(Asm::new(Vec::new()), HashSet::new(), HashSet::new());
+    let funs = in_source_order(&file.funs)
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|(name, fun)| text_fun(name, fun, &file.funs))
+        .collect::<LinearisingResult<Vec<_>>>()?;
+
+    let mut nodes = vec![AsmNode::Globl("main")];
 
-    for (name, fun) in &file.funs {
-        context.emit(AsmNode::DeclFun(name.clone()));
-        lin(&mut context, &fun.body, &fun.entry)?;
+    for fun in funs {
+        nodes.extend(fun);
     }
 
-    let mut nodes = vec![AsmNode::Globl("main")];
+    if nodes.iter().any(|node| matches!(node, AsmNode::LeaRip(name, _) if *name == GETCHAR_STUB)) {
+        nodes.extend(getchar_stub());
+    }
+
+    Ok(Asm::new(nodes))
+}
+
+/// Calls `getchar` and sign extends its result, as a direct call does.
+fn getchar_stub<'a>() -> Vec<AsmNode<'a>> {
+    vec![
+        AsmNode::DeclFun(GETCHAR_STUB),
+        // Realigns the stack on 16 bytes for the call
+        AsmNode::Pushq(PhysicalRegister::Rbp.into()),
+        AsmNode::Call(GETCHAR),
+        AsmNode::Cltq,
+        AsmNode::Popq(PhysicalRegister::Rbp.into()),
+        AsmNode::Ret,
+    ]
+}
+
+/// Code of a function. Jumps don't leave a function, so its labels are only needed inside it.
+fn text_fun<'a>(name: Ident<'a>, fun: &Fun<'a>, funs: &HashMap<Ident<'a>, Fun<'a>>) -> LinearisingResult<Vec<AsmNode<'a>>> {
+    let mut context = Context::new(Asm::new(Vec::new()), HashSet::new(), HashSet::new(), funs);
 
-    nodes.extend(context.code.nodes
+    context.emit(AsmNode::DeclFun(name));
+    lin(&mut context, &fun.body, &fun.entry)?;
+
+    Ok(context.code.nodes
         .into_iter()
         .filter(|l| {
             match l {
@@ -38,12 +75,11 @@ fn text<'a>(file: &File<'a>) -> LinearisingResult<Asm<'a>> {
                 }
                 _ => true
             }
-        }));
-
-    Ok(Asm::new(nodes))
+        })
+        .collect())
 }
 
-fn lin<'a>(context: &mut Context<'a>, graph: &Graph<'a>, label: &Label) -> LinearisingResult<()> {
+fn lin<'a>(context: &mut Context<'a, '_>, graph: &Graph<'a>, label: &Label) -> LinearisingResult<()> {
     if !context.visited.contains(label) {
         context.visited.insert(label.clone());
         instr(context, graph, label, graph.instrs.get(label).expect("Instr not found for lbl"))
@@ -54,7 +90,7 @@ fn lin<'a>(context: &mut Context<'a>, graph: &Graph<'a>, label: &Label) -> Linea
     }
 }
 
-fn instr<'a>(context: &mut Context<'a>, graph: &Graph<'a>, label: &Label, instr: &Instr<'a>) -> LinearisingResult<()> {
+fn instr<'a>(context: &mut Context<'a, '_>, graph: &Graph<'a>, label: &Label, instr: &Instr<'a>) -> LinearisingResult<()> {
     match instr {
         Instr::ELoad(from, o, dest, l) => {
             context.emit_at_label(label.clone(), AsmNode::Mov(Size::Q, X86Operand::Offset(*o, from.clone()), dest.clone().into()));
@@ -110,6 +146,8 @@ fn instr<'a>(context: &mut Context<'a>, graph: &Graph<'a>, label: &Label, instr:
                         }
                         _ => unreachable!()
                     };
+                    // `setcc` only writes the low byte of the register
+                    context.emit(AsmNode::And(Size::Q, 1.into(), r_or_reg.clone().into()));
                     match r {
                         Operand::Register(_) => {}
                         Operand::Spilled(_) => {
@@ -124,7 +162,7 @@ fn instr<'a>(context: &mut Context<'a>, graph: &Graph<'a>, label: &Label, instr:
             let (r1, label):(X86Operand, Option<Label>) = match (r1, r2) {
                 (Operand::Spilled(_), Operand::Spilled(_)) => {
                     let r = TMP_1;
-                    context.emit(AsmNode::Mov(Size::Q, r1.clone().into(), X86Operand::Register(r.clone())));
+                    context.emit_at_label(label.clone(), AsmNode::Mov(Size::Q, r1.clone().into(), X86Operand::Register(r.clone())));
                     (X86Operand::Register(r), None)
                 }
                 _ => {
@@ -204,6 +242,8 @@ fn instr<'a>(context: &mut Context<'a>, graph: &Graph<'a>, label: &Label, instr:
                         }
                         _ => unreachable!()
                     };
+                    // `setcc` only writes the low byte of the register
+                    context.emit(AsmNode::And(Size::Q, 1.into(), r_or_reg.clone().into()));
                     match r2 {
                         Operand::Register(_) => {}
                         Operand::Spilled(_) => {
@@ -286,6 +326,60 @@ fn instr<'a>(context: &mut Context<'a>, graph: &Graph<'a>, label: &Label, instr:
         }
         Instr::ECall(i, l) => {
             context.emit_at_label(label.clone(), AsmNode::Call(i.clone()));
+            // `getchar` returns a 32 bits `int`, and `EOF` must stay negative
+            if *i == GETCHAR {
+                context.emit(AsmNode::Cltq);
+            }
+            lin(context, graph, l)
+        }
+        Instr::ECallStar(callee, l) => {
+            context.emit_at_label(label.clone(), AsmNode::CallStar(callee.clone().into()));
+            lin(context, graph, l)
+        }
+        Instr::EFunAddr(name, r, l) => {
+            let address = |to: X86Operand| if *name == GETCHAR {
+                AsmNode::LeaRip(GETCHAR_STUB, to)
+            } else if context.funs.contains_key(name) {
+                AsmNode::LeaRip(name, to)
+            } else {
+                // The C library may be loaded too far for a rip-relative address in a PIE
+                AsmNode::MovGot(name, to)
+            };
+            match r {
+                Operand::Register(r) => {
+                    let node = address(r.clone().into());
+                    context.emit_at_label(label.clone(), node);
+                }
+                Operand::Spilled(_) => {
+                    let node = address(TMP_1.into());
+                    context.emit_at_label(label.clone(), node);
+                    context.emit(AsmNode::Mov(Size::Q, TMP_1.into(), r.clone().into()));
+                }
+            }
+            lin(context, graph, l)
+        }
+        Instr::EString(index, r, l) => {
+            match r {
+                Operand::Register(r) => {
+                    context.emit_at_label(label.clone(), AsmNode::LeaString(*index, r.clone().into()));
+                }
+                Operand::Spilled(_) => {
+                    context.emit_at_label(label.clone(), AsmNode::LeaString(*index, TMP_1.into()));
+                    context.emit(AsmNode::Mov(Size::Q, TMP_1.into(), r.clone().into()));
+                }
+            }
+            lin(context, graph, l)
+        }
+        Instr::EStackAddr(o, r, l) => {
+            match r {
+                Operand::Register(r) => {
+                    context.emit_at_label(label.clone(), AsmNode::Lea(Size::Q, X86Operand::Offset(*o, PhysicalRegister::Rbp), r.clone().into()));
+                }
+                Operand::Spilled(_) => {
+                    context.emit_at_label(label.clone(), AsmNode::Lea(Size::Q, X86Operand::Offset(*o, PhysicalRegister::Rbp), TMP_1.into()));
+                    context.emit(AsmNode::Mov(Size::Q, TMP_1.into(), r.clone().into()));
+                }
+            }
             lin(context, graph, l)
         }
         Instr::EPop(o, l) => {
@@ -295,6 +389,13 @@ fn instr<'a>(context: &mut Context<'a>, graph: &Graph<'a>, label: &Label, instr:
     }
 }
 
-fn data<'a>(_file: &File<'a>) -> LinearisingResult<Asm<'a>> {
-    Ok(Asm::new(Vec::new()))
+fn data<'a>(file: &File<'a>) -> LinearisingResult<Asm<'a>> {
+    let mut nodes = Vec::new();
+
+    for (index, string) in file.strings.iter().enumerate() {
+        nodes.push(AsmNode::DeclString(index));
+        nodes.push(AsmNode::String(string));
+    }
+
+    Ok(Asm::new(nodes))
 }
\ No newline at end of file
@@ -49,6 +49,10 @@ pub enum AsmNode<'a> {
     Movz(Size, Size, X86Operand, X86Operand),
     Movs(Size, Size, X86Operand, X86Operand),
     Lea(Size, X86Operand, X86Operand),
+    LeaRip(Ident<'a>, X86Operand),
+    /// Loads the address of a function from the global offset table.
+    MovGot(Ident<'a>, X86Operand),
+    LeaString(usize, X86Operand),
     Inc(Size, X86Operand),
     Dec(Size, X86Operand),
     Neg(Size, X86Operand),
@@ -57,6 +61,8 @@ pub enum AsmNode<'a> {
     Imul(Size, X86Operand, X86Operand),
     IDivq(X86Operand),
     Cqto,
+    /// Sign extends `%eax` into `%rax`.
+    Cltq,
     Not(Size, X86Operand),
     And(Size, X86Operand, X86Operand),
     Or(Size, X86Operand, X86Operand),
@@ -110,6 +116,7 @@ pub enum AsmNode<'a> {
     Space(u32),
     Label(Label),
     DeclFun(Ident<'a>),
+    DeclString(usize),
     Globl(Ident<'a>),
     Comment(String),
 }
@@ -149,7 +156,7 @@ impl Into<X86Operand> for Operand {
     fn into(self) -> X86Operand {
         match self {
             Operand::Register(r) => X86Operand::Register(r),
-            Operand::Spilled(o) => X86Operand::Offset(-8 * (o + 1), PhysicalRegister::Rbp)
+            Operand::Spilled(o) => X86Operand::Offset(o, PhysicalRegister::Rbp)
         }
     }
 }
@@ -194,6 +201,9 @@ impl Display for AsmNode<'_> {
             AsmNode::Movz(s1, s2, f1, f2) => write!(f, "\tmovz{}{} {}, {}", s1, s2, f1, f2),
             AsmNode::Movs(s1, s2, f1, f2) => write!(f, "\tmovs{}{} {}, {}", s1, s2, f1, f2),
             AsmNode::Lea(s, f1, f2) => write!(f, "\tlea{} {}, {}", s, f1, f2),
+            AsmNode::LeaRip(l, f1) => write!(f, "\tleaq {}(%rip), {}", mangle(l), f1),
+            AsmNode::MovGot(l, f1) => write!(f, "\tmovq {}@GOTPCREL(%rip), {}", mangle(l), f1),
+            AsmNode::LeaString(i, f1) => write!(f, "\tleaq .LS{}(%rip), {}", i, f1),
             AsmNode::And(s, f1, f2) => write!(f, "\tand{} {}, {}", s, f1, f2),
             AsmNode::Or(s, f1, f2) => write!(f, "\tor{} {}, {}", s, f1, f2),
             AsmNode::Xor(s, f1, f2) => write!(f, "\txor{} {}, {}", s, f1, f2),
@@ -211,6 +221,7 @@ impl Display for AsmNode<'_> {
             AsmNode::Not(s, f1) => write!(f, "\tnot{} {}", s, f1),
             AsmNode::IDivq(f1) => write!(f, "\tidivq {}", f1),
             AsmNode::Cqto => write!(f, "\tcqto"),
+            AsmNode::Cltq => write!(f, "\tcltq"),
             AsmNode::Jmp(l) => write!(f, "\tjmp {}", l),
             AsmNode::JmpStar(l) => write!(f, "\tjmp *{}", l),
             AsmNode::Call(c) => write!(f, "\tcall {}", mangle(c)),
@@ -248,13 +259,14 @@ impl Display for AsmNode<'_> {
             AsmNode::DInt(v) => write!(f, "\t.int {}", CommaSeparatedVec(v)),
             AsmNode::DWord(v) => write!(f, "\t.word {}", CommaSeparatedVec(v)),
             AsmNode::DQuad(v) => write!(f, "\t.quad {}", CommaSeparatedVec(v)),
-            AsmNode::String(f1) => write!(f, "\t.string {}", f1),
+            AsmNode::String(f1) => write!(f, "\t.string \"{}\"", f1),
             AsmNode::Address(v) => write!(f, "\t.quad {}", CommaSeparatedVec(v)),
             AsmNode::Space(f1) => write!(f, "\t.space {}", f1),
             AsmNode::Pushq(f1) => write!(f, "\tpushq {}", f1),
             AsmNode::Popq(f1) => write!(f, "\tpopq {}", f1),
             AsmNode::Label(l) => write!(f, "{}:", mangle(l)),
             AsmNode::DeclFun(d) => write!(f, "{}:", d),
+            AsmNode::DeclString(i) => write!(f, ".LS{}:", i),
             AsmNode::Globl(d) => write!(f, "\t.globl {}", mangle(d)),
             AsmNode::Comment(c) => write!(f, "#{}", c),
         }
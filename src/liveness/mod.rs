@@ -1,4 +1,4 @@
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use crate::ertl::structure::{Graph, Instr, Label, Mbinop};
 use crate::ertl::structure::register::{CALLER_SAVED, PARAMETERS, PhysicalRegister, Register};
 use crate::liveness::error::LivenessError;
@@ -20,7 +20,7 @@ pub fn liveness_graph<'a>(graph: &'a Graph<'a>) -> LivenessResult<LivenessGraph<
     let mut pred_map = HashMap::new();
 
     for (label, _) in &graph.instrs {
-        pred_map.insert(label.clone(), HashSet::new());
+        pred_map.insert(label.clone(), BTreeSet::new());
     }
 
     for (label, succ) in &succ_map {
@@ -37,9 +37,9 @@ pub fn liveness_graph<'a>(graph: &'a Graph<'a>) -> LivenessResult<LivenessGraph<
 
     let mut outs_map: HashMap<Label, Vec<Register>> = HashMap::new();
 
-    let mut ins_map: HashMap<Label, HashSet<Register>> = HashMap::new();
+    let mut ins_map: HashMap<Label, BTreeSet<Register>> = HashMap::new();
     for (label, _) in &graph.instrs {
-        ins_map.insert(label.clone(), HashSet::new());
+        ins_map.insert(label.clone(), BTreeSet::new());
     }
 
     let mut to_handle: BTreeSet<&Label> = graph.instrs.keys().collect();
@@ -55,7 +55,7 @@ pub fn liveness_graph<'a>(graph: &'a Graph<'a>) -> LivenessResult<LivenessGraph<
         }
 
         let (def, used) = def_use_map.get(label).unwrap();
-        let mut new_in = HashSet::new();
+        let mut new_in = BTreeSet::new();
 
         for out in &new_out {
             new_in.insert(out.clone());
@@ -80,7 +80,7 @@ pub fn liveness_graph<'a>(graph: &'a Graph<'a>) -> LivenessResult<LivenessGraph<
         ins_map.insert(label.clone(), new_in);
     }
 
-    let mut infos = HashMap::new();
+    let mut infos = BTreeMap::new();
 
     for (label, instr) in &graph.instrs {
         let (def, uses) = def_use_map.remove(label).unwrap();
@@ -109,11 +109,15 @@ fn succ(instr: &Instr) -> Vec<Label> {
         | Instr::EMUnop(_, _, l)
         | Instr::EMBinop(_, _, _, l)
         | Instr::ECall(_, _, l)
+        | Instr::ECallStar(_, _, l)
+        | Instr::EFunAddr(_, _, l)
+        | Instr::EString(_, _, l)
         | Instr::EGoto(l)
         | Instr::EAllocFrame(l)
         | Instr::EDeleteFrame(l)
         | Instr::EGetParam(_, _, l)
-        | Instr::EPushParam(_, l) => vec![l.clone()],
+        | Instr::EPushParam(_, l)
+        | Instr::EStackAddr(_, _, l) => vec![l.clone()],
         Instr::EMuBranch(_, _, l1, l2)
         | Instr::EMbBranch(_, _, _, l1, l2) => vec![l1.clone(), l2.clone()],
         Instr::EReturn => vec![]
@@ -123,7 +127,10 @@ fn succ(instr: &Instr) -> Vec<Label> {
 pub fn def_use(instr: &Instr) -> (Vec<Register>, Vec<Register>) {
     match instr {
         Instr::EConst(_, r, _)
-        | Instr::EGetParam(_, r, _) => (vec![r.clone()], vec![]),
+        | Instr::EGetParam(_, r, _)
+        | Instr::EStackAddr(_, r, _)
+        | Instr::EFunAddr(_, r, _)
+        | Instr::EString(_, r, _) => (vec![r.clone()], vec![]),
         Instr::EMuBranch(_, r, _, _)
         | Instr::EPushParam(r, _) => (vec![], vec![r.clone()]),
         Instr::EMUnop(_, r, _) => (vec![r.clone()], vec![r.clone()]),
@@ -136,16 +143,21 @@ pub fn def_use(instr: &Instr) -> (Vec<Register>, Vec<Register>) {
         Instr::EMBinop(_, rs, rd, _) => (vec![rd.clone()], vec![rs.clone(), rd.clone()]),
         Instr::EStore(r1, r2, _, _)
         | Instr::EMbBranch(_, r1, r2, _, _) => (vec![], vec![r1.clone(), r2.clone()]),
-        Instr::ECall(_, n, _) => {
+        Instr::ECall(_, n, _)
+        | Instr::ECallStar(_, n, _) => {
             let def = CALLER_SAVED.iter()
                 .map(|r| Register::Physical(r.clone()))
                 .collect();
-            let used = PARAMETERS
+            let mut used: Vec<Register> = PARAMETERS
                 .iter()
                 .map(|r| Register::Physical(r.clone()))
                 .collect::<Vec<Register>>()
                 .drain(..(*n as usize))
                 .collect();
+            used.push(Register::Physical(PhysicalRegister::Rax));
+            if let Instr::ECallStar(callee, _, _) = instr {
+                used.push(callee.clone());
+            }
             (def, used)
         }
         Instr::EGoto(_)
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fmt::{Display, Formatter};
 use derive_new::new;
 use crate::ertl::structure::{Instr, Label};
@@ -6,17 +6,17 @@ use crate::ertl::structure::register::Register;
 use crate::utils::{DisplayableSet, DisplayableVec};
 
 pub struct LivenessGraph<'a> {
-    pub infos: HashMap<Label, LivenessInfo<'a>>,
+    pub infos: BTreeMap<Label, LivenessInfo<'a>>,
 }
 
 #[derive(Debug, new)]
 pub struct LivenessInfo<'a> {
     pub instr: &'a Instr<'a>,
     pub succ: Vec<Label>,
-    pub pred: HashSet<Label>,
+    pub pred: BTreeSet<Label>,
     pub defs: Vec<Register>,
     pub uses: Vec<Register>,
-    pub ins: HashSet<Register>,
+    pub ins: BTreeSet<Register>,
     pub outs: Vec<Register>,
 }
 
@@ -46,11 +46,15 @@ impl DisplayableLivenessGraph<'_> {
                 | Instr::EMUnop(_, _, l)
                 | Instr::EMBinop(_, _, _, l)
                 | Instr::ECall(_, _, l)
+                | Instr::ECallStar(_, _, l)
+                | Instr::EFunAddr(_, _, l)
+                | Instr::EString(_, _, l)
                 | Instr::EGoto(l)
                 | Instr::EAllocFrame(l)
                 | Instr::EDeleteFrame(l)
                 | Instr::EGetParam(_, _, l)
-                | Instr::EPushParam(_, l) => {
+                | Instr::EPushParam(_, l)
+                | Instr::EStackAddr(_, _, l) => {
                     self.visit(visited, &l, f)?;
                 }
                 Instr::EMuBranch(_, _, l1, l2)
@@ -0,0 +1,165 @@
+//! Answers the questions of an editor about a source text, as byte offsets in that text.
+
+use std::ops::Range;
+use crate::parser::parse_file;
+use crate::typer::structure::{declaration, Definition, File, Reference, Typ};
+use crate::typer::typ_file;
+use crate::warning::WarningCode;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub span: Range<usize>,
+    pub severity: Severity,
+    pub code: Option<WarningCode>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub label: String,
+    pub detail: String,
+}
+
+/// Errors of the parser or the typer, or else warnings of the typer and of the RTL analyses.
+pub fn diagnostics(source: &str) -> Vec<Diagnostic> {
+    let parsed = match parse_file(source) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            let located = error.anchor().and_then(|anchor| span(source, anchor));
+            return vec![error_diagnostic(located, error.to_string())];
+        }
+    };
+    let typed = match typ_file(&parsed) {
+        Ok(typed) => typed,
+        Err(error) => {
+            let located = error.span().and_then(|x| Some(span(source, x.first)?.start..span(source, x.last)?.end));
+            return vec![error_diagnostic(located, error.to_string())];
+        }
+    };
+
+    let rtl = typed.minic_rtl().ok();
+    let uninit = rtl.as_ref().map(|rtl| rtl.minic_uninit()).unwrap_or_default();
+    typed.warnings()
+        .iter()
+        .chain(&uninit)
+        .map(|warning| Diagnostic {
+            span: warning.anchor()
+                .or(Some(warning.fun()))
+                .and_then(|anchor| span(source, anchor))
+                .unwrap_or(0..0),
+            severity: Severity::Warning,
+            code: Some(*warning.code()),
+            message: warning.message().clone(),
+        })
+        .collect()
+}
+
+/// Span of the declaration of the identifier at `offset`.
+pub fn definition(source: &str, offset: usize) -> Option<Range<usize>> {
+    with_typed(source, |file| {
+        let reference = reference_at(source, file, offset)?;
+        span(source, file.declaration(reference)?)
+    })
+}
+
+/// Declaration of what the identifier at `offset` names, with its type.
+pub fn hover(source: &str, offset: usize) -> Option<String> {
+    with_typed(source, |file| {
+        let reference = reference_at(source, file, offset)?;
+        let name = file.declaration(reference).unwrap_or(*reference.ident());
+        Some(match reference.definition() {
+            Definition::Local(_, _) => declaration(reference.typ(), name),
+            Definition::Field(field) => field.to_string(),
+            Definition::Fun(signature) => signature.to_string(),
+            Definition::Struct(structure) => structure.to_string(),
+        })
+    })
+}
+
+/// Fields of the structure pointed to by the expression before `->`, when `offset` follows it.
+/// The member access is usually incomplete while typing, so it is removed before typing the source.
+pub fn completion(source: &str, offset: usize) -> Vec<Completion> {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let (before, after) = source.split_at(offset);
+
+    let before = before.trim_end_matches(is_ident);
+    let Some(receiver) = before.trim_end().strip_suffix("->") else {
+        return vec![];
+    };
+    let arrow = receiver.len();
+    let receiver = receiver.trim_end();
+    let name = &receiver[receiver.trim_end_matches(is_ident).len()..];
+    if name.is_empty() {
+        return vec![];
+    }
+    let receiver_start = receiver.len() - name.len();
+    let rest = after.trim_start_matches(is_ident);
+
+    for separator in ["", ";"] {
+        let patched = format!("{}{}{}", &source[..arrow], separator, rest);
+        let fields = with_typed(&patched, |file| {
+            let reference = file.references()
+                .iter()
+                .find(|reference| span(&patched, reference.ident()) == Some(receiver_start..receiver.len()))?;
+            let Typ::TStruct(structure) = reference.typ() else {
+                return None;
+            };
+
+            let fields = structure.fields().borrow();
+            let mut fields = fields.values().collect::<Vec<_>>();
+            fields.sort_by_key(|field| *field.index());
+            Some(
+                fields
+                    .into_iter()
+                    .map(|field| Completion { label: field.name().to_string(), detail: field.to_string() })
+                    .collect::<Vec<_>>()
+            )
+        });
+
+        if let Some(fields) = fields {
+            return fields;
+        }
+    }
+
+    vec![]
+}
+
+fn with_typed<T>(source: &str, f: impl FnOnce(&File) -> Option<T>) -> Option<T> {
+    let parsed = parse_file(source).ok()?;
+    let typed = typ_file(&parsed).ok()?;
+    f(&typed)
+}
+
+/// Reference whose identifier contains `offset`, or ends at it.
+fn reference_at<'a, 'b>(source: &str, file: &'b File<'a>, offset: usize) -> Option<&'b Reference<'a>> {
+    let spans = file.references()
+        .iter()
+        .filter_map(|reference| Some((span(source, reference.ident())?, reference)))
+        .collect::<Vec<_>>();
+
+    spans.iter()
+        .find(|(span, _)| span.contains(&offset))
+        .or_else(|| spans.iter().find(|(span, _)| span.end == offset))
+        .map(|(_, reference)| *reference)
+}
+
+fn error_diagnostic(span: Option<Range<usize>>, message: String) -> Diagnostic {
+    Diagnostic {
+        span: span.unwrap_or(0..0),
+        severity: Severity::Error,
+        code: None,
+        message,
+    }
+}
+
+/// Span of `ident` in `source`, if it is a slice of it.
+fn span(source: &str, ident: &str) -> Option<Range<usize>> {
+    let start = (ident.as_ptr() as usize).checked_sub(source.as_ptr() as usize)?;
+    (start + ident.len() <= source.len()).then(|| start..start + ident.len())
+}
@@ -0,0 +1,180 @@
+//! Language server for mini-C : diagnostics on save, go-to-definition, hover and completion
+//! of fields after `->`. Documents are synchronised as a whole and analysed on each request.
+
+pub mod analysis;
+pub mod protocol;
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use serde_json::{json, Value};
+use crate::lsp::analysis::{completion, definition, diagnostics, hover, Severity};
+use crate::lsp::protocol::{offset, range, read_content, write_message};
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const COMPLETION_ITEM_FIELD: i64 = 5;
+const DIAGNOSTIC_ERROR: i64 = 1;
+const DIAGNOSTIC_WARNING: i64 = 2;
+const TEXT_DOCUMENT_SYNC_FULL: i64 = 1;
+
+pub struct Server<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+    documents: HashMap<String, String>,
+}
+
+impl<R: BufRead, W: Write> Server<R, W> {
+    pub fn new(input: R, output: W) -> Server<R, W> {
+        Server { input, output, documents: HashMap::new() }
+    }
+
+    /// Serves requests until the `exit` notification or the end of the input.
+    /// A message which isn't JSON gets an error, and the next ones are still served.
+    pub fn run(&mut self) -> std::io::Result<()> {
+        while let Some(content) = read_content(&mut self.input)? {
+            let message = match serde_json::from_slice::<Value>(&content) {
+                Ok(message) => message,
+                Err(error) => {
+                    write_message(&mut self.output, &json!({
+                        "jsonrpc": "2.0",
+                        "id": null,
+                        "error": { "code": PARSE_ERROR, "message": error.to_string() }
+                    }))?;
+                    continue;
+                }
+            };
+            let method = message["method"].as_str().unwrap_or_default();
+            let params = &message["params"];
+
+            if method == "exit" {
+                break;
+            }
+
+            let result = match method {
+                "initialize" => Some(capabilities()),
+                "shutdown" => Some(Value::Null),
+                "textDocument/didOpen" => {
+                    let document = &params["textDocument"];
+                    self.open(document["uri"].as_str(), document["text"].as_str());
+                    self.publish_diagnostics(document["uri"].as_str())?;
+                    None
+                }
+                "textDocument/didChange" => {
+                    let text = params["contentChanges"].as_array().and_then(|x| x.last());
+                    self.open(params["textDocument"]["uri"].as_str(), text.and_then(|x| x["text"].as_str()));
+                    None
+                }
+                "textDocument/didSave" => {
+                    self.open(params["textDocument"]["uri"].as_str(), params["text"].as_str());
+                    self.publish_diagnostics(params["textDocument"]["uri"].as_str())?;
+                    None
+                }
+                "textDocument/didClose" => {
+                    if let Some(uri) = params["textDocument"]["uri"].as_str() {
+                        self.documents.remove(uri);
+                        self.notify("textDocument/publishDiagnostics", json!({ "uri": uri, "diagnostics": [] }))?;
+                    }
+                    None
+                }
+                "textDocument/definition" => Some(self.at_position(params, |uri, source, offset| {
+                    definition(source, offset)
+                        .map_or(Value::Null, |span| json!({ "uri": uri, "range": range(source, &span) }))
+                })),
+                "textDocument/hover" => Some(self.at_position(params, |_, source, offset| {
+                    hover(source, offset).map_or(Value::Null, |text| json!({
+                        "contents": { "kind": "markdown", "value": format!("```c\n{}\n```", text) }
+                    }))
+                })),
+                "textDocument/completion" => Some(self.at_position(params, |_, source, offset| {
+                    let items = completion(source, offset)
+                        .into_iter()
+                        .map(|item| json!({ "label": item.label, "kind": COMPLETION_ITEM_FIELD, "detail": item.detail }))
+                        .collect::<Vec<_>>();
+                    json!(items)
+                })),
+                _ => None,
+            };
+
+            // Notifications have no id and get no response
+            let Some(id) = message.get("id") else {
+                continue;
+            };
+            let response = match result {
+                Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                None => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": METHOD_NOT_FOUND, "message": format!("unknown method `{}`", method) }
+                }),
+            };
+            write_message(&mut self.output, &response)?;
+        }
+
+        Ok(())
+    }
+
+    fn open(&mut self, uri: Option<&str>, text: Option<&str>) {
+        if let (Some(uri), Some(text)) = (uri, text) {
+            self.documents.insert(uri.to_string(), text.to_string());
+        }
+    }
+
+    fn at_position<F>(&self, params: &Value, f: F) -> Value where F: FnOnce(&str, &str, usize) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        match self.documents.get(uri) {
+            Some(source) => match offset(source, &params["position"]) {
+                Some(offset) => f(uri, source, offset),
+                None => Value::Null,
+            },
+            None => Value::Null,
+        }
+    }
+
+    fn publish_diagnostics(&mut self, uri: Option<&str>) -> std::io::Result<()> {
+        let Some(source) = uri.and_then(|uri| self.documents.get(uri)) else {
+            return Ok(());
+        };
+
+        let diagnostics = diagnostics(source)
+            .into_iter()
+            .map(|diagnostic| {
+                let severity = match diagnostic.severity {
+                    Severity::Error => DIAGNOSTIC_ERROR,
+                    Severity::Warning => DIAGNOSTIC_WARNING,
+                };
+                let mut value = json!({
+                    "range": range(source, &diagnostic.span),
+                    "severity": severity,
+                    "source": "minic",
+                    "message": diagnostic.message,
+                });
+                if let Some(code) = diagnostic.code {
+                    value["code"] = json!(code.code());
+                }
+                value
+            })
+            .collect::<Vec<_>>();
+
+        self.notify("textDocument/publishDiagnostics", json!({ "uri": uri, "diagnostics": diagnostics }))
+    }
+
+    fn notify(&mut self, method: &str, params: Value) -> std::io::Result<()> {
+        write_message(&mut self.output, &json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+}
+
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            "textDocumentSync": {
+                "openClose": true,
+                "change": TEXT_DOCUMENT_SYNC_FULL,
+                "save": { "includeText": true },
+            },
+            "definitionProvider": true,
+            "hoverProvider": true,
+            "completionProvider": { "triggerCharacters": [">"] },
+        },
+        "serverInfo": { "name": "minic-lsp" },
+    })
+}
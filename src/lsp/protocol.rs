@@ -0,0 +1,77 @@
+//! Framing of JSON-RPC messages, and positions of the Language Server Protocol.
+
+use std::io::{BufRead, Error, ErrorKind, Write};
+use serde_json::{json, Value};
+
+/// Reads a message framed by a `Content-Length` header, or `None` at the end of the input.
+pub fn read_message(input: &mut impl BufRead) -> std::io::Result<Option<Value>> {
+    match read_content(input)? {
+        Some(content) => serde_json::from_slice(&content)
+            .map(Some)
+            .map_err(|error| Error::new(ErrorKind::InvalidData, error)),
+        None => Ok(None),
+    }
+}
+
+/// Reads the content of a message framed by a `Content-Length` header, or `None` at the end of the input.
+pub fn read_content(input: &mut impl BufRead) -> std::io::Result<Option<Vec<u8>>> {
+    let mut length = None;
+
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let length = length.ok_or_else(|| Error::new(ErrorKind::InvalidData, "missing Content-Length header"))?;
+    let mut content = vec![0; length];
+    input.read_exact(&mut content)?;
+    Ok(Some(content))
+}
+
+pub fn write_message(output: &mut impl Write, message: &Value) -> std::io::Result<()> {
+    let content = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", content.len(), content)?;
+    output.flush()
+}
+
+/// Byte offset of an LSP position, whose character counts UTF-16 code units.
+pub fn offset(source: &str, position: &Value) -> Option<usize> {
+    let line = position["line"].as_u64()? as usize;
+    let character = position["character"].as_u64()? as usize;
+
+    let start = if line == 0 {
+        0
+    } else {
+        source.match_indices('\n').nth(line - 1)?.0 + 1
+    };
+
+    let mut units = 0;
+    for (index, c) in source[start..].char_indices() {
+        if units >= character || c == '\n' {
+            return Some(start + index);
+        }
+        units += c.len_utf16();
+    }
+    Some(source.len())
+}
+
+pub fn position(source: &str, offset: usize) -> Value {
+    let before = &source[..offset];
+    let line = before.matches('\n').count();
+    let start = before.rfind('\n').map_or(0, |x| x + 1);
+    let character = before[start..].encode_utf16().count();
+    json!({ "line": line, "character": character })
+}
+
+pub fn range(source: &str, span: &std::ops::Range<usize>) -> Value {
+    json!({ "start": position(source, span.start), "end": position(source, span.end) })
+}
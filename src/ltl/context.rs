@@ -1,22 +1,27 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::sync::Arc;
 use derive_new::new;
 use crate::coloring::structure::Coloring;
 use crate::ertl::structure::Label;
+use crate::ertl::structure::frame::Frame;
 use crate::ertl::structure::register::Register;
 use crate::ltl::error::LtlError;
 use crate::ltl::LtlResult;
 use crate::ltl::structure::{Instr, Operand};
 use crate::rtl::structure::Fresh;
+use crate::session::Session;
 
 #[derive(new)]
 pub struct Context<'a> {
     pub coloring: Coloring,
-    pub graph: HashMap<Label, Instr<'a>>,
+    pub frame: Frame<'a>,
+    pub graph: BTreeMap<Label, Instr<'a>>,
+    pub session: Arc<Session>,
 }
 
 impl<'a> Context<'a> {
     pub fn insert(&mut self, instr: Instr<'a>) -> Label {
-        let label = Label::fresh();
+        let label = Label::fresh(&self.session);
         self.graph.insert(label.clone(), instr);
         label
     }
@@ -28,7 +33,13 @@ impl<'a> Context<'a> {
     pub fn color(&self, reg: &Register) -> LtlResult<Operand> {
         match reg {
             Register::Pseudo(reg) => {
-                self.coloring.colors.get(reg).ok_or(LtlError::MissingRegisterColor(reg.clone())).cloned()
+                match self.coloring.colors.get(reg).ok_or(LtlError::MissingRegisterColor(reg.clone()))? {
+                    Operand::Spilled(index) => {
+                        let offset = self.frame.spilled(*index).ok_or(LtlError::Any("Spilled register has no frame slot"))?;
+                        Ok(Operand::Spilled(offset))
+                    }
+                    color => Ok(color.clone())
+                }
             }
             Register::Physical(reg) => {
                 Ok(Operand::Register(reg.clone()))
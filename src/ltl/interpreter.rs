@@ -0,0 +1,156 @@
+use crate::common::{Execution, Ident, MAIN, STRING_ADDRESS_BASE, Stdin, Value};
+use crate::ertl::interpreter::ErtlInterpreterResult;
+use crate::ertl::interpreter::error::ErtlInterpreterError;
+use crate::ertl::interpreter::machine::{binary_branch, Machine, unary_branch, unop};
+use crate::ertl::structure::{Label, Munop};
+use crate::ertl::structure::register::{PhysicalRegister, RESULT};
+use crate::ltl::structure::{File, Fun, Instr, Operand};
+
+/// Runs on the same machine as the ERTL interpreter : only the operands change.
+pub type LtlInterpreterResult<T> = ErtlInterpreterResult<T>;
+
+/// A function being executed, and the next instruction.
+struct Activation<'a, 'b> {
+    fun: &'b Fun<'a>,
+    label: Label,
+    /// Registers the function must restore before returning.
+    preserved: Vec<(PhysicalRegister, Value)>,
+}
+
+enum Step<'a> {
+    Next(Label),
+    Call(Ident<'a>, Label),
+    Return,
+}
+
+/// Runs the program after register allocation : spilled operands live in the frame, below `%rbp`.
+pub fn interp_ltl_file(file: &File) -> LtlInterpreterResult<Execution> {
+    interp_ltl_file_with(file, Stdin::default())
+}
+
+/// Runs the program on the input.
+pub fn interp_ltl_file_with(file: &File, stdin: Stdin) -> LtlInterpreterResult<Execution> {
+    let mut machine = Machine::new(file.funs.keys().cloned(), &file.strings, stdin);
+    let main = file.funs.get(MAIN).ok_or(ErtlInterpreterError::FunctionDoesNotExist(String::from(MAIN)))?;
+
+    let preserved = machine.preserved();
+    machine.push_return_address()?;
+    let mut stack = vec![Activation::new(main, preserved)];
+
+    while let Some(activation) = stack.last_mut() {
+        let instr = activation.fun.body.instrs
+            .get(&activation.label)
+            .ok_or(ErtlInterpreterError::NoSuchInstruction(activation.label.clone()))?;
+
+        match interp_instr(&mut machine, instr)? {
+            Step::Next(label) => activation.label = label,
+            Step::Call(name, label) if Machine::is_library(name) => {
+                machine.call_library(name)?;
+                activation.label = label;
+            }
+            Step::Call(name, label) => {
+                let fun = file.funs.get(name).ok_or(ErtlInterpreterError::FunctionDoesNotExist(String::from(name)))?;
+                activation.label = label;
+                let preserved = machine.preserved();
+                machine.push_return_address()?;
+                stack.push(Activation::new(fun, preserved));
+            }
+            Step::Return => {
+                machine.pop_return_address()?;
+                machine.check_preserved(activation.fun.name, &activation.preserved)?;
+                stack.pop();
+            }
+        }
+    }
+
+    Ok(Execution { status: machine.get(&RESULT), stdout: machine.stdout })
+}
+
+impl<'a, 'b> Activation<'a, 'b> {
+    fn new(fun: &'b Fun<'a>, preserved: Vec<(PhysicalRegister, Value)>) -> Activation<'a, 'b> {
+        Activation { fun, label: fun.entry.clone(), preserved }
+    }
+}
+
+fn get(machine: &Machine, operand: &Operand) -> LtlInterpreterResult<Value> {
+    match operand {
+        Operand::Register(register) => Ok(machine.get(register)),
+        Operand::Spilled(offset) => machine.load(machine.get(&PhysicalRegister::Rbp) + *offset as Value),
+    }
+}
+
+fn put(machine: &mut Machine, operand: &Operand, value: Value) -> LtlInterpreterResult<()> {
+    match operand {
+        Operand::Register(register) => {
+            machine.set(register, value);
+            Ok(())
+        }
+        Operand::Spilled(offset) => machine.store(machine.get(&PhysicalRegister::Rbp) + *offset as Value, value),
+    }
+}
+
+fn interp_instr<'a>(machine: &mut Machine<'a>, instr: &Instr<'a>) -> LtlInterpreterResult<Step<'a>> {
+    match instr {
+        Instr::ELoad(address, offset, r, l) => {
+            let value = machine.load(machine.get(address) + *offset as Value)?;
+            machine.set(r, value);
+            Ok(Step::Next(l.clone()))
+        }
+        Instr::EStore(r, address, offset, l) => {
+            machine.store(machine.get(address) + *offset as Value, machine.get(r))?;
+            Ok(Step::Next(l.clone()))
+        }
+        Instr::EGoto(l) => Ok(Step::Next(l.clone())),
+        Instr::EReturn => Ok(Step::Return),
+        Instr::EConst(c, operand, l) => {
+            put(machine, operand, *c)?;
+            Ok(Step::Next(l.clone()))
+        }
+        Instr::EMunop(op, operand, l) => {
+            match (op, operand) {
+                (Munop::Maddi(c), Operand::Register(PhysicalRegister::Rsp)) => machine.move_stack(*c)?,
+                _ => {
+                    let value = unop(op, get(machine, operand)?);
+                    put(machine, operand, value)?
+                }
+            }
+            Ok(Step::Next(l.clone()))
+        }
+        Instr::EMBinop(op, operand_1, operand_2, l) => {
+            let value = machine.binop(op, get(machine, operand_1)?, get(machine, operand_2)?)?;
+            put(machine, operand_2, value)?;
+            Ok(Step::Next(l.clone()))
+        }
+        Instr::EMuBranch(op, operand, l1, l2) => {
+            let jump = unary_branch(op, get(machine, operand)?);
+            Ok(Step::Next(if jump { l1.clone() } else { l2.clone() }))
+        }
+        Instr::EMbBranch(op, operand_1, operand_2, l1, l2) => {
+            let jump = binary_branch(op, get(machine, operand_1)?, get(machine, operand_2)?);
+            Ok(Step::Next(if jump { l1.clone() } else { l2.clone() }))
+        }
+        Instr::EPush(operand, l) => {
+            machine.push(get(machine, operand)?)?;
+            Ok(Step::Next(l.clone()))
+        }
+        Instr::EPop(operand, l) => {
+            let value = machine.pop()?;
+            put(machine, operand, value)?;
+            Ok(Step::Next(l.clone()))
+        }
+        Instr::ECall(name, l) => Ok(Step::Call(name, l.clone())),
+        Instr::ECallStar(callee, l) => Ok(Step::Call(machine.fun_at(get(machine, callee)?)?, l.clone())),
+        Instr::EFunAddr(name, operand, l) => {
+            put(machine, operand, machine.fun_address(name)?)?;
+            Ok(Step::Next(l.clone()))
+        }
+        Instr::EString(index, operand, l) => {
+            put(machine, operand, STRING_ADDRESS_BASE + *index as Value)?;
+            Ok(Step::Next(l.clone()))
+        }
+        Instr::EStackAddr(offset, operand, l) => {
+            put(machine, operand, machine.get(&PhysicalRegister::Rbp) + *offset as Value)?;
+            Ok(Step::Next(l.clone()))
+        }
+    }
+}
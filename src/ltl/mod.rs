@@ -1,10 +1,12 @@
 pub mod structure;
 pub mod error;
+pub mod interpreter;
 mod context;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use rayon::prelude::*;
 use crate::coloring::color_graph;
-use crate::common::Value;
+use crate::common::{StackOffset, Value};
 use crate::ertl::structure as ertl;
 use crate::ertl::structure::{Label, Mbinop};
 use crate::ertl::structure::register::{PhysicalRegister, TMP_1, TMP_2};
@@ -19,13 +21,12 @@ use crate::rtl::structure::{Fresh, Munop};
 pub type LtlResult<T> = Result<T, LtlError>;
 
 pub fn ltl_file<'a>(file: &ertl::File<'a>) -> LtlResult<File<'a>> {
-    let mut funs = HashMap::new();
+    let funs = file.funs
+        .par_iter()
+        .map(|(name, fun)| Ok((*name, ltl_fun(fun)?)))
+        .collect::<LtlResult<HashMap<_, _>>>()?;
 
-    for (name, fun) in &file.funs {
-        funs.insert(name.clone(), ltl_fun(fun)?);
-    }
-
-    Ok(File::new(funs))
+    Ok(File::new(funs, file.strings.clone()))
 }
 
 fn ltl_fun<'a>(fun: &ertl::Fun<'a>) -> LtlResult<Fun<'a>> {
@@ -33,9 +34,17 @@ fn ltl_fun<'a>(fun: &ertl::Fun<'a>) -> LtlResult<Fun<'a>> {
     let interference = interference_graph(&liveness).map_err(|err| LtlError::InterferenceError(err))?;
     let coloring = color_graph(&interference).map_err(|err| LtlError::ColoringError(err))?;
 
+    let mut frame = fun.frame.clone();
+
+    for index in 0..coloring.count_on_stack {
+        frame.alloc_spilled(index as StackOffset);
+    }
+
     let mut context = Context::new(
         coloring,
-        HashMap::new(),
+        frame,
+        BTreeMap::new(),
+        fun.body.session.clone(),
     );
 
     for (label, instr) in &fun.body.instrs {
@@ -44,6 +53,7 @@ fn ltl_fun<'a>(fun: &ertl::Fun<'a>) -> LtlResult<Fun<'a>> {
 
     Ok(Fun::new(
         fun.name.clone(),
+        context.frame,
         fun.entry.clone(),
         Graph::new(context.graph),
     ))
@@ -74,7 +84,7 @@ fn ltl_instr<'a>(context: &mut Context<'a>, label: &Label, instr: &ertl::Instr<'
                 Operand::Register(r) => (label.clone(), r.clone()),
                 Operand::Spilled(_) => {
                     let o = TMP_1;
-                    let pre_label = Label::fresh();
+                    let pre_label = Label::fresh(&context.session);
                     context.insert_at_label(label.clone(), Instr::EMBinop(Mbinop::MMov, addr, Operand::Register(o.clone()), pre_label.clone()));
                     (pre_label, o)
                 }
@@ -91,7 +101,7 @@ fn ltl_instr<'a>(context: &mut Context<'a>, label: &Label, instr: &ertl::Instr<'
                 Operand::Register(r) => (label.clone(), r.clone()),
                 Operand::Spilled(_) => {
                     let r = TMP_1;
-                    let addr_lbl = Label::fresh();
+                    let addr_lbl = Label::fresh(&context.session);
                     context.insert_at_label(
                         label.clone(),
                         Instr::EMBinop(Mbinop::MMov, addr, Operand::Register(r.clone()), addr_lbl.clone()),
@@ -103,7 +113,7 @@ fn ltl_instr<'a>(context: &mut Context<'a>, label: &Label, instr: &ertl::Instr<'
                 Operand::Register(r) => (addr_lbl, r.clone()),
                 Operand::Spilled(_) => {
                     let o = TMP_2;
-                    let store_lbl = Label::fresh();
+                    let store_lbl = Label::fresh(&context.session);
                     context.insert_at_label(addr_lbl, Instr::EMBinop(Mbinop::MMov, value, Operand::Register(o.clone()), store_lbl.clone()));
                     (store_lbl, o)
                 }
@@ -165,6 +175,27 @@ fn ltl_instr<'a>(context: &mut Context<'a>, label: &Label, instr: &ertl::Instr<'
             );
             Ok(())
         }
+        ertl::Instr::ECallStar(callee, _, l) => {
+            context.insert_at_label(
+                label.clone(),
+                Instr::ECallStar(context.color(callee)?, l.clone()),
+            );
+            Ok(())
+        }
+        ertl::Instr::EFunAddr(name, r, l) => {
+            context.insert_at_label(
+                label.clone(),
+                Instr::EFunAddr(name, context.color(r)?, l.clone()),
+            );
+            Ok(())
+        }
+        ertl::Instr::EString(index, r, l) => {
+            context.insert_at_label(
+                label.clone(),
+                Instr::EString(*index, context.color(r)?, l.clone()),
+            );
+            Ok(())
+        }
         ertl::Instr::EGoto(l) => {
             context.insert_at_label(
                 label.clone(),
@@ -173,40 +204,31 @@ fn ltl_instr<'a>(context: &mut Context<'a>, label: &Label, instr: &ertl::Instr<'
             Ok(())
         }
         ertl::Instr::EAllocFrame(l) => {
-            if context.coloring.count_on_stack != 0 {
-                let add_rsp_lbl = context.insert(
-                    Instr::EMunop(Munop::Maddi(-(8 * context.coloring.count_on_stack as Value)), Operand::Register(PhysicalRegister::Rsp), l.clone())
-                );
-                let mov_rsp_lbl = context.insert(
-                    Instr::EMBinop(Mbinop::MMov, Operand::Register(PhysicalRegister::Rsp), Operand::Register(PhysicalRegister::Rbp), add_rsp_lbl)
-                );
-                context.insert_at_label(
-                    label.clone(),
-                    Instr::EPush(Operand::Register(PhysicalRegister::Rbp), mov_rsp_lbl),
-                );
+            let frame_size = context.frame.size();
+            let add_rsp_lbl = if frame_size != 0 {
+                context.insert(
+                    Instr::EMunop(Munop::Maddi(-(frame_size as Value)), Operand::Register(PhysicalRegister::Rsp), l.clone())
+                )
             } else {
-                context.insert_at_label(
-                    label.clone(),
-                    Instr::EGoto(l.clone()),
-                );
-            }
+                l.clone()
+            };
+            let mov_rsp_lbl = context.insert(
+                Instr::EMBinop(Mbinop::MMov, Operand::Register(PhysicalRegister::Rsp), Operand::Register(PhysicalRegister::Rbp), add_rsp_lbl)
+            );
+            context.insert_at_label(
+                label.clone(),
+                Instr::EPush(Operand::Register(PhysicalRegister::Rbp), mov_rsp_lbl),
+            );
             Ok(())
         }
         ertl::Instr::EDeleteFrame(l) => {
-            if context.coloring.count_on_stack != 0 {
-                let pop_lbl = context.insert(
-                    Instr::EPop(Operand::Register(PhysicalRegister::Rbp), l.clone()),
-                );
-                context.insert_at_label(
-                    label.clone(),
-                    Instr::EMBinop(Mbinop::MMov, Operand::Register(PhysicalRegister::Rbp), Operand::Register(PhysicalRegister::Rsp), pop_lbl),
-                );
-            } else {
-                context.insert_at_label(
-                    label.clone(),
-                    Instr::EGoto(l.clone()),
-                );
-            }
+            let pop_lbl = context.insert(
+                Instr::EPop(Operand::Register(PhysicalRegister::Rbp), l.clone()),
+            );
+            context.insert_at_label(
+                label.clone(),
+                Instr::EMBinop(Mbinop::MMov, Operand::Register(PhysicalRegister::Rbp), Operand::Register(PhysicalRegister::Rsp), pop_lbl),
+            );
             Ok(())
         }
         ertl::Instr::EGetParam(index, dest, l) => {
@@ -223,7 +245,7 @@ fn ltl_instr<'a>(context: &mut Context<'a>, label: &Label, instr: &ertl::Instr<'
                     let tmp = TMP_1;
 
                     let store_lbl = context.insert(
-                        Instr::EStore(tmp.clone(), PhysicalRegister::Rbp, -8 * (o + 1), l.clone()),
+                        Instr::EStore(tmp.clone(), PhysicalRegister::Rbp, o, l.clone()),
                     );
                     context.insert_at_label(
                         label.clone(),
@@ -240,9 +262,16 @@ fn ltl_instr<'a>(context: &mut Context<'a>, label: &Label, instr: &ertl::Instr<'
             );
             Ok(())
         }
+        ertl::Instr::EStackAddr(o, r, l) => {
+            context.insert_at_label(
+                label.clone(),
+                Instr::EStackAddr(*o, context.color(r)?, l.clone()),
+            );
+            Ok(())
+        }
         ertl::Instr::EReturn => {
             context.insert_at_label(label.clone(), Instr::EReturn);
             Ok(())
         }
     }
-}
\ No newline at end of file
+}
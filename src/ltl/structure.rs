@@ -1,25 +1,28 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter};
 use derive_new::new;
-use crate::common::{Ident, StackOffset, Value};
+use crate::common::{in_source_order, Ident, StackOffset, Value};
 use crate::ertl::structure::{Label, MbBranch, Mbinop, MuBranch, Munop};
+use crate::ertl::structure::frame::Frame;
 use crate::ertl::structure::register::PhysicalRegister;
 
 #[derive(Debug, new)]
 pub struct File<'a> {
     pub funs: HashMap<Ident<'a>, Fun<'a>>,
+    pub strings: Vec<Ident<'a>>,
 }
 
 #[derive(Debug, new)]
 pub struct Fun<'a> {
     pub name: Ident<'a>,
+    pub frame: Frame<'a>,
     pub entry: Label,
     pub body: Graph<'a>,
 }
 
 #[derive(Debug, new)]
 pub struct Graph<'a> {
-    pub instrs: HashMap<Label, Instr<'a>>,
+    pub instrs: BTreeMap<Label, Instr<'a>>,
 }
 
 #[derive(Debug)]
@@ -35,7 +38,11 @@ pub enum Instr<'a> {
     EMbBranch(MbBranch, Operand, Operand, Label, Label),
     EPush(Operand, Label),
     ECall(Ident<'a>, Label),
+    ECallStar(Operand, Label),
+    EFunAddr(Ident<'a>, Operand, Label),
+    EString(usize, Operand, Label),
     EPop(Operand, Label),
+    EStackAddr(StackOffset, Operand, Label),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -48,7 +55,7 @@ impl Display for Operand {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Operand::Register(x) => write!(f, "{}", x),
-            Operand::Spilled(x) => write!(f, "stack {}", x)
+            Operand::Spilled(x) => write!(f, "stack {}(%rbp)", x)
         }
     }
 }
@@ -67,7 +74,11 @@ impl Display for Instr<'_> {
             Instr::EMbBranch(op, ope1, ope2, l1, l2) => write!(f, "{} {},{} --> {},{}", op, ope1, ope2, l1, l2),
             Instr::EPush(op, l) => write!(f, "push {} --> {}", op, l),
             Instr::ECall(name, l) => write!(f, "call {} --> {}", name, l),
+            Instr::ECallStar(op, l) => write!(f, "call *{} --> {}", op, l),
+            Instr::EFunAddr(name, op, l) => write!(f, "fun_addr {} {} --> {}", name, op, l),
+            Instr::EString(index, op, l) => write!(f, "string {} {} --> {}", index, op, l),
             Instr::EPop(reg, l) => write!(f, "pop {} --> {}", reg, l),
+            Instr::EStackAddr(o, r, l) => write!(f, "lea {}(%rbp) {} --> {}", o, r, l),
         }
     }
 }
@@ -76,6 +87,7 @@ impl Display for Fun<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{}:", self.name)?;
         writeln!(f, "\tentry: {}", self.entry)?;
+        writeln!(f, "\tframe: {}", self.frame)?;
         write!(f, "{}", self.body)
     }
 }
@@ -92,7 +104,7 @@ impl Display for Graph<'_> {
 impl Display for File<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "=== LTL =================================================")?;
-        for (_name, fun) in &self.funs {
+        for (_name, fun) in in_source_order(&self.funs) {
             writeln!(f, "{}", fun)?;
         }
         Ok(())
@@ -1,13 +1,34 @@
+use std::fmt::Display;
 use std::fs::{File, read_to_string};
-use std::io::Write;
+use std::io::{stderr, stdin, stdout, Write};
+use std::process::exit;
 use clap::{Arg, ArgAction, Command};
 use rust_mini_c::liveness::liveness_graph;
 use rust_mini_c::liveness::structure::DisplayableLivenessGraph;
 use rust_mini_c::coloring::color_graph;
+use rust_mini_c::common::{in_source_order, Location, Stdin};
 use rust_mini_c::interference::interference_graph;
 use rust_mini_c::parser::parse_file;
+use rust_mini_c::rtl::interpreter::Limits;
+use rust_mini_c::warning::{DisplayableWarning, Warning, WarningOptions};
+
+/// The interpreters recurse on the calls of the program they run.
+const STACK_SIZE: usize = 1 << 30;
+/// Nested calls the RTL interpreter runs within `STACK_SIZE`, with room to spare.
+const MAX_CALL_DEPTH: u64 = 100_000;
 
 fn main() {
+    let compiler = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("Failed to start the compiler");
+
+    if let Err(panic) = compiler.join() {
+        std::panic::resume_unwind(panic);
+    }
+}
+
+fn run() {
     let matches = Command::new("minic")
         .version("1.0")
         .author("Louis P. <louisproffit86@gmail.com>")
@@ -18,7 +39,70 @@ fn main() {
         )
         .arg(
             Arg::new("output")
-                .required(true)
+                .required_unless_present("interp")
+        )
+        .arg(
+            Arg::new("interp")
+                .long("interp")
+                .value_name("STAGE")
+                .help("Runs the program with the interpreter of the stage, RTL by default, instead of compiling it")
+                .value_parser(["typer", "rtl"])
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("rtl"),
+        )
+        .arg(
+            Arg::new("trace")
+                .long("trace")
+                .help("Prints each statement or instruction run by the interpreter to stderr")
+                .requires("interp")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .help("Prints the calls and steps run by the interpreter in each function to stderr")
+                .requires("interp")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("fuel")
+                .long("fuel")
+                .value_name("STEPS")
+                .help("Number of instructions the RTL interpreter runs before giving up")
+                .requires("interp")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("max-call-depth")
+                .long("max-call-depth")
+                .value_name("CALLS")
+                .help("Number of nested calls beyond which the RTL interpreter reports a stack overflow")
+                .requires("interp")
+                .value_parser(clap::value_parser!(u64).range(1..=MAX_CALL_DEPTH)),
+        )
+        .arg(
+            Arg::new("max-heap")
+                .long("max-heap")
+                .value_name("BYTES")
+                .help("Number of bytes the RTL interpreter may allocate")
+                .requires("interp")
+                .value_parser(clap::value_parser!(i64)),
+        )
+        .arg(
+            Arg::new("warning")
+                .short('W')
+                .value_name("WARNING")
+                .help("Enables a warning, disables it with `no-`, or makes warnings errors with `error`")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("jobs")
+                .short('j')
+                .long("jobs")
+                .value_name("JOBS")
+                .help("Number of threads compiling the functions, one per core by default")
+                .value_parser(clap::value_parser!(usize)),
         )
         .arg(
             Arg::new("debug-parser")
@@ -50,8 +134,20 @@ fn main() {
                 .long("debug-ltl")
                 .action(ArgAction::SetTrue),
         )
+        .subcommand(
+            Command::new("debug")
+                .about("Runs the program step by step with the RTL interpreter")
+                .arg(Arg::new("file").required(true))
+        )
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true)
         .get_matches();
 
+    if let Some(("debug", matches)) = matches.subcommand() {
+        debug(matches.get_one::<String>("file").expect("required"));
+        return;
+    }
+
     let file_path = matches.get_one::<String>("file").expect("required");
     let debug_parser = matches.get_flag("debug-parser");
     let debug_typer = matches.get_flag("debug-typer");
@@ -60,25 +156,59 @@ fn main() {
     let debug_liveness = matches.get_flag("debug-liveness");
     let debug_ltl = matches.get_flag("debug-ltl");
 
-    let output = matches.get_one::<String>("output").expect("required");
+    let interp = matches.get_one::<String>("interp");
+    let trace = matches.get_flag("trace");
+    let profile = matches.get_flag("profile");
+    let limited = ["fuel", "max-call-depth", "max-heap"].iter().any(|id| matches.contains_id(id));
+    if limited && interp.is_some_and(|stage| stage == "typer") {
+        eprintln!("error: --fuel, --max-call-depth and --max-heap only apply to the RTL interpreter");
+        exit(2);
+    }
+    let mut limits = Limits::default();
+    if let Some(fuel) = matches.get_one::<u64>("fuel") {
+        limits.fuel = *fuel;
+    }
+    if let Some(call_depth) = matches.get_one::<u64>("max-call-depth") {
+        limits.call_depth = *call_depth as usize;
+    }
+    if let Some(heap_size) = matches.get_one::<i64>("max-heap") {
+        limits.heap_size = *heap_size;
+    }
+
+    let mut warning_options = WarningOptions::default();
+    for flag in matches.get_many::<String>("warning").unwrap_or_default() {
+        if let Err(error) = warning_options.set(flag) {
+            eprintln!("error: {}", error);
+            exit(2);
+        }
+    }
+
+    if let Some(jobs) = matches.get_one::<usize>("jobs") {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(*jobs)
+            .build_global()
+            .expect("Failed to start the threads");
+    }
 
     let content = read_to_string(file_path).expect("Failed to read file");
-    let _ = parse_file(&content)
+    let file = parse_file(&content)
         .map(|file| {
             if debug_parser {
-                println!("Parsed file : {:?}", file);
+                println!("Parsed file :\n{}", file);
             }
             file
         })
-        .expect("Failed to parse file")
+        .expect("Failed to parse file");
+    let file = file
         .minic_typ()
         .map(|file| {
             if debug_typer {
-                println!("Typed file : {:?}", file);
+                println!("Typed file :\n{:#}", file);
             }
             file
         })
-        .expect("Failed to parse file")
+        .expect("Failed to parse file");
+    let rtl = file
         .minic_rtl()
         .map(|file| {
             if debug_rtl {
@@ -86,7 +216,53 @@ fn main() {
             }
             file
         })
-        .expect("Failed to rtl file")
+        .expect("Failed to rtl file");
+
+    let uninit = rtl.minic_uninit();
+    let warnings = file.warnings().iter().chain(&uninit);
+    if report_warnings(warnings, &warning_options, &content) && warning_options.is_error() {
+        eprintln!("error: warnings are treated as errors");
+        exit(1);
+    }
+
+    if let Some(stage) = interp {
+        let input = Stdin::from_reader(stdin());
+
+        let trace = trace.then(stderr);
+        let (result, costs) = match stage.as_str() {
+            "typer" => {
+                let (result, costs) = file.minic_trace(input, trace);
+                let result = result.map_err(|error| match error.anchor().and_then(|anchor| Location::of(&content, anchor)) {
+                    Some(location) => format!("{}: {}", location, error),
+                    None => error.to_string(),
+                });
+                (result, costs)
+            }
+            _ => {
+                let (result, costs) = rtl.minic_trace(input, limits, trace);
+                (result.map_err(|error| error.to_string()), costs)
+            }
+        };
+
+        if profile {
+            eprintln!("{}", costs);
+        }
+        match result {
+            Ok(execution) => {
+                print!("{}", execution.stdout);
+                stdout().flush().expect("Failed to write the output");
+                // The system only keeps the low byte, as for the compiled program
+                exit(execution.status as i32)
+            }
+            Err(error) => {
+                eprintln!("error: {}", error);
+                exit(1);
+            }
+        }
+    }
+
+    let output = matches.get_one::<String>("output").expect("required");
+    let _ = rtl
         .minic_ertl()
         .map(|file| {
             if debug_ertl {
@@ -95,7 +271,7 @@ fn main() {
 
             if debug_liveness {
                 println!("--------Liveness---------------------------");
-                for (name, fun) in &file.funs {
+                for (name, fun) in in_source_order(&file.funs) {
                     let graph = liveness_graph(&fun.body).expect("Liveness failed");
                     println!("Liveness : \n{}:\n", name);
                     println!("{}", DisplayableLivenessGraph::new(&graph, &fun.entry));
@@ -119,4 +295,38 @@ fn main() {
             let mut output = File::create(output).expect("Failed to create a.out");
             writeln!(&mut output, "{}", file).expect("Failed to write to a.out")
         });
-}
\ No newline at end of file
+}
+
+fn debug(file_path: &str) {
+    let content = read_to_string(file_path).unwrap_or_else(|error| fail(None, format!("{}: {}", file_path, error)));
+    let file = parse_file(&content)
+        .unwrap_or_else(|error| fail(error.anchor().and_then(|anchor| Location::of(&content, anchor)), error));
+    let file = file
+        .minic_typ()
+        .unwrap_or_else(|error| fail(error.span().and_then(|span| Location::of(&content, span.first)), error));
+    let rtl = file.minic_rtl().unwrap_or_else(|error| fail(None, error));
+
+    if rtl.minic_debug(stdin().lock(), stdout()).is_err() {
+        exit(1);
+    }
+}
+
+/// Reports the error, at its location if it has one, and stops the compiler.
+fn fail(location: Option<Location>, error: impl Display) -> ! {
+    match location {
+        Some(location) => eprintln!("error: {}: {}", location, error),
+        None => eprintln!("error: {}", error),
+    }
+    exit(1)
+}
+
+/// Prints the enabled warnings, and tells whether there was any.
+fn report_warnings<'a, 'b>(warnings: impl Iterator<Item=&'b Warning<'a>>, options: &WarningOptions, content: &str) -> bool
+    where 'a: 'b {
+    let mut reported = false;
+    for warning in warnings.filter(|warning| options.is_enabled(*warning.code())) {
+        eprintln!("{}", DisplayableWarning::new(warning, content));
+        reported = true;
+    }
+    reported
+}
@@ -1,3 +1,4 @@
+use std::fmt::{Display, Formatter};
 use nom::error::Error;
 use crate::parser::Input;
 
@@ -5,4 +6,24 @@ use crate::parser::Input;
 pub enum ParserError<'a> {
     Nom(Error<Input<'a>>),
     Any(&'static str),
-}
\ No newline at end of file
+}
+
+impl<'a> ParserError<'a> {
+    /// Token the parser stopped at, if it didn't reach the end of the source.
+    pub fn anchor(&self) -> Option<&'a str> {
+        match self {
+            ParserError::Nom(error) => error.input.peek().map(|(_, x)| x),
+            ParserError::Any(_) => None,
+        }
+    }
+}
+
+impl Display for ParserError<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self, self.anchor()) {
+            (ParserError::Any(message), _) => write!(f, "{}", message),
+            (ParserError::Nom(_), Some(token)) => write!(f, "syntax error at `{}`", token),
+            (ParserError::Nom(_), None) => write!(f, "syntax error at the end of the file"),
+        }
+    }
+}
@@ -34,6 +34,8 @@ pub enum Token {
     Struct,
     #[token("int")]
     Int,
+    #[token("const")]
+    Const,
     #[token("if")]
     If,
     #[token("else")]
@@ -54,6 +56,12 @@ pub enum Token {
     Bang,
     #[token("->")]
     Arrow,
+    #[token(".")]
+    Dot,
+    #[token("...")]
+    Ellipsis,
+    #[token("&")]
+    Ampersand,
     #[token("=")]
     Eq,
     #[token("==")]
@@ -98,6 +106,8 @@ pub enum Token {
     CharConstant(Value),
     #[regex(r"'\\n'")]
     NewlineConstant,
+    #[regex(r#""([^"\\\n]|\\.)*""#)]
+    StringLiteral,
 
     #[regex(r"/\*([^*]|\*[^/])*\*/", logos::skip)]
     BlockComment,
@@ -111,6 +121,51 @@ pub enum Token {
     Error,
 }
 
+/// A comment, which the parser doesn't see. `trailing` if it follows some code on its line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comment<'a> {
+    pub text: &'a str,
+    pub trailing: bool,
+}
+
+/// Comments of `source`, in order. They are what stands between tokens, besides blanks.
+pub fn comments(source: &str) -> Vec<Comment<'_>> {
+    let mut comments = vec![];
+    let mut previous_end = 0;
+    let mut code_on_line = false;
+
+    let spans = Token::lexer(source)
+        .spanned()
+        .map(|(_, span)| span)
+        .chain(std::iter::once(source.len()..source.len()));
+
+    for span in spans {
+        let mut gap = &source[previous_end..span.start];
+        loop {
+            let blanks = gap.len() - gap.trim_start().len();
+            if gap[..blanks].contains('\n') {
+                code_on_line = false;
+            }
+            gap = &gap[blanks..];
+
+            let length = if gap.starts_with("//") {
+                gap.find(['\n', '\r']).unwrap_or(gap.len())
+            } else if gap.starts_with("/*") {
+                gap.find("*/").map_or(gap.len(), |x| x + 2)
+            } else {
+                break;
+            };
+            comments.push(Comment { text: &gap[..length], trailing: code_on_line });
+            code_on_line = true;
+            gap = &gap[length..];
+        }
+        previous_end = span.end;
+        code_on_line = true;
+    }
+
+    comments
+}
+
 #[cfg(test)]
 mod tests {
     use logos::Logos;
@@ -155,13 +210,14 @@ mod tests {
 
     #[test]
     fn test_all() {
-        let string = "struct int if else \
+        let string = "struct int const if else \
         while return sizeof + - / * !  -> && \
         || != == <= >= < > ( ) { } ; , x\
-         /* zzzz */ // zzzz \n 0 0x0 56 'a' '\\n'";
+         /* zzzz */ // zzzz \n 0 0x0 56 'a' '\\n' \"a\\\"b\" ...";
         _test_value(string, vec![
             Token::Struct,
             Token::Int,
+            Token::Const,
             Token::If,
             Token::Else,
             Token::While,
@@ -193,6 +249,8 @@ mod tests {
             Token::DecimalConstant(56),
             Token::CharConstant(97),
             Token::NewlineConstant,
+            Token::StringLiteral,
+            Token::Ellipsis,
         ])
     }
 }
\ No newline at end of file
@@ -1,9 +1,10 @@
 pub mod structure;
 pub mod error;
 pub mod lexer;
+pub mod printer;
 
 use nom::branch::alt;
-use nom::combinator::{map, opt};
+use nom::combinator::{cut, map, opt};
 use nom::multi::{many0, separated_list0, separated_list1};
 use logos_nom_bridge::{data_variant_parser, token_parser, Tokens};
 use nom::error::{ErrorKind, ParseError};
@@ -69,16 +70,17 @@ fn eof(input: Input) -> IResult<Input, ()> {
 }
 
 fn decl_fun(input: Input) -> IResult<Input, Fun> {
+    // Past `(`, this can only be a function : errors are reported where they are, not at the start of the function
     map(
-        tuple((formal, Token::Lpar, separated_list0(Token::Comma, formal), Token::Rpar, block)),
-        |(profile, _, args, _, body)| Fun::new(profile, args, body),
+        tuple((scalar_formal, Token::Lpar, cut(tuple((separated_list0(Token::Comma, formal), opt(ellipsis), Token::Rpar, block))))),
+        |(profile, _, (args, variadic, _, body))| Fun::new(profile, args, variadic.is_some(), body),
     )(input)
 }
 
 fn decl_struct(input: Input) -> IResult<Input, Struct> {
     map(
-        tuple((Token::Struct, ident, Token::Lbrace, many0(decl_var), Token::Rbrace, Token::Semicolon)),
-        |(_, name, _, fields, _, _)| Struct::new(name, fields.into_iter().flatten().collect()),
+        tuple((Token::Struct, ident, Token::Lbrace, many0(decl_field), anchor, Token::Rbrace, Token::Semicolon)),
+        |(_, name, _, fields, end, _, _)| Struct::new(name, fields.into_iter().flatten().collect(), end),
     )(input)
 }
 
@@ -86,8 +88,19 @@ fn star_ident(input: Input) -> IResult<Input, Ident> {
     map(tuple((Token::Star, ident)), |(_, x)| x)(input)
 }
 
-fn decl_var(input: Input) -> IResult<Input, Vec<Formal>> {
+fn struct_declarator(input: Input) -> IResult<Input, (Ident, bool)> {
     alt((
+        map(star_ident, |x| (x, true)),
+        map(ident, |x| (x, false)),
+    ))(input)
+}
+
+fn decl_field(input: Input) -> IResult<Input, Vec<Formal>> {
+    alt((
+        map(tuple((fun_pointer_formal, Token::Semicolon)), |(formal, _)| vec![formal]),
+        map(tuple((Token::Const, Token::Int, separated_list1(Token::Comma, ident), Token::Semicolon)),
+            |(_, _, idents, _)| idents.into_iter().map(|ident| Formal::new(ident, Typ::TInt).into_const()).collect(),
+        ),
         map(tuple((Token::Int, separated_list1(Token::Comma, ident), Token::Semicolon)),
             |(_, idents, _)| idents.into_iter().map(|ident| Formal::new(ident, Typ::TInt)).collect(),
         ),
@@ -97,44 +110,122 @@ fn decl_var(input: Input) -> IResult<Input, Vec<Formal>> {
     ))(input)
 }
 
+fn decl_var(input: Input) -> IResult<Input, Vec<(Formal, Option<Expr>)>> {
+    alt((
+        map(tuple((fun_pointer_formal, initializer, Token::Semicolon)), |(formal, init, _)| vec![(formal, init)]),
+        map(tuple((Token::Const, Token::Int, separated_list1(Token::Comma, tuple((ident, initializer))), Token::Semicolon)),
+            |(_, _, declarators, _)| declarators.into_iter().map(|(ident, init)| (Formal::new(ident, Typ::TInt).into_const(), init)).collect(),
+        ),
+        map(tuple((Token::Int, separated_list1(Token::Comma, tuple((ident, initializer))), Token::Semicolon)),
+            |(_, declarators, _)| declarators.into_iter().map(|(ident, init)| (Formal::new(ident, Typ::TInt), init)).collect(),
+        ),
+        map(tuple((Token::Struct, ident, separated_list1(Token::Comma, tuple((struct_declarator, initializer))), Token::Semicolon)),
+            |(_, struct_ident, declarators, _)| declarators.into_iter().map(|((ident, pointer), init)| {
+                if pointer {
+                    (Formal::new(ident, Typ::TStruct(struct_ident)), init)
+                } else {
+                    (Formal::new(ident, Typ::TStructValue(struct_ident)), init)
+                }
+            }).collect(),
+        ),
+    ))(input)
+}
+
+fn initializer(input: Input) -> IResult<Input, Option<Expr>> {
+    opt(map(tuple((Token::Eq, expr)), |(_, x)| x))(input)
+}
+
 fn formal(input: Input) -> IResult<Input, Formal> {
+    alt((
+        fun_pointer_formal,
+        scalar_formal,
+    ))(input)
+}
+
+fn scalar_formal(input: Input) -> IResult<Input, Formal> {
     alt((
         map(tuple((Token::Int, ident)), |(_, ident)| Formal::new(ident, Typ::TInt)),
         map(tuple((Token::Struct, ident, Token::Star, ident)), |(_, struct_ident, _, ident)| Formal::new(ident, Typ::TStruct(struct_ident)))
     ))(input)
 }
 
+fn scalar_typ(input: Input) -> IResult<Input, Typ> {
+    alt((
+        map(Token::Int, |_| Typ::TInt),
+        map(tuple((Token::Struct, ident, Token::Star)), |(_, struct_ident, _)| Typ::TStruct(struct_ident)),
+    ))(input)
+}
+
+fn fun_pointer_formal(input: Input) -> IResult<Input, Formal> {
+    let param = map(tuple((scalar_typ, opt(ident))), |(typ, _)| typ);
+
+    map(
+        tuple((scalar_typ, Token::Lpar, Token::Star, ident, Token::Rpar, Token::Lpar, separated_list0(Token::Comma, param), opt(ellipsis), Token::Rpar)),
+        |(typ, _, _, ident, _, _, params, variadic, _)| Formal::new(ident, Typ::TFun(Box::new(typ), params, variadic.is_some())),
+    )(input)
+}
+
+fn ellipsis(input: Input) -> IResult<Input, ()> {
+    map(tuple((Token::Comma, Token::Ellipsis)), |_| ())(input)
+}
+
+/// The next token, left in the input.
+fn anchor(input: Input) -> IResult<Input, Anchor> {
+    match input.peek() {
+        Some((_, x)) => Ok((input, Anchor(x))),
+        None => Err(nom::Err::Error(ParseError::from_error_kind(input, ErrorKind::Eof))),
+    }
+}
+
 fn ident(input: Input) -> IResult<Input, Ident> {
     map(Token::Ident, |x| x)(input)
 }
 
+fn string_literal(input: Input) -> IResult<Input, Ident> {
+    map(Token::StringLiteral, |x: Ident| &x[1..x.len() - 1])(input)
+}
+
 fn block(input: Input) -> IResult<Input, Block> {
     enum BlockElement<'a> {
         Stmt(Stmt<'a>),
-        DeclVar(Vec<Formal<'a>>),
+        DeclVar(Vec<(Formal<'a>, Option<Expr<'a>>)>),
     }
 
     map(
         tuple((
             Token::Lbrace,
             many0(
-                alt((
-                    map(decl_var, |x| BlockElement::DeclVar(x)),
-                    map(stmt, |x| BlockElement::Stmt(x))
+                tuple((
+                    anchor,
+                    alt((
+                        map(decl_var, |x| BlockElement::DeclVar(x)),
+                        map(stmt, |x| BlockElement::Stmt(x))
+                    ))
                 ))
             ),
+            anchor,
             Token::Rbrace
         )),
-        |(_, elements, _)| {
+        |(_, elements, end, _)| {
             let mut vars = vec![];
             let mut stmts = vec![];
-            for element in elements {
+            let mut anchors = vec![];
+            for (anchor, element) in elements {
                 match element {
-                    BlockElement::Stmt(x) => stmts.push(x),
-                    BlockElement::DeclVar(mut x) => vars.append(&mut x)
+                    BlockElement::Stmt(x) => {
+                        stmts.push(x);
+                        anchors.push(anchor);
+                    }
+                    BlockElement::DeclVar(x) => {
+                        for (formal, init) in x {
+                            stmts.push(Stmt::SDecl(formal.name(), init));
+                            vars.push(formal);
+                            anchors.push(anchor);
+                        }
+                    }
                 }
             }
-            Block::new(vars, stmts)
+            Block::new(vars, stmts, anchors, end)
         },
     )(input)
 }
@@ -176,6 +267,7 @@ fn expr(input: Input) -> IResult<Input, Expr> {
 fn primary_expr(input: Input) -> IResult<Input, Expr> {
     alt((
         map(integer, |x| Expr::EConst(x)),
+        map(string_literal, Expr::EString),
         map(ident, |x| Expr::EVar(x)),
         map(tuple((Token::Lpar, expr, Token::Rpar)), |(_, x, _)| x),
     ))(input)
@@ -192,6 +284,7 @@ fn postfix_expression(input: Input) -> IResult<Input, Expr> {
     enum PostfixSuffix<'a> {
         Call(Vec<Expr<'a>>),
         Access(Ident<'a>),
+        Dot(Ident<'a>),
     }
 
     fn postfix_suffix(input: Input) -> IResult<Input, PostfixSuffix> {
@@ -203,6 +296,10 @@ fn postfix_expression(input: Input) -> IResult<Input, Expr> {
             map(
                 tuple((Token::Arrow, ident)),
                 |(_, ident)| PostfixSuffix::Access(ident),
+            ),
+            map(
+                tuple((Token::Dot, ident)),
+                |(_, ident)| PostfixSuffix::Dot(ident),
             )
         ))(input)
     }
@@ -213,7 +310,8 @@ fn postfix_expression(input: Input) -> IResult<Input, Expr> {
             for suffix in suffixes {
                 match suffix {
                     PostfixSuffix::Call(args) => expr = Expr::ECall(Box::new(expr), args),
-                    PostfixSuffix::Access(ident) => expr = Expr::EArrow(Box::new(expr), ident)
+                    PostfixSuffix::Access(ident) => expr = Expr::EArrow(Box::new(expr), ident),
+                    PostfixSuffix::Dot(ident) => expr = Expr::EDot(Box::new(expr), ident)
                 }
             }
             expr
@@ -232,6 +330,7 @@ fn unary_expr(input: Input) -> IResult<Input, Expr> {
     alt((
         postfix_expression,
         map(tuple((unary_op, unary_expr)), |(op, expr)| Expr::EUnop(op, Box::new(expr))),
+        map(tuple((Token::Ampersand, unary_expr)), |(_, expr)| Expr::EAddrOf(Box::new(expr))),
         sizeof_expr
     ))(input)
 }
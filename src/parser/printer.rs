@@ -0,0 +1,382 @@
+use crate::common::Ident;
+use crate::parser::parse_file;
+use crate::parser::error::ParserError;
+use std::ops::Range;
+use logos::Logos;
+use crate::parser::lexer::{comments, Comment, Token};
+use crate::parser::structure::{operand, ASSIGN_PRECEDENCE, Block, File, Fun, INDENT, Stmt, Struct};
+
+/// Formats `source` : the file is printed back with canonical layout, keeping its comments.
+pub fn format_source(source: &str) -> Result<String, ParserError<'_>> {
+    let file = parse_file(source)?;
+    let mut printer = Printer::new(source);
+    printer.file(&file);
+    Ok(printer.into_output())
+}
+
+/// How a statement is laid out, whatever the tree it comes from : the parser's and the typer's
+/// printers share the layout of nested statements through `Layout`.
+pub enum Shape<'s, B, S> {
+    Skip,
+    /// A statement written on one line, without its `\n`.
+    Line(String),
+    Block(&'s B),
+    While(String, &'s S),
+    /// The head of the `if`, its statement and its `else` statement.
+    If(String, &'s S, &'s S),
+}
+
+pub trait Layout {
+    type Block;
+    type Stmt;
+
+    fn output(&mut self) -> &mut String;
+
+    fn shape<'s>(&self, stmt: &'s Self::Stmt) -> Shape<'s, Self::Block, Self::Stmt>;
+
+    /// Writes `block` from the current position to its closing brace.
+    fn block(&mut self, block: &Self::Block, indent: usize);
+
+    fn indent(&mut self, indent: usize) {
+        for _ in 0..indent {
+            self.output().push_str(INDENT);
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        self.output().push_str(line);
+        self.output().push('\n');
+    }
+
+    /// Writes `stmt` from the current position to the end of its last line.
+    /// When `closed`, an `if` without `else` gets an empty one, so that an `else` which follows
+    /// isn't taken as its own.
+    fn stmt(&mut self, stmt: &Self::Stmt, indent: usize, closed: bool) {
+        match self.shape(stmt) {
+            Shape::Skip => self.line(";"),
+            Shape::Line(line) => self.line(&line),
+            Shape::Block(block) => {
+                self.block(block, indent);
+                self.output().push('\n');
+            }
+            Shape::While(head, body) => {
+                self.output().push_str(&head);
+                self.body(body, indent, closed)
+            }
+            Shape::If(head, stmt_if, stmt_else) => {
+                let has_else = closed || !matches!(self.shape(stmt_else), Shape::Skip);
+                self.output().push_str(&head);
+                if !has_else {
+                    return self.body(stmt_if, indent, false);
+                }
+
+                if let Shape::Block(block) = self.shape(stmt_if) {
+                    self.output().push(' ');
+                    self.block(block, indent);
+                    self.output().push_str(" else");
+                } else {
+                    self.body(stmt_if, indent, true);
+                    self.indent(indent);
+                    self.output().push_str("else");
+                }
+
+                match self.shape(stmt_else) {
+                    Shape::If(_, _, _) => {
+                        self.output().push(' ');
+                        self.stmt(stmt_else, indent, closed)
+                    }
+                    _ => self.body(stmt_else, indent, closed),
+                }
+            }
+        }
+    }
+
+    /// Writes the body of an `if` or a `while` : a block on the same line, or an indented statement.
+    fn body(&mut self, body: &Self::Stmt, indent: usize, closed: bool) {
+        match self.shape(body) {
+            Shape::Block(block) => {
+                self.output().push(' ');
+                self.block(block, indent);
+                self.output().push('\n');
+            }
+            Shape::Skip => self.line(" ;"),
+            _ => {
+                self.output().push('\n');
+                self.indent(indent + 1);
+                self.stmt(body, indent + 1, closed)
+            }
+        }
+    }
+}
+
+/// Writes files as mini-C. Comments are written before the first node which follows them in
+/// the source, or next to the token they follow when they stand among the code of a node.
+pub struct Printer<'a> {
+    output: String,
+    source: &'a str,
+    comments: Vec<Comment<'a>>,
+    next_comment: usize,
+    /// Where the code written since the last anchor starts, in the source and in the output
+    segment: (usize, usize),
+}
+
+/// A comment to insert in the output at `position`, on a line of its own if `own_line`.
+struct Insertion<'a> {
+    position: usize,
+    comment: Comment<'a>,
+    own_line: bool,
+}
+
+enum Item<'a, 'b> {
+    Struct(&'b Struct<'a>),
+    Fun(&'b Fun<'a>),
+}
+
+impl<'a> Printer<'a> {
+    /// Printer of nodes parsed from `source`, keeping its comments.
+    pub fn new(source: &'a str) -> Printer<'a> {
+        Printer { output: String::new(), source, comments: comments(source), next_comment: 0, segment: (0, 0) }
+    }
+
+    pub fn into_output(mut self) -> String {
+        self.comments_before(None, 0);
+        self.output
+    }
+
+    fn offset(&self, text: &str) -> usize {
+        text.as_ptr() as usize - self.source.as_ptr() as usize
+    }
+
+    /// Writes the comments standing before `anchor`, or all the remaining ones.
+    fn comments_before(&mut self, anchor: Option<Ident<'a>>, indent: usize) {
+        if self.next_comment == self.comments.len() {
+            return;
+        }
+
+        let position = anchor.map_or(self.source.len(), |anchor| self.offset(anchor));
+        if position > self.segment.0 {
+            self.inner_comments(position);
+        }
+
+        while let Some(comment) = self.comments.get(self.next_comment).copied() {
+            if anchor.is_some_and(|anchor| comment.text.as_ptr() >= anchor.as_ptr()) {
+                break;
+            }
+
+            if comment.trailing && self.output.ends_with('\n') && !self.output.ends_with("\n\n") {
+                self.output.pop();
+                self.output.push(' ');
+            } else {
+                self.indent(indent);
+            }
+            self.line(comment.text);
+            self.next_comment += 1;
+        }
+
+        if position > self.segment.0 {
+            self.segment = (position, self.output.len());
+        }
+    }
+
+    /// Inserts the comments which stand among the code of the segment ending at `end`, next to the
+    /// tokens written for the code around them. The tokens of the source and of the output mostly
+    /// match, parentheses and declarations aside : they are paired by their longest common subsequence.
+    fn inner_comments(&mut self, end: usize) {
+        let (start, output_start) = self.segment;
+        let source_tokens = tokens(&self.source[start..end], start);
+        let output_tokens = tokens(&self.output[output_start..], output_start);
+        let pairs = common_subsequence(&source_tokens, &output_tokens);
+
+        let mut insertions = vec![];
+        let mut index = self.next_comment;
+        while let Some(comment) = self.comments.get(index).copied() {
+            let offset = self.offset(comment.text);
+            if offset >= end {
+                break;
+            }
+
+            // Paired tokens around the comment, the nearest ones on each side
+            let before = source_tokens.iter().take_while(|(_, range)| range.end <= offset).count();
+            let previous = pairs[..before].iter().rev().flatten().next();
+            let next = pairs[before..].iter().flatten().next();
+
+            let insertion = match (previous, next) {
+                (Some(&previous), _) if comment.trailing => Some(Insertion {
+                    position: output_tokens[previous].1.end,
+                    comment,
+                    own_line: false,
+                }),
+                (_, Some(&next)) if !comment.trailing => Some(Insertion {
+                    position: self.output[..output_tokens[next].1.start].rfind('\n').map_or(output_start, |x| x + 1).max(output_start),
+                    comment,
+                    own_line: true,
+                }),
+                _ => None,
+            };
+            match insertion {
+                Some(insertion) => {
+                    insertions.push(insertion);
+                    self.comments.remove(index);
+                }
+                None => index += 1,
+            }
+        }
+
+        // From the end, so that the positions still to come stay right
+        for insertion in insertions.into_iter().rev() {
+            self.insert(insertion);
+        }
+    }
+
+    fn insert(&mut self, insertion: Insertion) {
+        let Insertion { position, comment, own_line } = insertion;
+        let line_start = self.output[..position].rfind('\n').map_or(0, |x| x + 1);
+        let line = &self.output[line_start..];
+        let indent = line[..line.len() - line.trim_start_matches(' ').len()].to_string();
+
+        let rest = &self.output[position..];
+        let (replaced, inserted) = if own_line {
+            (0, format!("{}{}\n", indent, comment.text))
+        } else if rest.is_empty() || rest.starts_with('\n') {
+            (0, format!(" {}", comment.text))
+        } else if comment.text.starts_with("//") {
+            // Nothing can follow a line comment on its line : the code after it goes to the next one
+            let blanks = rest.len() - rest.trim_start_matches(' ').len();
+            (blanks, format!(" {}\n{}", comment.text, indent))
+        } else if rest.starts_with([' ', ';', ')', ',']) {
+            (0, format!(" {}", comment.text))
+        } else {
+            (0, format!(" {} ", comment.text))
+        };
+        self.output.replace_range(position..position + replaced, &inserted);
+    }
+
+    pub fn file(&mut self, file: &File<'a>) {
+        // Structures and functions are kept separately : put them back in the order of the source
+        let mut items = file.structs()
+            .iter()
+            .map(|x| (x.name().as_ptr(), Item::Struct(x)))
+            .chain(file.funs().iter().map(|x| (x.profile().name().as_ptr(), Item::Fun(x))))
+            .collect::<Vec<_>>();
+        items.sort_by_key(|(position, _)| *position);
+
+        for (index, (_, item)) in items.into_iter().enumerate() {
+            if index > 0 {
+                self.output.push('\n');
+            }
+            match item {
+                Item::Struct(structure) => self.structure(structure),
+                Item::Fun(fun) => self.fun(fun),
+            }
+        }
+    }
+
+    fn structure(&mut self, structure: &Struct<'a>) {
+        self.comments_before(Some(structure.name()), 0);
+        self.line(&format!("struct {} {{", structure.name()));
+        for field in structure.fields() {
+            self.comments_before(Some(field.name()), 1);
+            self.indent(1);
+            self.line(&format!("{};", field));
+        }
+        self.comments_before(Some(structure.end().0), 1);
+        self.line("};");
+    }
+
+    fn fun(&mut self, fun: &Fun<'a>) {
+        self.comments_before(Some(fun.profile().name()), 0);
+        let args = fun.args()
+            .iter()
+            .map(|arg| arg.to_string())
+            .chain(fun.variadic().then(|| String::from("...")))
+            .collect::<Vec<_>>();
+        self.output.push_str(&format!("{}({}) ", fun.profile(), args.join(", ")));
+        self.block(fun.body(), 0);
+        self.output.push('\n');
+    }
+}
+
+impl<'a> Layout for Printer<'a> {
+    type Block = Block<'a>;
+    type Stmt = Stmt<'a>;
+
+    fn output(&mut self) -> &mut String {
+        &mut self.output
+    }
+
+    fn shape<'s>(&self, stmt: &'s Stmt<'a>) -> Shape<'s, Block<'a>, Stmt<'a>> {
+        match stmt {
+            Stmt::SSkip => Shape::Skip,
+            Stmt::SExpr(expr) => Shape::Line(format!("{};", expr)),
+            Stmt::SReturn(expr) => Shape::Line(format!("return {};", expr)),
+            Stmt::SDecl(name, _) => Shape::Line(format!("{};", name)),
+            Stmt::SBlock(block) => Shape::Block(block),
+            Stmt::SWhile(condition, body) => Shape::While(format!("while ({})", condition), body),
+            Stmt::SIf(condition, stmt_if, stmt_else) => Shape::If(format!("if ({})", condition), stmt_if, stmt_else),
+        }
+    }
+
+    fn block(&mut self, block: &Block<'a>, indent: usize) {
+        // Declarations come in the same order as their `SDecl` statements
+        let mut vars = block.vars().iter();
+
+        self.line("{");
+        for (index, stmt) in block.stmts().iter().enumerate() {
+            self.comments_before(block.anchors().get(index).map(|x| x.0), indent + 1);
+            self.indent(indent + 1);
+
+            let formal = match stmt {
+                Stmt::SDecl(_, _) => vars.next(),
+                _ => None,
+            };
+            match (stmt, formal) {
+                (Stmt::SDecl(_, Some(init)), Some(formal)) => {
+                    self.line(&format!("{} = {};", formal, operand(init, ASSIGN_PRECEDENCE)))
+                }
+                (Stmt::SDecl(_, None), Some(formal)) => self.line(&format!("{};", formal)),
+                (stmt, _) => self.stmt(stmt, indent + 1, false),
+            }
+        }
+        self.comments_before(Some(block.end().0), indent + 1);
+        self.indent(indent);
+        self.output.push('}');
+    }
+}
+
+/// Tokens of `text` and where they are, `text` starting at `offset`.
+fn tokens(text: &str, offset: usize) -> Vec<(&str, Range<usize>)> {
+    Token::lexer(text)
+        .spanned()
+        .map(|(_, span)| (&text[span.clone()], span.start + offset..span.end + offset))
+        .collect()
+}
+
+/// For each token of `source`, the index of the token of `output` it is paired with, if any.
+fn common_subsequence(source: &[(&str, Range<usize>)], output: &[(&str, Range<usize>)]) -> Vec<Option<usize>> {
+    // `lengths[i][j]` is the length of the longest common subsequence of `source[i..]` and `output[j..]`
+    let mut lengths = vec![vec![0; output.len() + 1]; source.len() + 1];
+    for i in (0..source.len()).rev() {
+        for j in (0..output.len()).rev() {
+            lengths[i][j] = if source[i].0 == output[j].0 {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = vec![None; source.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < source.len() && j < output.len() {
+        if source[i].0 == output[j].0 {
+            pairs[i] = Some(j);
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
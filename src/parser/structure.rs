@@ -1,6 +1,8 @@
+use std::fmt::{Display, Formatter};
 use derive_new::new;
 use derive_getters::Getters;
 use crate::common::{Value, Ident};
+use crate::parser::printer::{Layout, Printer};
 
 #[derive(new, Debug, PartialEq, Getters)]
 pub struct File<'a> {
@@ -12,31 +14,48 @@ pub struct File<'a> {
 pub enum Typ<'a> {
     TInt,
     TStruct(Ident<'a>),
+    TStructValue(Ident<'a>),
+    TFun(Box<Typ<'a>>, Vec<Typ<'a>>, bool),
 }
 
 #[derive(new, Debug, PartialEq, Getters)]
 pub struct Formal<'a> {
     name: Ident<'a>,
     typ: Typ<'a>,
+    #[new(default)]
+    constant: bool,
 }
 
-#[derive(new, Debug, PartialEq, Getters)]
+impl<'a> Formal<'a> {
+    pub fn into_const(self) -> Formal<'a> {
+        Formal { constant: true, ..self }
+    }
+}
+
+#[derive(new, Debug, Getters)]
 pub struct Struct<'a> {
     name: Ident<'a>,
     fields: Vec<Formal<'a>>,
+    /// Closing brace
+    end: Anchor<'a>,
 }
 
 #[derive(new, Debug, PartialEq, Getters)]
 pub struct Fun<'a> {
     profile: Formal<'a>,
     args: Vec<Formal<'a>>,
+    variadic: bool,
     body: Block<'a>,
 }
 
-#[derive(new, Debug, PartialEq, Getters)]
+#[derive(new, Debug, Getters)]
 pub struct Block<'a> {
     vars: Vec<Formal<'a>>,
     stmts: Vec<Stmt<'a>>,
+    /// First token of each statement
+    anchors: Vec<Anchor<'a>>,
+    /// Closing brace
+    end: Anchor<'a>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -47,13 +66,17 @@ pub enum Stmt<'a> {
     SWhile(Expr<'a>, Box<Stmt<'a>>),
     SBlock(Block<'a>),
     SReturn(Expr<'a>),
+    SDecl(Ident<'a>, Option<Expr<'a>>),
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Expr<'a> {
     EConst(Value),
+    EString(Ident<'a>),
     EVar(Ident<'a>),
     EArrow(Box<Expr<'a>>, Ident<'a>),
+    EDot(Box<Expr<'a>>, Ident<'a>),
+    EAddrOf(Box<Expr<'a>>),
     EAssign(Box<Expr<'a>>, Box<Expr<'a>>),
     EUnop(Unop, Box<Expr<'a>>),
     EBinop(Binop, Box<Expr<'a>>, Box<Expr<'a>>),
@@ -82,3 +105,223 @@ pub enum Binop {
     BAnd,
     BOr,
 }
+
+pub const INDENT: &str = "    ";
+
+/// Where a node starts in the source, to keep the comments around it when formatting.
+/// Anchors aren't part of the syntax : nodes compare equal whatever their anchors.
+#[derive(Debug, Clone, Copy)]
+pub struct Anchor<'a>(pub Ident<'a>);
+
+impl PartialEq for Struct<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.fields == other.fields
+    }
+}
+
+impl PartialEq for Block<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.vars == other.vars && self.stmts == other.stmts
+    }
+}
+
+/// Binding strength of the expressions which aren't binary operations, higher binds tighter.
+pub const ASSIGN_PRECEDENCE: u8 = 0;
+pub const UNARY_PRECEDENCE: u8 = 7;
+pub const POSTFIX_PRECEDENCE: u8 = 8;
+pub const PRIMARY_PRECEDENCE: u8 = 9;
+
+impl Binop {
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Binop::BOr => 1,
+            Binop::BAnd => 2,
+            Binop::BEq | Binop::BNeq => 3,
+            Binop::BLt | Binop::BGt | Binop::BGe | Binop::BLe => 4,
+            Binop::BAdd | Binop::BSub => 5,
+            Binop::BMul | Binop::BDiv => 6,
+        }
+    }
+}
+
+impl Expr<'_> {
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Expr::EConst(_) | Expr::EString(_) | Expr::EVar(_) => PRIMARY_PRECEDENCE,
+            Expr::EArrow(_, _) | Expr::EDot(_, _) | Expr::ECall(_, _) => POSTFIX_PRECEDENCE,
+            Expr::EAddrOf(_) | Expr::EUnop(_, _) | Expr::ESizeof(_) => UNARY_PRECEDENCE,
+            Expr::EBinop(op, _, _) => op.precedence(),
+            Expr::EAssign(_, _) => ASSIGN_PRECEDENCE,
+        }
+    }
+}
+
+/// Writes `operand`, in parentheses if it binds looser than `precedence`.
+pub fn write_operand(f: &mut Formatter<'_>, operand: impl Display, operand_precedence: u8, precedence: u8) -> std::fmt::Result {
+    if operand_precedence < precedence {
+        write!(f, "({})", operand)
+    } else {
+        write!(f, "{}", operand)
+    }
+}
+
+/// Prefixes an operator, keeping it apart from an operand starting with the same symbol
+/// so that `- -x` isn't read back as `--x`.
+pub fn prefix(operator: &str, operand: String) -> String {
+    if operand.starts_with(operator) {
+        format!("{} {}", operator, operand)
+    } else {
+        format!("{}{}", operator, operand)
+    }
+}
+
+pub fn write_indent(f: &mut Formatter<'_>, indent: usize) -> std::fmt::Result {
+    for _ in 0..indent {
+        write!(f, "{}", INDENT)?;
+    }
+    Ok(())
+}
+
+/// Writes `typ` declaring `name`, as in `struct S *p` or `int (*f)(int)`.
+fn write_declaration(f: &mut Formatter<'_>, typ: &Typ, name: &str) -> std::fmt::Result {
+    match typ {
+        Typ::TInt if name.is_empty() => write!(f, "int"),
+        Typ::TInt => write!(f, "int {}", name),
+        Typ::TStruct(s) => write!(f, "struct {} *{}", s, name),
+        Typ::TStructValue(s) if name.is_empty() => write!(f, "struct {}", s),
+        Typ::TStructValue(s) => write!(f, "struct {} {}", s, name),
+        Typ::TFun(typ, args, variadic) => {
+            let mut declarator = format!("(*{})(", name);
+            for (index, arg) in args.iter().enumerate() {
+                if index > 0 {
+                    declarator.push_str(", ");
+                }
+                declarator.push_str(&arg.to_string());
+            }
+            if *variadic {
+                declarator.push_str(", ...");
+            }
+            declarator.push(')');
+            write_declaration(f, typ, &declarator)
+        }
+    }
+}
+
+impl Display for Formal<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.constant {
+            write!(f, "const ")?;
+        }
+        write_declaration(f, &self.typ, self.name)
+    }
+}
+
+impl Display for Typ<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_declaration(f, self, "")
+    }
+}
+
+impl Display for File<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut printer = Printer::new("");
+        printer.file(self);
+        write!(f, "{}", printer.into_output())
+    }
+}
+
+impl Display for Block<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut printer = Printer::new("");
+        printer.block(self, 0);
+        write!(f, "{}", printer.into_output())
+    }
+}
+
+impl Display for Stmt<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut printer = Printer::new("");
+        printer.stmt(self, 0, false);
+        write!(f, "{}", printer.into_output())
+    }
+}
+
+impl Display for Expr<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::EConst(x) if *x < 0 => write!(f, "({})", x),
+            Expr::EConst(x) => write!(f, "{}", x),
+            Expr::EString(x) => write!(f, "\"{}\"", x),
+            Expr::EVar(x) => write!(f, "{}", x),
+            Expr::EArrow(expr, field) => {
+                write_operand(f, expr, expr.precedence(), POSTFIX_PRECEDENCE)?;
+                write!(f, "->{}", field)
+            }
+            Expr::EDot(expr, field) => {
+                write_operand(f, expr, expr.precedence(), POSTFIX_PRECEDENCE)?;
+                write!(f, ".{}", field)
+            }
+            Expr::EAddrOf(expr) => write!(f, "{}", prefix("&", operand(expr, UNARY_PRECEDENCE))),
+            Expr::EUnop(op, expr) => write!(f, "{}", prefix(&op.to_string(), operand(expr, UNARY_PRECEDENCE))),
+            Expr::EAssign(assignee, assigned) => {
+                write_operand(f, assignee, assignee.precedence(), UNARY_PRECEDENCE)?;
+                write!(f, " = ")?;
+                write_operand(f, assigned, assigned.precedence(), ASSIGN_PRECEDENCE)
+            }
+            Expr::EBinop(op, expr_1, expr_2) => {
+                write_operand(f, expr_1, expr_1.precedence(), op.precedence())?;
+                write!(f, " {} ", op)?;
+                write_operand(f, expr_2, expr_2.precedence(), op.precedence() + 1)
+            }
+            Expr::ECall(callee, args) => {
+                write_operand(f, callee, callee.precedence(), POSTFIX_PRECEDENCE)?;
+                write!(f, "(")?;
+                for (index, arg) in args.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write_operand(f, arg, arg.precedence(), ASSIGN_PRECEDENCE)?;
+                }
+                write!(f, ")")
+            }
+            Expr::ESizeof(name) => write!(f, "sizeof(struct {})", name),
+        }
+    }
+}
+
+pub(crate) fn operand(expr: &Expr, precedence: u8) -> String {
+    if expr.precedence() < precedence {
+        format!("({})", expr)
+    } else {
+        expr.to_string()
+    }
+}
+
+impl Display for Unop {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Unop::UNot => write!(f, "!"),
+            Unop::UMinus => write!(f, "-"),
+        }
+    }
+}
+
+impl Display for Binop {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Binop::BEq => "==",
+            Binop::BNeq => "!=",
+            Binop::BLt => "<",
+            Binop::BGt => ">",
+            Binop::BGe => ">=",
+            Binop::BLe => "<=",
+            Binop::BAdd => "+",
+            Binop::BSub => "-",
+            Binop::BMul => "*",
+            Binop::BDiv => "/",
+            Binop::BAnd => "&&",
+            Binop::BOr => "||",
+        };
+        write!(f, "{}", symbol)
+    }
+}
@@ -1,24 +1,123 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
 use derive_new::new;
-use crate::common::{Ident, StackOffset, Stdout, Value};
-use crate::rtl::interpreter::{RtlInterpFun, RtlInterpreterResult};
+use crate::common::{FUN_ADDRESS_BASE, Ident, StackOffset, Stdout, Value};
+use crate::rtl::interpreter::{Limits, RtlInterpFun, RtlInterpreterResult};
 use crate::rtl::interpreter::error::RtlInterpreterError;
+use crate::rtl::interpreter::observer::Observer;
 use crate::rtl::structure::register::PseudoRegister;
 
 const DEFAULT_FIELD_VALUE: Value = 0;
 const DEFAULT_REGISTER_VALUE: Value = 0;
+const FIELD_SIZE: Value = 8;
 
+/// Fields of a structure allocated on the heap or the stack, spanning `size` bytes.
 #[derive(new)]
+pub struct Block {
+    pub size: Value,
+    #[new(default)]
+    pub fields: HashMap<StackOffset, Value>,
+}
+
+/// Structures allocated by the program, at consecutive addresses.
+#[derive(new)]
+pub struct Memory {
+    /// Address of the next structure allocated.
+    pub next_address: Value,
+    /// Bytes the structures may span in all.
+    pub heap_size: Value,
+    #[new(default)]
+    pub allocated: Value,
+    #[new(default)]
+    pub blocks: HashMap<Value, Block>,
+}
+
 pub struct Context<'a> {
     pub stdout: Rc<Stdout>,
     pub funs: Rc<HashMap<Ident<'a>, Rc<dyn RtlInterpFun<'a> + 'a>>>,
+    pub fun_addresses: Rc<Vec<Ident<'a>>>,
     pub regs: Rc<RefCell<HashMap<PseudoRegister, Value>>>,
-    pub memory: Rc<RefCell<HashMap<Value, HashMap<StackOffset, Value>>>>,
+    pub memory: Rc<RefCell<Memory>>,
+    /// Values of the arguments passed beyond the formal ones of a variadic function.
+    pub varargs: Rc<Vec<Value>>,
+    /// Structures on the stack of the function, freed when it returns.
+    pub frame: Rc<RefCell<Vec<Value>>>,
+    /// Number of calls the function runs in.
+    pub depth: usize,
+    pub limits: Limits,
+    /// Instructions the program may still run.
+    pub fuel: Rc<Cell<u64>>,
+    pub observer: Rc<RefCell<dyn Observer<'a> + 'a>>,
 }
 
-impl Context<'_> {
+impl<'a> Context<'a> {
+    pub fn new(
+        stdout: Rc<Stdout>,
+        funs: Rc<HashMap<Ident<'a>, Rc<dyn RtlInterpFun<'a> + 'a>>>,
+        fun_addresses: Rc<Vec<Ident<'a>>>,
+        memory: Rc<RefCell<Memory>>,
+        limits: Limits,
+        observer: Rc<RefCell<dyn Observer<'a> + 'a>>,
+    ) -> Context<'a> {
+        Context {
+            stdout,
+            funs,
+            fun_addresses,
+            regs: Rc::new(RefCell::new(HashMap::new())),
+            memory,
+            varargs: Rc::new(vec![]),
+            frame: Rc::new(RefCell::new(vec![])),
+            depth: 0,
+            limits,
+            fuel: Rc::new(Cell::new(limits.fuel)),
+            observer,
+        }
+    }
+
+    /// Context of a function called from this one, given the arguments passed beyond its formal ones.
+    pub fn callee(&self, varargs: Vec<Value>) -> Context<'a> {
+        Context {
+            stdout: self.stdout.clone(),
+            funs: self.funs.clone(),
+            fun_addresses: self.fun_addresses.clone(),
+            regs: Rc::new(RefCell::new(HashMap::new())),
+            memory: self.memory.clone(),
+            varargs: Rc::new(varargs),
+            frame: Rc::new(RefCell::new(vec![])),
+            depth: self.depth + 1,
+            limits: self.limits,
+            fuel: self.fuel.clone(),
+            observer: self.observer.clone(),
+        }
+    }
+
+    /// Uses up the fuel of an instruction.
+    pub fn burn(&self) -> RtlInterpreterResult<()> {
+        match self.fuel.get().checked_sub(1) {
+            Some(fuel) => {
+                self.fuel.set(fuel);
+                Ok(())
+            }
+            None => Err(RtlInterpreterError::OutOfFuel(self.limits.fuel)),
+        }
+    }
+
+    pub fn fun_address(&self, name: Ident<'a>) -> RtlInterpreterResult<Value> {
+        self.fun_addresses
+            .binary_search(&name)
+            .map(|index| FUN_ADDRESS_BASE + index as Value)
+            .map_err(|_| RtlInterpreterError::FunctionDoesNotExist(String::from(name)))
+    }
+
+    pub fn fun_at(&self, address: Value) -> RtlInterpreterResult<Ident<'a>> {
+        usize::try_from(address - FUN_ADDRESS_BASE)
+            .ok()
+            .and_then(|index| self.fun_addresses.get(index))
+            .cloned()
+            .ok_or(RtlInterpreterError::InvalidFunctionAddress(address))
+    }
+
     pub fn put(&self, register: &PseudoRegister, value: Value) {
         self.regs.borrow_mut().insert(register.clone(), value);
     }
@@ -29,24 +128,56 @@ impl Context<'_> {
 
     pub fn load(&self, register: &PseudoRegister, offset: &StackOffset) -> RtlInterpreterResult<Value> {
         let address = self.get(register);
+        let mut memory = self.memory.borrow_mut();
+        let block = memory.block(address, offset)?;
 
-        Ok(*self.memory
-            .borrow()
-            .get(&address)
-            .ok_or(RtlInterpreterError::UnallocatedMemory(address))?
-            .get(offset)
-            .unwrap_or(&DEFAULT_FIELD_VALUE))
+        Ok(*block.fields.get(offset).unwrap_or(&DEFAULT_FIELD_VALUE))
     }
 
     pub fn store(&self, register: &PseudoRegister, offset: &StackOffset, value: &Value) -> RtlInterpreterResult<()> {
         let address = self.get(register);
+        let mut memory = self.memory.borrow_mut();
+        let block = memory.block(address, offset)?;
 
-        self.memory
-            .borrow_mut()
-            .get_mut(&address)
-            .ok_or(RtlInterpreterError::UnallocatedMemory(address))?
-            .insert(*offset, *value);
+        block.fields.insert(*offset, *value);
 
         Ok(())
     }
 }
+
+impl Memory {
+    pub fn allocate(&mut self, size: Value) -> RtlInterpreterResult<Value> {
+        let allocated = self.allocated.saturating_add(size.max(0));
+        if allocated > self.heap_size {
+            return Err(RtlInterpreterError::OutOfMemory(self.heap_size));
+        }
+
+        let address = self.next_address;
+        self.next_address += 1;
+        self.allocated = allocated;
+        self.blocks.insert(address, Block::new(size));
+        Ok(address)
+    }
+
+    /// Releases the structure at the address, so that its bytes may be allocated again.
+    pub fn free(&mut self, address: Value) {
+        if let Some(block) = self.blocks.remove(&address) {
+            self.allocated -= block.size.max(0);
+        }
+    }
+
+    /// The block allocated at the address, if the field at the offset lies within it.
+    fn block(&mut self, address: Value, offset: &StackOffset) -> RtlInterpreterResult<&mut Block> {
+        if address == 0 {
+            return Err(RtlInterpreterError::NullDereference);
+        }
+
+        let block = self.blocks.get_mut(&address).ok_or(RtlInterpreterError::DanglingDereference(address))?;
+
+        if *offset < 0 || *offset as Value + FIELD_SIZE > block.size {
+            return Err(RtlInterpreterError::FieldOutOfBounds(address, *offset));
+        }
+
+        Ok(block)
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeSet;
+use std::io::{BufRead, Write};
+use std::iter::once;
+use itertools::Itertools;
+use crate::common::{Execution, Ident, Stdout};
+use crate::rtl::interpreter::context::Context;
+use crate::rtl::interpreter::error::RtlInterpreterError;
+use crate::rtl::interpreter::observer::Observer;
+use crate::rtl::interpreter::RtlInterpreterResult;
+use crate::rtl::structure::{BlockIdent, File, Fun, Instr};
+use crate::rtl::structure::graph::DisplayableBlockIdent;
+use crate::rtl::structure::label::Label;
+use crate::rtl::structure::register::PseudoRegister;
+
+const PROMPT: &str = "(minic) ";
+const HELP: &str = "\
+step, s              run the next instruction
+continue, c          run up to the next breakpoint
+break, b <where>     stop on entering a function, or at an RTL label
+delete, d <where>    remove a breakpoint
+print, p <name>      print a variable, or a pseudo-register like #3
+locals               print the variables of the function
+heap                 print the structures allocated
+backtrace, bt        print the call stack
+quit, q              stop the program";
+
+/// Runs the program step by step, on commands read from the input.
+pub struct Debugger<'a, R: BufRead, W: Write> {
+    input: R,
+    output: W,
+    /// Functions and labels of the program, where a breakpoint may be set.
+    places: BTreeSet<String>,
+    /// Functions and labels to stop at.
+    breakpoints: BTreeSet<String>,
+    /// Stops before the next instruction, whatever the breakpoints.
+    stepping: bool,
+    /// Functions being run, with the label of their current instruction, the innermost last.
+    frames: Vec<(Ident<'a>, Label)>,
+    /// Number of characters of the program output already printed.
+    printed: usize,
+}
+
+impl<'a, R: BufRead, W: Write> Debugger<'a, R, W> {
+    /// The debugger stops before the first instruction of `main`.
+    pub fn new(file: &File<'a>, input: R, output: W) -> Debugger<'a, R, W> {
+        let places = file.funs
+            .values()
+            .flat_map(|fun| once(fun.name.to_string()).chain(fun.graph.instrs.keys().map(Label::to_string)))
+            .collect();
+        Debugger { input, output, places, breakpoints: BTreeSet::new(), stepping: true, frames: vec![], printed: 0 }
+    }
+
+    /// Prints the rest of the output of the program, and how it ended.
+    pub fn finish(&mut self, result: &RtlInterpreterResult<Execution>) -> std::io::Result<()> {
+        match result {
+            Ok(execution) => {
+                self.print_stdout(&execution.stdout)?;
+                match execution.status {
+                    0 => writeln!(self.output, "Program exited normally"),
+                    status => writeln!(self.output, "Program exited with code {}", status),
+                }
+            }
+            Err(RtlInterpreterError::Stopped) => writeln!(self.output, "Program stopped"),
+            Err(error) => writeln!(self.output, "Program failed : {}", error),
+        }
+    }
+
+    fn print_stdout(&mut self, stdout: &Stdout) -> std::io::Result<()> {
+        let stdout = stdout.to_string();
+        let new = stdout.chars().skip(self.printed).collect::<String>();
+        self.printed += new.chars().count();
+        write!(self.output, "{}", new)
+    }
+
+    fn should_stop(&self, fun: &Fun<'a>, label: &Label) -> bool {
+        self.stepping
+            || self.breakpoints.contains(&label.to_string())
+            || (*label == fun.entry && self.breakpoints.contains(fun.name))
+    }
+
+    /// Reads commands up to one which resumes the program.
+    fn prompt(&mut self, fun: &Fun<'a>, context: &Context<'a>) -> std::io::Result<RtlInterpreterResult<()>> {
+        loop {
+            write!(self.output, "{}", PROMPT)?;
+            self.output.flush()?;
+
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                return Ok(Err(RtlInterpreterError::Stopped));
+            }
+
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next()) {
+                (None, _) => {}
+                (Some("step" | "s"), _) => {
+                    self.stepping = true;
+                    return Ok(Ok(()));
+                }
+                (Some("continue" | "c"), _) => {
+                    self.stepping = false;
+                    return Ok(Ok(()));
+                }
+                (Some("break" | "b"), Some(place)) if self.places.contains(place) => {
+                    self.breakpoints.insert(String::from(place));
+                    writeln!(self.output, "Breakpoint at {}", place)?;
+                }
+                (Some("break" | "b"), Some(place)) => writeln!(self.output, "No function or label `{}`", place)?,
+                (Some("delete" | "d"), Some(place)) => {
+                    if !self.breakpoints.remove(place) {
+                        writeln!(self.output, "No breakpoint at {}", place)?;
+                    }
+                }
+                (Some("print" | "p"), Some(name)) => self.print(fun, context, name)?,
+                (Some("locals"), _) => {
+                    for (ident, register) in variables(fun).sorted_by_key(|(ident, _)| declaration_order(ident)) {
+                        writeln!(self.output, "{} = {}", DisplayableBlockIdent(ident), context.get(register))?;
+                    }
+                }
+                (Some("heap"), _) => {
+                    let memory = context.memory.borrow();
+                    for (address, block) in memory.blocks.iter().sorted_by_key(|(address, _)| **address) {
+                        let fields = block.fields
+                            .iter()
+                            .sorted()
+                            .map(|(offset, value)| format!("{}: {}", offset, value))
+                            .join(", ");
+                        writeln!(self.output, "{} [{} bytes] {{{}}}", address, block.size, fields)?;
+                    }
+                }
+                (Some("backtrace" | "bt"), _) => {
+                    for (index, (name, label)) in self.frames.iter().rev().enumerate() {
+                        writeln!(self.output, "#{} {} at {}", index, name, label)?;
+                    }
+                }
+                (Some("quit" | "q"), _) => return Ok(Err(RtlInterpreterError::Stopped)),
+                (Some("help" | "h"), _) => writeln!(self.output, "{}", HELP)?,
+                (Some(command), _) => writeln!(self.output, "Unknown command `{}`, try `help`", command)?,
+            }
+        }
+    }
+
+    fn print(&mut self, fun: &Fun<'a>, context: &Context<'a>, name: &str) -> std::io::Result<()> {
+        if name.starts_with('#') {
+            let regs = context.regs.borrow();
+            return match regs.iter().find(|(register, _)| register.to_string() == name) {
+                Some((register, value)) => writeln!(self.output, "{} = {}", register, value),
+                None => writeln!(self.output, "{} wasn't set", name),
+            };
+        }
+
+        let mut found = false;
+        for (ident, register) in variables(fun).filter(|(ident, _)| matches(ident, name)) {
+            writeln!(self.output, "{} = {}", DisplayableBlockIdent(ident), context.get(register))?;
+            found = true;
+        }
+        for (ident, value) in fun.graph.constants.iter().filter(|(ident, _)| matches(ident, name)) {
+            writeln!(self.output, "{} = {}", DisplayableBlockIdent(ident), value)?;
+            found = true;
+        }
+
+        if !found {
+            writeln!(self.output, "No variable `{}` in {}", name, fun.name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Arguments and locals of the function, with the pseudo-register holding them.
+fn variables<'f, 'a>(fun: &'f Fun<'a>) -> impl Iterator<Item=(&'f BlockIdent<'a>, &'f PseudoRegister)> {
+    fun.graph.vars
+        .iter()
+        .filter(|(ident, _)| matches!(ident, BlockIdent::Arg(..)))
+        .chain(fun.locals.iter())
+}
+
+/// Sorts the arguments by their index, then the locals by where they are declared.
+fn declaration_order(ident: &BlockIdent) -> (bool, usize) {
+    match ident {
+        BlockIdent::Arg(index, _) => (false, *index),
+        // Names are slices of the source, so their address is their position in it.
+        BlockIdent::Local(_, name) => (true, name.as_ptr() as usize),
+    }
+}
+
+/// Whether the variable is named so, with the index of its block or without if it is unambiguous.
+fn matches(ident: &BlockIdent, name: &str) -> bool {
+    ident.name() == name || DisplayableBlockIdent(ident).to_string() == name
+}
+
+impl<'a, R: BufRead, W: Write> Observer<'a> for Debugger<'a, R, W> {
+    fn enter(&mut self, fun: &Fun<'a>, _context: &Context<'a>) {
+        self.frames.push((fun.name, fun.entry.clone()));
+    }
+
+    fn step(&mut self, fun: &Fun<'a>, label: &Label, instr: &Instr<'a>, context: &Context<'a>) -> RtlInterpreterResult<()> {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.1 = label.clone();
+        }
+
+        if !self.should_stop(fun, label) {
+            return Ok(());
+        }
+
+        let stopped = self.print_stdout(&context.stdout)
+            .and_then(|_| writeln!(self.output, "{} {}: {}", fun.name, label, instr))
+            .and_then(|_| self.prompt(fun, context));
+
+        stopped.unwrap_or(Err(RtlInterpreterError::Other("Failed to talk with the debugger")))
+    }
+
+    fn leave(&mut self, _fun: &Fun<'a>, _context: &Context<'a>) {
+        self.frames.pop();
+    }
+}
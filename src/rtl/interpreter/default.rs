@@ -1,12 +1,13 @@
 pub mod malloc {
-    use std::collections::HashMap;
-    use std::sync::Mutex;
     use crate::rtl::interpreter::context::Context;
     use crate::rtl::interpreter::{RtlInterpFun, RtlInterpreterResult, Value};
     use crate::rtl::structure::Fresh;
     use crate::rtl::structure::register::PseudoRegister;
+    use crate::session::Session;
 
-    static MALLOC_VALUE_INDEX: Mutex<Value> = Mutex::new(1);
+    pub fn allocate(context: &Context, size: Value) -> RtlInterpreterResult<Value> {
+        context.memory.borrow_mut().allocate(size)
+    }
 
     pub struct Malloc {
         result: PseudoRegister,
@@ -14,8 +15,8 @@ pub mod malloc {
     }
 
     impl Malloc {
-        pub fn new() -> Malloc {
-            Malloc { result: PseudoRegister::fresh(), args: vec![PseudoRegister::fresh()] }
+        pub fn new(session: &Session) -> Malloc {
+            Malloc { result: PseudoRegister::fresh(session), args: vec![PseudoRegister::fresh(session)] }
         }
     }
 
@@ -29,15 +30,8 @@ pub mod malloc {
         }
 
         fn interp_fun(&self, context: &Context<'a>) -> RtlInterpreterResult<()> {
-            let mut address_mutex = MALLOC_VALUE_INDEX.lock().expect("Lock failed");
-
-            let address = *address_mutex;
-            *address_mutex = address + 1;
-
-            context.memory
-                .borrow_mut()
-                .insert(address, HashMap::new());
-
+            let size = context.get(&self.args[0]);
+            let address = allocate(context, size)?;
 
             context.put(&self.result, address);
 
@@ -52,6 +46,7 @@ pub mod putchar {
     use crate::rtl::interpreter::{RtlInterpFun, RtlInterpreterResult};
     use crate::rtl::structure::Fresh;
     use crate::rtl::structure::register::PseudoRegister;
+    use crate::session::Session;
 
     pub struct Putchar {
         result: PseudoRegister,
@@ -59,8 +54,8 @@ pub mod putchar {
     }
 
     impl Putchar {
-        pub fn new() -> Putchar {
-            Putchar { result: PseudoRegister::fresh(), args: vec![PseudoRegister::fresh()] }
+        pub fn new(session: &Session) -> Putchar {
+            Putchar { result: PseudoRegister::fresh(session), args: vec![PseudoRegister::fresh(session)] }
         }
     }
 
@@ -86,3 +81,99 @@ pub mod putchar {
         }
     }
 }
+
+pub mod getchar {
+    use crate::common::Stdin;
+    use crate::rtl::interpreter::context::Context;
+    use crate::rtl::interpreter::{RtlInterpFun, RtlInterpreterResult};
+    use crate::rtl::structure::Fresh;
+    use crate::rtl::structure::register::PseudoRegister;
+    use crate::session::Session;
+
+    pub struct Getchar {
+        result: PseudoRegister,
+        args: Vec<PseudoRegister>,
+        stdin: Stdin,
+    }
+
+    impl Getchar {
+        pub fn new(session: &Session, stdin: Stdin) -> Getchar {
+            Getchar { result: PseudoRegister::fresh(session), args: vec![], stdin }
+        }
+    }
+
+    impl<'a> RtlInterpFun<'a> for Getchar {
+        fn fun_result(&self) -> &PseudoRegister {
+            &self.result
+        }
+
+        fn fun_arguments(&self) -> &Vec<PseudoRegister> {
+            &self.args
+        }
+
+        fn interp_fun(&self, context: &Context<'a>) -> RtlInterpreterResult<()> {
+            context.put(&self.result, self.stdin.getchar());
+
+            Ok(())
+        }
+    }
+}
+
+pub mod printf {
+    use crate::common::{printf, STRING_ADDRESS_BASE, Value};
+    use crate::rtl::interpreter::context::Context;
+    use crate::rtl::interpreter::error::RtlInterpreterError;
+    use crate::rtl::interpreter::{RtlInterpFun, RtlInterpreterResult};
+    use crate::rtl::structure::Fresh;
+    use crate::rtl::structure::register::PseudoRegister;
+    use crate::session::Session;
+
+    pub struct Printf {
+        result: PseudoRegister,
+        args: Vec<PseudoRegister>,
+        strings: Vec<String>,
+    }
+
+    impl Printf {
+        pub fn new(session: &Session, strings: Vec<String>) -> Printf {
+            Printf { result: PseudoRegister::fresh(session), args: vec![PseudoRegister::fresh(session)], strings }
+        }
+
+        fn string_at(&self, address: Value) -> Option<String> {
+            address.checked_sub(STRING_ADDRESS_BASE)
+                .and_then(|offset| usize::try_from(offset).ok())
+                .and_then(|index| self.strings.get(index))
+                .cloned()
+        }
+    }
+
+    impl<'a> RtlInterpFun<'a> for Printf {
+        fn fun_result(&self) -> &PseudoRegister {
+            &self.result
+        }
+
+        fn fun_arguments(&self) -> &Vec<PseudoRegister> {
+            &self.args
+        }
+
+        fn interp_fun(&self, context: &Context<'a>) -> RtlInterpreterResult<()> {
+            let arg = self.args
+                .first()
+                .ok_or(RtlInterpreterError::Other("Pas d'argument pour la fonction printf"))?;
+
+            let format = self.string_at(context.get(arg))
+                .ok_or(RtlInterpreterError::Other("Invalid printf format"))?;
+
+            let output = printf::format(&format, &context.varargs, |address| self.string_at(address))
+                .map_err(RtlInterpreterError::Other)?;
+
+            for c in output.chars() {
+                context.stdout.putchar(c);
+            }
+
+            context.put(&self.result, output.len() as Value);
+
+            Ok(())
+        }
+    }
+}
@@ -1,18 +1,69 @@
 use std::fmt::{Display, Formatter};
-use crate::common::{Value};
+use crate::common::arith::DivisionError;
+use crate::common::{StackOffset, Value};
 use crate::rtl::structure::label::Label;
 
 #[derive(Debug)]
 pub enum RtlInterpreterError {
     FunctionDoesNotExist(String),
     NoSuchInstruction(Label),
-    UnallocatedMemory(Value),
+    InvalidFunctionAddress(Value),
+    DivisionByZero,
+    /// The quotient of `Value::MIN / -1` doesn't fit, which makes `idivq` trap.
+    DivisionOverflow,
+    NullDereference,
+    /// Access to an address which was never allocated.
+    DanglingDereference(Value),
+    /// Access past the end of the block allocated at the address.
+    FieldOutOfBounds(Value, StackOffset),
+    /// Calls nested deeper than the limit.
+    StackOverflow,
+    /// The program ran that many instructions without ending.
+    OutOfFuel(u64),
+    /// The structures allocated would span more bytes than the limit.
+    OutOfMemory(Value),
+    /// The debugger stopped the program.
+    Stopped,
+    /// Error raised by the instruction at the label, in the function.
+    Runtime(String, Label, Box<RtlInterpreterError>),
     Other(&'static str),
 }
 
+impl RtlInterpreterError {
+    /// Locates the error at the instruction which raised it, unless it was already.
+    pub fn at(self, fun: &str, label: &Label) -> RtlInterpreterError {
+        match self {
+            error @ (RtlInterpreterError::Runtime(..) | RtlInterpreterError::Stopped) => error,
+            error => RtlInterpreterError::Runtime(String::from(fun), label.clone(), Box::new(error)),
+        }
+    }
+}
 
-impl<'a> Display for RtlInterpreterError {
+
+impl Display for RtlInterpreterError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            RtlInterpreterError::DivisionByZero => write!(f, "division by zero"),
+            RtlInterpreterError::DivisionOverflow => write!(f, "division overflow"),
+            RtlInterpreterError::NullDereference => write!(f, "null pointer dereference"),
+            RtlInterpreterError::DanglingDereference(address) => write!(f, "dereference of unallocated address {}", address),
+            RtlInterpreterError::FieldOutOfBounds(address, offset) => {
+                write!(f, "offset {} is out of the structure allocated at {}", offset, address)
+            }
+            RtlInterpreterError::StackOverflow => write!(f, "stack overflow"),
+            RtlInterpreterError::OutOfFuel(fuel) => write!(f, "out of fuel after {} instructions", fuel),
+            RtlInterpreterError::OutOfMemory(size) => write!(f, "out of memory, more than {} bytes allocated", size),
+            RtlInterpreterError::Runtime(fun, label, error) => write!(f, "{} in function `{}` at {}", error, fun, label),
+            error => write!(f, "{:?}", error),
+        }
     }
-}
\ No newline at end of file
+}
+
+impl From<DivisionError> for RtlInterpreterError {
+    fn from(error: DivisionError) -> Self {
+        match error {
+            DivisionError::DivisionByZero => RtlInterpreterError::DivisionByZero,
+            DivisionError::Overflow => RtlInterpreterError::DivisionOverflow,
+        }
+    }
+}
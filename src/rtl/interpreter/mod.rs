@@ -1,24 +1,63 @@
 mod default;
-mod context;
-mod error;
+pub mod context;
+pub mod debugger;
+pub mod error;
+pub mod observer;
+pub mod tracer;
 
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::iter::zip;
 use std::rc::Rc;
-use crate::common::{Ident, MAIN, MALLOC, PUTCHAR, Stdout, Value};
+use itertools::Itertools;
+use crate::common::{Execution, GETCHAR, Ident, MAIN, MALLOC, PRINTF, printf, PUTCHAR, STRING_ADDRESS_BASE, Stdin, Stdout, Value};
+use crate::common::arith::idiv;
 use crate::common::bool::{Bool, ToCBool};
-use crate::rtl::interpreter::context::Context;
-use crate::rtl::interpreter::default::malloc::Malloc;
+use crate::rtl::interpreter::context::{Context, Memory};
+use crate::rtl::interpreter::default::getchar::Getchar;
+use crate::rtl::interpreter::default::malloc::{allocate, Malloc};
+use crate::rtl::interpreter::default::printf::Printf;
 use crate::rtl::interpreter::default::putchar::Putchar;
 use crate::rtl::interpreter::error::RtlInterpreterError;
+use crate::rtl::interpreter::observer::Observer;
 use crate::rtl::structure::{File, Fun, Instr, MbBranch, Mbinop, MuBranch, Munop};
 use crate::rtl::structure::label::Label;
 use crate::rtl::structure::register::PseudoRegister;
 
 pub type RtlInterpreterResult<T> = Result<T, RtlInterpreterError>;
 
-pub fn interp_rtl_file<'a>(file: &'a File) -> RtlInterpreterResult<Stdout> {
+/// Address of the first structure allocated, so that `0` is never a valid one.
+const HEAP_BASE: Value = 1;
+/// Calls nested deeper make the program overflow its stack. Each one takes a few kilobytes of the
+/// stack of the host, so that a default 8 MiB thread holds them.
+const MAX_CALL_DEPTH: usize = 1000;
+/// Far more instructions than the test programs run, so that only the ones which loop forever run out.
+const DEFAULT_FUEL: u64 = 1 << 30;
+const DEFAULT_HEAP_SIZE: Value = 1 << 30;
+
+/// Resources a program may use, beyond which the interpreter stops it.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Instructions run in all.
+    pub fuel: u64,
+    /// Calls nested in each other.
+    pub call_depth: usize,
+    /// Bytes of the structures allocated on the heap, and on the stack of the running functions.
+    pub heap_size: Value,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits { fuel: DEFAULT_FUEL, call_depth: MAX_CALL_DEPTH, heap_size: DEFAULT_HEAP_SIZE }
+    }
+}
+
+pub fn interp_rtl_file(file: &File) -> RtlInterpreterResult<Execution> {
+    interp_rtl_file_with(file, Stdin::default(), Limits::default(), Rc::new(RefCell::new(())))
+}
+
+/// Runs the program on the input within the limits, notifying the observer of each instruction run.
+pub fn interp_rtl_file_with<'a>(file: &'a File, stdin: Stdin, limits: Limits, observer: Rc<RefCell<dyn Observer<'a> + 'a>>) -> RtlInterpreterResult<Execution> {
     let main = file.funs.get(MAIN).ok_or(RtlInterpreterError::FunctionDoesNotExist(String::from(MAIN)))?;
 
     let stdout = Rc::new(Stdout::new());
@@ -28,19 +67,25 @@ pub fn interp_rtl_file<'a>(file: &'a File) -> RtlInterpreterResult<Stdout> {
         funs.insert(name.clone(), Rc::new(fun));
     }
 
-    funs.insert(PUTCHAR, Rc::new(Putchar::new()));
-    funs.insert(MALLOC, Rc::new(Malloc::new()));
+    funs.insert(PUTCHAR, Rc::new(Putchar::new(&file.session)));
+    funs.insert(GETCHAR, Rc::new(Getchar::new(&file.session, stdin)));
+    funs.insert(MALLOC, Rc::new(Malloc::new(&file.session)));
+    funs.insert(PRINTF, Rc::new(Printf::new(&file.session, file.strings.iter().map(|x| printf::unescape(x)).collect())));
+
+    let fun_addresses = funs.keys().cloned().sorted().collect();
 
     let context = Context::new(
         stdout.clone(),
         Rc::new(funs),
-        Rc::new(RefCell::new(HashMap::new())),
-        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(fun_addresses),
+        Rc::new(RefCell::new(Memory::new(HEAP_BASE, limits.heap_size))),
+        limits,
+        observer,
     );
 
     main.interp_fun(&context)?;
 
-    Ok(stdout.as_ref().clone())
+    Ok(Execution { stdout: stdout.as_ref().clone(), status: context.get(&main.result) })
 }
 
 pub trait RtlInterpFun<'a> {
@@ -50,28 +95,29 @@ pub trait RtlInterpFun<'a> {
 }
 
 impl<'a> Fun<'a> {
-    fn interp_instr(&self, context: &Context<'a>, instr: &Instr<'a>) -> RtlInterpreterResult<()> {
+    /// Runs the instruction, and gives the label of the next one.
+    fn interp_instr(&self, context: &Context<'a>, instr: &Instr<'a>) -> RtlInterpreterResult<Label> {
         match instr {
             Instr::EConst(c, r, l) => {
                 context.put(r, *c as Value);
-                self.interp_label(context, l)
+                Ok(l.clone())
             }
             Instr::ELoad(address_reg, offset, value_reg, l) => {
                 let value = context.load(address_reg, offset)?;
                 context.put(value_reg, value);
-                self.interp_label(context, l)
+                Ok(l.clone())
             }
-            Instr::EStore(address_reg, value_reg, offset, l) => {
+            Instr::EStore(value_reg, address_reg, offset, l) => {
                 let value = context.get(value_reg);
                 context.store(address_reg, offset, &value)?;
 
-                self.interp_label(context, l)
+                Ok(l.clone())
             }
             Instr::EMUnop(op, r, l) => {
                 match op {
                     Munop::Maddi(c) => {
                         let val = context.get(r);
-                        context.put(r, val + c)
+                        context.put(r, val.wrapping_add(*c))
                     }
                     Munop::Msetei(c) => {
                         let val = context.get(r);
@@ -82,7 +128,7 @@ impl<'a> Fun<'a> {
                         context.put(r, (val != *c).to_minic_bool())
                     }
                 }
-                self.interp_label(context, l)
+                Ok(l.clone())
             }
             Instr::EMBinop(op, r1, r2, l) => {
                 match op {
@@ -91,16 +137,17 @@ impl<'a> Fun<'a> {
                         context.put(r2, val)
                     }
                     Mbinop::MAdd => {
-                        context.put(r2, context.get(r2) + context.get(r1))
+                        context.put(r2, context.get(r2).wrapping_add(context.get(r1)))
                     }
                     Mbinop::MSub => {
-                        context.put(r2, context.get(r2) - context.get(r1))
+                        context.put(r2, context.get(r2).wrapping_sub(context.get(r1)))
                     }
                     Mbinop::MMul => {
-                        context.put(r2, context.get(r2) * context.get(r1))
+                        context.put(r2, context.get(r2).wrapping_mul(context.get(r1)))
                     }
                     Mbinop::MDiv => {
-                        context.put(r2, context.get(r2) / context.get(r1))
+                        let (quotient, _) = idiv(context.get(r2), context.get(r1))?;
+                        context.put(r2, quotient)
                     }
                     Mbinop::MSete => {
                         let bool = context.get(r2) == context.get(r1);
@@ -127,16 +174,15 @@ impl<'a> Fun<'a> {
                         context.put(r2, bool.to_minic_bool())
                     }
                 }
-                self.interp_label(context, l)
+                Ok(l.clone())
             }
             Instr::EMuBranch(op, r, l1, l2) => {
-                let l = match op {
+                Ok(match op {
                     MuBranch::MJz => if !context.get(r).to_bool() { l1 } else { l2 },
                     MuBranch::MJnz => if context.get(r).to_bool() { l1 } else { l2 },
                     MuBranch::MJlei(c) => if context.get(r) <= *c { l1 } else { l2 },
                     MuBranch::MJgi(c) => if context.get(r) > *c { l1 } else { l2 },
-                };
-                self.interp_label(context, l)
+                }.clone())
             }
             Instr::EMbBranch(op, r1, r2, l1, l2) => {
                 let bool = match op {
@@ -144,51 +190,66 @@ impl<'a> Fun<'a> {
                     MbBranch::MJle => context.get(r1) <= context.get(r2),
                 };
                 if bool {
-                    self.interp_label(context, l1)
+                    Ok(l1.clone())
                 } else {
-                    self.interp_label(context, l2)
+                    Ok(l2.clone())
                 }
             }
             Instr::ECall(return_reg, name, args, l) => {
-                let fun = context.funs
-                    .get(name)
-                    .ok_or(RtlInterpreterError::FunctionDoesNotExist(String::from(name.clone())))?;
-
-                let new_context = Context::new(
-                    context.stdout.clone(),
-                    context.funs.clone(),
-                    Rc::new(RefCell::new(HashMap::new())),
-                    context.memory.clone(),
-                );
-
-                for (fun_reg, arg_reg) in zip(fun.fun_arguments(), args) {
-                    new_context.put(fun_reg, context.get(arg_reg));
-                }
-
-                fun.interp_fun(&new_context)?;
-
-                context.put(return_reg, new_context.get(fun.fun_result()));
-                self.interp_label(context, l)
+                self.interp_call(context, return_reg, name, args)?;
+                Ok(l.clone())
+            }
+            Instr::ECallStar(return_reg, callee, args, l) => {
+                let name = context.fun_at(context.get(callee))?;
+                self.interp_call(context, return_reg, name, args)?;
+                Ok(l.clone())
+            }
+            Instr::EFunAddr(name, r, l) => {
+                context.put(r, context.fun_address(name)?);
+                Ok(l.clone())
+            }
+            Instr::EString(index, r, l) => {
+                context.put(r, STRING_ADDRESS_BASE + *index as Value);
+                Ok(l.clone())
+            }
+            Instr::EGoto(l) => Ok(l.clone()),
+            Instr::EStackAddr(ident, r, l) => {
+                let size = *self.aggregates.get(ident).ok_or(RtlInterpreterError::Other("Unknown aggregate"))?;
+                let address = allocate(context, size)?;
+                context.frame.borrow_mut().push(address);
+                context.put(r, address);
+                Ok(l.clone())
             }
-            Instr::EGoto(l) => self.interp_label(context, l),
         }
     }
 
-    fn interp_label(&self, context: &Context<'a>, label: &Label) -> RtlInterpreterResult<()> {
-        if *label == self.exit {
-            Ok(())
-        } else {
-            let instr = self
-                .graph
-                .instrs
-                .get(label)
-                .ok_or(RtlInterpreterError::NoSuchInstruction(label.clone()))?
-                .clone();
+    fn interp_call(&self, context: &Context<'a>, return_reg: &PseudoRegister, name: Ident<'a>, args: &Vec<PseudoRegister>) -> RtlInterpreterResult<()> {
+        let fun = context.funs
+            .get(name)
+            .ok_or(RtlInterpreterError::FunctionDoesNotExist(String::from(name)))?;
+
+        if context.depth >= context.limits.call_depth {
+            return Err(RtlInterpreterError::StackOverflow);
+        }
 
-            // println!("Instr {}", instr);
-            self.interp_instr(context, &instr)
+        let varargs = args
+            .iter()
+            .skip(fun.fun_arguments().len())
+            .map(|arg_reg| context.get(arg_reg))
+            .collect();
+
+        let new_context = context.callee(varargs);
+
+        for (fun_reg, arg_reg) in zip(fun.fun_arguments(), args) {
+            new_context.put(fun_reg, context.get(arg_reg));
         }
+
+        fun.interp_fun(&new_context)?;
+
+        context.put(return_reg, new_context.get(fun.fun_result()));
+        Ok(())
     }
+
 }
 
 impl<'a> RtlInterpFun<'a> for &'a Fun<'a> {
@@ -201,7 +262,24 @@ impl<'a> RtlInterpFun<'a> for &'a Fun<'a> {
     }
 
     fn interp_fun(&self, context: &Context<'a>) -> RtlInterpreterResult<()> {
-        let entry = &self.entry;
-        self.interp_label(context, entry)
+        let mut label = self.entry.clone();
+        context.observer.borrow_mut().enter(self, context);
+
+        while label != self.exit {
+            let instr = self.graph
+                .instrs
+                .get(&label)
+                .ok_or(RtlInterpreterError::NoSuchInstruction(label.clone()))?;
+
+            context.burn().map_err(|error| error.at(self.name, &label))?;
+            context.observer.borrow_mut().step(self, &label, instr, context)?;
+            label = self.interp_instr(context, instr).map_err(|error| error.at(self.name, &label))?;
+        }
+
+        context.observer.borrow_mut().leave(self, context);
+        for address in context.frame.borrow_mut().drain(..) {
+            context.memory.borrow_mut().free(address);
+        }
+        Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,20 @@
+use crate::rtl::interpreter::context::Context;
+use crate::rtl::interpreter::RtlInterpreterResult;
+use crate::rtl::structure::{Fun, Instr};
+use crate::rtl::structure::label::Label;
+
+/// Follows the interpreter as it runs the program, and may stop it.
+pub trait Observer<'a> {
+    /// The function was called, and its arguments were set.
+    fn enter(&mut self, _fun: &Fun<'a>, _context: &Context<'a>) {}
+
+    /// The instruction at the label of the function is about to run.
+    fn step(&mut self, _fun: &Fun<'a>, _label: &Label, _instr: &Instr<'a>, _context: &Context<'a>) -> RtlInterpreterResult<()> {
+        Ok(())
+    }
+
+    /// The function returned.
+    fn leave(&mut self, _fun: &Fun<'a>, _context: &Context<'a>) {}
+}
+
+impl Observer<'_> for () {}
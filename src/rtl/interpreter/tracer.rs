@@ -0,0 +1,39 @@
+use std::io::Write;
+use crate::common::profile::Profile;
+use crate::rtl::interpreter::context::Context;
+use crate::rtl::interpreter::error::RtlInterpreterError;
+use crate::rtl::interpreter::observer::Observer;
+use crate::rtl::interpreter::RtlInterpreterResult;
+use crate::rtl::structure::{Fun, Instr};
+use crate::rtl::structure::label::Label;
+
+/// Counts the calls and instructions of each function, and writes the instructions run to the trace if any.
+pub struct Tracer<'a, W: Write> {
+    trace: Option<W>,
+    profile: Profile<'a>,
+}
+
+impl<'a, W: Write> Tracer<'a, W> {
+    pub fn new(trace: Option<W>) -> Tracer<'a, W> {
+        Tracer { trace, profile: Profile::default() }
+    }
+
+    pub fn profile(&self) -> &Profile<'a> {
+        &self.profile
+    }
+}
+
+impl<'a, W: Write> Observer<'a> for Tracer<'a, W> {
+    fn enter(&mut self, fun: &Fun<'a>, _context: &Context<'a>) {
+        self.profile.call(fun.name);
+    }
+
+    fn step(&mut self, fun: &Fun<'a>, label: &Label, instr: &Instr<'a>, _context: &Context<'a>) -> RtlInterpreterResult<()> {
+        self.profile.step(fun.name);
+        match &mut self.trace {
+            Some(trace) => writeln!(trace, "{} {}: {}", fun.name, label, instr)
+                .map_err(|_| RtlInterpreterError::Other("Failed to write the trace")),
+            None => Ok(()),
+        }
+    }
+}
@@ -1,37 +1,42 @@
 pub mod error;
 pub mod interpreter;
 pub mod structure;
+pub mod uninit;
 
 use std::collections::HashMap;
+use std::sync::Arc;
+use itertools::Itertools;
+use crate::common::in_source_order;
 use crate::rtl::error::RtlError;
-use crate::rtl::structure::{BlockIdent, File, Fresh, Fun, Instr, Mbinop, MuBranch, Munop};
+use crate::rtl::structure::{BlockIdent, File, Fresh, Fun, Instr, Mbinop, MuBranch, Munop, Source};
 use crate::rtl::structure::graph::Graph;
 use crate::rtl::structure::label::Label;
 use crate::rtl::structure::register::PseudoRegister;
+use crate::session::Session;
 use crate::typer::structure as typer;
 
 pub type RtlResult<'a, T> = Result<T, RtlError<'a>>;
 
-pub fn rtl_file<'a>(file: &typer::File<'a>) -> RtlResult<'a, File<'a>> {
+pub fn rtl_file<'a>(session: &Arc<Session>, file: &typer::File<'a>) -> RtlResult<'a, File<'a>> {
     let mut funs = HashMap::new();
 
-    for (name, fun) in file.funs() {
-        funs.insert(name.clone(), rtl_fun(fun)?);
+    for (name, fun) in in_source_order(file.funs()) {
+        funs.insert(*name, rtl_fun(session, fun)?);
     }
 
-    Ok(File::new(funs))
+    Ok(File::new(funs, file.strings().clone(), session.clone()))
 }
 
-fn rtl_fun<'a>(fun: &typer::Fun<'a>) -> RtlResult<'a, Fun<'a>> {
+fn rtl_fun<'a>(session: &Arc<Session>, fun: &typer::Fun<'a>) -> RtlResult<'a, Fun<'a>> {
     let name = fun.signature().name().clone();
-    let result = PseudoRegister::fresh();
+    let result = PseudoRegister::fresh(session);
 
     let mut arguments = vec![];
     let mut locals = HashMap::new();
     let mut vars = HashMap::new();
 
     for argument in fun.signature().args() {
-        let register = PseudoRegister::fresh();
+        let register = PseudoRegister::fresh(session);
         arguments.push(register.clone());
         let argument_name:  BlockIdent = argument.name().clone().into();
         match vars.insert(argument_name.clone(), register) {
@@ -42,9 +47,9 @@ fn rtl_fun<'a>(fun: &typer::Fun<'a>) -> RtlResult<'a, Fun<'a>> {
         }
     }
 
-    for local in fun.locals() {
+    for local in fun.locals().keys().sorted_by_key(|local| local.name().as_ptr()) {
         let local:BlockIdent = local.clone().into();
-        let register = PseudoRegister::fresh();
+        let register = PseudoRegister::fresh(session);
         locals.insert(local.clone(), register.clone());
         match vars.insert(local.clone(), register) {
             None => {}
@@ -54,10 +59,10 @@ fn rtl_fun<'a>(fun: &typer::Fun<'a>) -> RtlResult<'a, Fun<'a>> {
         }
     }
 
-    let mut graph = Graph::new(vars);
+    let mut graph = Graph::new(session.clone(), vars, fun.constants().clone());
 
-    let exit = Label::fresh();
-    let entry = rtl_block(
+    let exit = Label::fresh(session);
+    let mut entry = rtl_block(
         &mut graph,
         &result,
         &exit,
@@ -65,15 +70,27 @@ fn rtl_fun<'a>(fun: &typer::Fun<'a>) -> RtlResult<'a, Fun<'a>> {
         fun.block(),
     )?;
 
-    Ok(Fun::new(
+    let mut aggregates = HashMap::new();
+
+    for (aggregate, structure) in fun.aggregates().iter().sorted_by_key(|(aggregate, _)| aggregate.name().as_ptr()) {
+        let register = locals
+            .get(aggregate)
+            .ok_or(RtlError::VarNotFound(aggregate.clone()))?
+            .clone();
+        entry = graph.insert(Instr::EStackAddr(aggregate.clone(), register, entry));
+        aggregates.insert(aggregate.clone(), structure.c_size());
+    }
+
+    Ok(Fun {
         name,
         result,
         arguments,
         locals,
+        aggregates,
         entry,
         exit,
         graph,
-    ))
+    })
 }
 
 fn rtl_block<'a>(graph: &mut Graph<'a>, retr: &PseudoRegister, retl: &Label, destl: &Label, block: &typer::Block<'a>) -> RtlResult<'a, Label> {
@@ -91,14 +108,14 @@ fn rtl_stmt<'a>(graph: &mut Graph<'a>, retr: &PseudoRegister, retl: &Label, dest
         typer::Stmt::SSkip => Ok(destl.clone()),
         typer::Stmt::SExpr(expr) => rtl_expr(
             graph,
-            &PseudoRegister::fresh(),
+            &PseudoRegister::fresh(&graph.session),
             destl,
             expr,
         ),
         typer::Stmt::SIf(expr, stmt_if, stmt_else) => {
             let to_else = rtl_stmt(graph, retr, retl, destl, stmt_else)?;
             let to_if = rtl_stmt(graph, retr, retl, destl, stmt_if)?;
-            let condition_register = PseudoRegister::fresh();
+            let condition_register = PseudoRegister::fresh(&graph.session);
             let to_jmp = graph.insert(Instr::EMuBranch(
                 MuBranch::MJnz,
                 condition_register.clone(),
@@ -112,9 +129,9 @@ fn rtl_stmt<'a>(graph: &mut Graph<'a>, retr: &PseudoRegister, retl: &Label, dest
             )
         }
         typer::Stmt::SWhile(expr, stmt) => {
-            let goto_label = Label::fresh();
+            let goto_label = Label::fresh(&graph.session);
             let stmt_label = rtl_stmt(graph, retr, retl, &goto_label, stmt)?;
-            let expr_reg = PseudoRegister::fresh();
+            let expr_reg = PseudoRegister::fresh(&graph.session);
             let expr_cond_label = graph.insert(Instr::EMuBranch(MuBranch::MJz, expr_reg.clone(), destl.clone(), stmt_label));
             let expr_label = rtl_expr(graph, &expr_reg, &expr_cond_label, expr)?;
             graph.insert_with_label(goto_label, Instr::EGoto(expr_label.clone()));
@@ -130,22 +147,31 @@ fn rtl_expr<'a>(graph: &mut Graph<'a>, destr: &PseudoRegister, destl: &Label, ex
         typer::ExprNode::EConst(x) => Ok(
             graph.insert(Instr::EConst(x.clone(), destr.clone(), destl.clone()))
         ),
+        typer::ExprNode::EAccessLocal(var) if graph.constants.contains_key(var) => Ok(
+            graph.insert(Instr::EConst(graph.constants[var], destr.clone(), destl.clone()))
+        ),
         typer::ExprNode::EAccessLocal(var) => {
+            let name = var.name();
             let var = var.clone().into();
             let register = graph
                 .vars
                 .get(&var)
                 .ok_or(RtlError::VarNotFound(var))?
                 .clone();
-            Ok(graph.insert(Instr::EMBinop(Mbinop::MMov,
-                                           register,
-                                           destr.clone(),
-                                           destl.clone(),
-            )))
+            let mov_lbl = graph.insert(Instr::EMBinop(Mbinop::MMov,
+                                                      register,
+                                                      destr.clone(),
+                                                      destl.clone(),
+            ));
+            graph.sources.insert(mov_lbl.clone(), Source::Local(name));
+            Ok(mov_lbl)
         }
         typer::ExprNode::EAccessField(expr, y) => {
-            let expr_reg = PseudoRegister::fresh();
+            let expr_reg = PseudoRegister::fresh(&graph.session);
             let field_lbl = graph.insert(Instr::ELoad(expr_reg.clone(), y.c_offset(), destr.clone(), destl.clone()));
+            if let typer::ExprNode::EAccessLocal(var) = expr.node() {
+                graph.sources.insert(field_lbl.clone(), Source::Field(var.name(), y.name()));
+            }
             rtl_expr(graph, &expr_reg, &field_lbl, expr)
         }
         typer::ExprNode::EAssignLocal(var, expr) => {
@@ -154,7 +180,7 @@ fn rtl_expr<'a>(graph: &mut Graph<'a>, destr: &PseudoRegister, destl: &Label, ex
             rtl_expr(graph, &expr_reg, &mov_lbl, expr)
         }
         typer::ExprNode::EAssignField(expr, field, value) => {
-            let expr_reg = PseudoRegister::fresh();
+            let expr_reg = PseudoRegister::fresh(&graph.session);
             let store_lbl = graph.insert(Instr::EStore(destr.clone(), expr_reg.clone(), field.c_offset(), destl.clone()));
             let expr_lbl = rtl_expr(graph, &expr_reg, &store_lbl, expr)?;
             rtl_expr(graph, &destr, &expr_lbl, value)
@@ -166,7 +192,7 @@ fn rtl_expr<'a>(graph: &mut Graph<'a>, destr: &PseudoRegister, destl: &Label, ex
                     rtl_expr(graph, &destr, &test_lbl, expr)
                 }
                 typer::Unop::UMinus => {
-                    let expr_reg = PseudoRegister::fresh();
+                    let expr_reg = PseudoRegister::fresh(&graph.session);
                     let sub_lbl = graph.insert(Instr::EMBinop(Mbinop::MSub, expr_reg.clone(), destr.clone(), destl.clone()));
                     let zero_lbl = graph.insert(Instr::EConst(0, destr.clone(), sub_lbl));
                     rtl_expr(graph, &expr_reg, &zero_lbl, expr)
@@ -198,7 +224,7 @@ fn rtl_expr<'a>(graph: &mut Graph<'a>, destr: &PseudoRegister, destl: &Label, ex
                         typer::Binop::BLe => Mbinop::Msetle,
                         _ => unreachable!()
                     };
-                    let reg_2 = PseudoRegister::fresh();
+                    let reg_2 = PseudoRegister::fresh(&graph.session);
                     let operation_lbl = graph.insert(Instr::EMBinop(
                         rtl_op,
                         reg_2.clone(),
@@ -225,29 +251,59 @@ fn rtl_expr<'a>(graph: &mut Graph<'a>, destr: &PseudoRegister, destl: &Label, ex
             }
         }
         typer::ExprNode::ECall(signature, args) => {
-            let eval_label = Label::fresh();
+            let eval_label = Label::fresh(&graph.session);
 
-            let mut arg_label = eval_label.clone();
-            let mut reverse_args = vec![];
-
-            for arg in args.iter().rev() {
-                let reg = PseudoRegister::fresh();
-                reverse_args.push(reg.clone());
-                arg_label = rtl_expr(graph, &reg, &arg_label, arg.expr())?;
-            }
-
-            reverse_args.reverse();
+            let (arg_label, arg_regs) = rtl_args(graph, &eval_label, args.iter().map(|arg| arg.expr()))?;
 
             graph.insert_with_label(eval_label.clone(), Instr::ECall(
                 destr.clone(),
                 signature.name().clone(),
-                reverse_args,
+                arg_regs,
                 destl.clone(),
             ));
 
             Ok(arg_label)
         }
+        typer::ExprNode::EString(index) => Ok(
+            graph.insert(Instr::EString(*index, destr.clone(), destl.clone()))
+        ),
+        typer::ExprNode::EFunAddr(signature) => Ok(
+            graph.insert(Instr::EFunAddr(signature.name(), destr.clone(), destl.clone()))
+        ),
+        typer::ExprNode::ECallStar(callee, args) => {
+            let eval_label = Label::fresh(&graph.session);
+
+            let (arg_label, arg_regs) = rtl_args(graph, &eval_label, args.iter())?;
+
+            let callee_reg = PseudoRegister::fresh(&graph.session);
+            let callee_label = rtl_expr(graph, &callee_reg, &arg_label, callee)?;
+
+            graph.insert_with_label(eval_label.clone(), Instr::ECallStar(
+                destr.clone(),
+                callee_reg,
+                arg_regs,
+                destl.clone(),
+            ));
+
+            Ok(callee_label)
+        }
     }
 }
 
+fn rtl_args<'a, 'b, I>(graph: &mut Graph<'a>, destl: &Label, args: I) -> RtlResult<'a, (Label, Vec<PseudoRegister>)>
+    where I: DoubleEndedIterator<Item=&'b typer::Expr<'a>>, 'a: 'b {
+    let mut arg_label = destl.clone();
+    let mut reverse_args = vec![];
+
+    for arg in args.rev() {
+        let reg = PseudoRegister::fresh(&graph.session);
+        reverse_args.push(reg.clone());
+        arg_label = rtl_expr(graph, &reg, &arg_label, arg)?;
+    }
+
+    reverse_args.reverse();
+
+    Ok((arg_label, reverse_args))
+}
+
 
@@ -1,14 +1,23 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 use derive_new::new;
-use crate::rtl::structure::{BlockIdent, Fresh, Instr};
+use itertools::Itertools;
+use crate::common::Value;
+use crate::rtl::structure::{BlockIdent, Fresh, Instr, Source};
 use crate::rtl::structure::label::Label;
 use crate::rtl::structure::register::PseudoRegister;
+use crate::session::Session;
 
 #[derive(Debug)]
 pub struct Graph<'a> {
+    pub session: Arc<Session>,
     pub vars: HashMap<BlockIdent<'a>, PseudoRegister>,
-    pub instrs: HashMap<Label, Instr<'a>>,
+    /// Const locals initialised with a constant, read as compile-time values.
+    pub constants: HashMap<BlockIdent<'a>, Value>,
+    pub instrs: BTreeMap<Label, Instr<'a>>,
+    /// Source of the instructions reading a variable or a field, for diagnostics.
+    pub sources: HashMap<Label, Source<'a>>,
 }
 
 #[derive(new)]
@@ -25,16 +34,19 @@ pub struct DisplayableVar {
 }
 
 impl<'a> Graph<'a> {
-    pub fn new(vars: HashMap<BlockIdent, PseudoRegister>) -> Graph {
+    pub fn new(session: Arc<Session>, vars: HashMap<BlockIdent<'a>, PseudoRegister>, constants: HashMap<BlockIdent<'a>, Value>) -> Graph<'a> {
         Graph {
-            instrs: HashMap::new(),
+            session,
+            instrs: BTreeMap::new(),
             vars,
+            constants,
+            sources: HashMap::new(),
         }
     }
 
     pub fn locals(&self) -> Vec<DisplayableVar> {
         let mut locals = vec![];
-        for (ident, reg) in self.vars.iter() {
+        for (ident, reg) in self.vars.iter().sorted_by_key(|(ident, _)| ident.name().as_ptr()) {
             match ident {
                 BlockIdent::Arg(_, _) => {}
                 BlockIdent::Local(block_index, ident) => {
@@ -46,12 +58,23 @@ impl<'a> Graph<'a> {
         locals
     }
 
+    pub fn aggregates(&self, sizes: &HashMap<BlockIdent<'a>, Value>) -> Vec<DisplayableVar> {
+        let mut aggregates = vec![];
+        for (ident, size) in sizes.iter().sorted_by_key(|(ident, _)| ident.name().as_ptr()) {
+            if let Some(reg) = self.vars.get(ident) {
+                aggregates.push(DisplayableVar::new(format!("{}[{}]", DisplayableBlockIdent(ident), size), reg.clone()))
+            }
+        }
+
+        aggregates
+    }
+
     pub fn insert_with_label(&mut self, label: Label, instr: Instr<'a>) {
         self.instrs.insert(label.clone(), instr);
     }
 
     pub fn insert(&mut self, instr: Instr<'a>) -> Label {
-        let label = Label::fresh();
+        let label = Label::fresh(&self.session);
         self.instrs.insert(label.clone(), instr);
         label
     }
@@ -70,7 +93,11 @@ impl DisplayableGraph<'_> {
                 | Instr::EMUnop(_, _, l)
                 | Instr::EMBinop(_, _, _, l)
                 | Instr::ECall(_, _, _, l)
-                | Instr::EGoto(l) => {
+                | Instr::ECallStar(_, _, _, l)
+                | Instr::EFunAddr(_, _, l)
+                | Instr::EString(_, _, l)
+                | Instr::EGoto(l)
+                | Instr::EStackAddr(_, _, l) => {
                     self.visit(visited, &l, f)?;
                 }
                 Instr::EMuBranch(_, _, l1, l2)
@@ -93,6 +120,17 @@ impl Display for DisplayableGraph<'_> {
 }
 
 
+pub struct DisplayableBlockIdent<'a, 'b>(pub &'b BlockIdent<'a>);
+
+impl Display for DisplayableBlockIdent<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            BlockIdent::Arg(_, ident) => write!(f, "{}", ident),
+            BlockIdent::Local(block_index, ident) => write!(f, "{}_{}", ident, block_index),
+        }
+    }
+}
+
 impl Display for DisplayableVar {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}:{}", self.name, self.register)
@@ -1,32 +1,29 @@
 use std::fmt::{Display, Formatter};
-use std::rc::Rc;
-use std::sync::Mutex;
+use std::sync::Arc;
 use crate::rtl::structure::Fresh;
+use crate::session::Session;
 
-static COUNTER: Mutex<u32> = Mutex::new(0);
-
-pub type Label = Rc<_Label>;
+pub type Label = Arc<_Label>;
 
 #[derive(Eq, PartialEq, Hash, Debug, Ord, PartialOrd)]
 pub struct _Label {
+    scope: u32,
     index: u32,
 }
 
 impl Fresh for Label {
     type Item = Label;
 
-    fn fresh() -> Self::Item {
-        let mut counter = COUNTER.lock().unwrap();
-        let local_counter = counter.clone();
-
-        *counter += 1;
-
-        Rc::new(_Label { index: local_counter })
+    fn fresh(session: &Session) -> Self::Item {
+        Arc::new(_Label { scope: session.scope(), index: session.next_label() })
     }
 }
 
 impl Display for _Label {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "L{}", self.index)
+        match self.scope {
+            0 => write!(f, "L{}", self.index),
+            scope => write!(f, "L{}_{}", scope, self.index),
+        }
     }
 }
@@ -3,25 +3,30 @@ pub mod label;
 pub mod register;
 
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::fmt::{Display, Formatter};
 use derive_new::new;
-use crate::common::{Ident, StackOffset, Value};
-use crate::rtl::structure::graph::{Graph, DisplayableGraph};
+use crate::common::{in_source_order, Ident, StackOffset, Value};
+use crate::rtl::structure::graph::{Graph, DisplayableBlockIdent, DisplayableGraph};
 use crate::rtl::structure::label::Label;
 use crate::rtl::structure::register::PseudoRegister;
+use crate::session::Session;
 use crate::utils::DisplayableVec;
 
 #[derive(new, Debug)]
 pub struct File<'a> {
     pub funs: HashMap<Ident<'a>, Fun<'a>>,
+    pub strings: Vec<Ident<'a>>,
+    pub session: Arc<Session>,
 }
 
-#[derive(new, Debug)]
+#[derive(Debug)]
 pub struct Fun<'a> {
     pub name: Ident<'a>,
     pub result: PseudoRegister,
     pub arguments: Vec<PseudoRegister>,
     pub locals: HashMap<BlockIdent<'a>, PseudoRegister>,
+    pub aggregates: HashMap<BlockIdent<'a>, Value>,
     pub entry: Label,
     pub exit: Label,
     pub graph: Graph<'a>,
@@ -37,7 +42,11 @@ pub enum Instr<'a> {
     EMuBranch(MuBranch, PseudoRegister, Label, Label),
     EMbBranch(MbBranch, PseudoRegister, PseudoRegister, Label, Label),
     ECall(PseudoRegister, Ident<'a>, Vec<PseudoRegister>, Label),
+    ECallStar(PseudoRegister, PseudoRegister, Vec<PseudoRegister>, Label),
+    EFunAddr(Ident<'a>, PseudoRegister, Label),
+    EString(usize, PseudoRegister, Label),
     EGoto(Label),
+    EStackAddr(BlockIdent<'a>, PseudoRegister, Label),
 }
 
 #[derive(Debug, Clone)]
@@ -78,16 +87,26 @@ pub enum MbBranch {
 
 pub type BlockIdent<'a> = crate::typer::structure::BlockIdent<'a>;
 
+/// What an instruction reads, as written in the source : a variable, or a field through a variable.
+#[derive(Debug, Clone)]
+pub enum Source<'a> {
+    Local(Ident<'a>),
+    Field(Ident<'a>, Ident<'a>),
+}
+
 pub trait Fresh {
     type Item;
 
-    fn fresh() -> Self::Item;
+    fn fresh(session: &Session) -> Self::Item;
 }
 
 impl Display for File<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "=== RTL ==================================================")?;
-        for (_, fun) in &self.funs {
+        for (index, string) in self.strings.iter().enumerate() {
+            writeln!(f, "string {}: \"{}\"", index, string)?;
+        }
+        for (_, fun) in in_source_order(&self.funs) {
             writeln!(f, "{}", fun)?;
         }
         Ok(())
@@ -100,6 +119,7 @@ impl Display for Fun<'_> {
         writeln!(f, "\tentry : {}", self.entry)?;
         writeln!(f, "\texit : {}", self.exit)?;
         writeln!(f, "\tlocals: {}", DisplayableVec(&self.graph.locals()))?;
+        writeln!(f, "\taggregates: {}", DisplayableVec(&self.graph.aggregates(&self.aggregates)))?;
 
         let printable_graph = DisplayableGraph::new(
             &self.graph,
@@ -112,18 +132,42 @@ impl Display for Fun<'_> {
     }
 }
 
+impl<'a> Instr<'a> {
+    pub fn successors(&self) -> Vec<Label> {
+        match self {
+            Instr::EConst(_, _, l)
+            | Instr::ELoad(_, _, _, l)
+            | Instr::EStore(_, _, _, l)
+            | Instr::EMUnop(_, _, l)
+            | Instr::EMBinop(_, _, _, l)
+            | Instr::ECall(_, _, _, l)
+            | Instr::ECallStar(_, _, _, l)
+            | Instr::EFunAddr(_, _, l)
+            | Instr::EString(_, _, l)
+            | Instr::EGoto(l)
+            | Instr::EStackAddr(_, _, l) => vec![l.clone()],
+            Instr::EMuBranch(_, _, l1, l2)
+            | Instr::EMbBranch(_, _, _, l1, l2) => vec![l1.clone(), l2.clone()],
+        }
+    }
+}
+
 impl Display for Instr<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Instr::EConst(c, r, l) => write!(f, "mov ${} {} --> {}", c, r, l),
             Instr::ELoad(address, offset, dest, l) => write!(f, "load {}({}) to {} --> {}", address, offset, dest, l),
-            Instr::EStore(address, value, offset, l) => write!(f, "store {} in {}({}) --> {}", value, address, offset, l),
+            Instr::EStore(value, address, offset, l) => write!(f, "store {} in {}({}) --> {}", value, address, offset, l),
             Instr::EMUnop(op, r, l) => write!(f, "{} {} --> {}", op, r, l),
             Instr::EMBinop(op, r1, r2, l) => write!(f, "{} {} {} --> {}", op, r1, r2, l),
             Instr::EMuBranch(op, reg, lbl1, lbl2) => write!(f, "{} {} --> {},{}", op, reg, lbl1, lbl2),
             Instr::EMbBranch(op, r1, r2, l1, l2) => write!(f, "bbranch {} : {} {} --> {},{}", op, r1, r2, l1, l2),
             Instr::ECall(reg, name, args, l) => write!(f, "call {} {}({}) --> {}", reg, name, DisplayableVec(args), l),
+            Instr::ECallStar(reg, callee, args, l) => write!(f, "call {} *{}({}) --> {}", reg, callee, DisplayableVec(args), l),
+            Instr::EFunAddr(name, r, l) => write!(f, "fun_addr {} {} --> {}", name, r, l),
+            Instr::EString(index, r, l) => write!(f, "string {} {} --> {}", index, r, l),
             Instr::EGoto(l) => write!(f, "goto {}", l),
+            Instr::EStackAddr(ident, r, l) => write!(f, "stack_addr {} {} --> {}", DisplayableBlockIdent(ident), r, l),
         }
     }
 }
@@ -1,32 +1,29 @@
 use std::fmt::{Display, Formatter};
-use std::rc::Rc;
-use std::sync::Mutex;
+use std::sync::Arc;
 use crate::rtl::structure::Fresh;
+use crate::session::Session;
 
-static COUNTER: Mutex<u32> = Mutex::new(1);
-
-pub type PseudoRegister = Rc<_PseudoRegister>;
+pub type PseudoRegister = Arc<_PseudoRegister>;
 
 #[derive(Eq, PartialEq, Hash, Debug,Ord, PartialOrd)]
 pub struct _PseudoRegister {
+    scope: u32,
     index: u32,
 }
 
 impl Fresh for PseudoRegister {
     type Item = PseudoRegister;
 
-    fn fresh() -> Self::Item {
-        let mut counter = COUNTER.lock().unwrap();
-        let local_counter = counter.clone();
-
-        *counter += 1;
-
-        Rc::new(_PseudoRegister { index: local_counter })
+    fn fresh(session: &Session) -> Self::Item {
+        Arc::new(_PseudoRegister { scope: session.scope(), index: session.next_register() })
     }
 }
 
 impl Display for _PseudoRegister {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "#{}", self.index)
+        match self.scope {
+            0 => write!(f, "#{}", self.index),
+            scope => write!(f, "#{}_{}", scope, self.index),
+        }
     }
 }
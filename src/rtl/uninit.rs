@@ -0,0 +1,240 @@
+//! Definite initialisation analysis : finds the locals, and the fields of the structures allocated
+//! by a function, which may be read before being written on some path of the RTL graph.
+
+use std::collections::{HashMap, HashSet};
+use crate::common::{MALLOC, StackOffset};
+use crate::rtl::structure::{File, Fun, Instr, Mbinop, Source};
+use crate::rtl::structure::label::Label;
+use crate::rtl::structure::register::PseudoRegister;
+use crate::warning::{Warning, WarningCode};
+
+/// What is known to be written at some point of a function.
+/// Structures are identified by the label of the instruction allocating them.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct State {
+    registers: HashSet<PseudoRegister>,
+    structures: HashMap<PseudoRegister, Label>,
+    fields: HashMap<Label, HashSet<StackOffset>>,
+}
+
+pub fn uninitialised_reads<'a>(file: &File<'a>) -> Vec<Warning<'a>> {
+    let mut reads = vec![];
+
+    for fun in file.funs.values() {
+        reads.append(&mut uninitialised_reads_fun(fun));
+    }
+
+    reads.sort_by_key(|read| read.anchor().map(str::as_ptr));
+    reads
+}
+
+fn uninitialised_reads_fun<'a>(fun: &Fun<'a>) -> Vec<Warning<'a>> {
+    let states = fixpoint(fun);
+    let locals = fun.locals
+        .iter()
+        .map(|(ident, register)| (register, ident))
+        .collect::<HashMap<_, _>>();
+
+    let mut reads = vec![];
+
+    for (label, state) in &states {
+        let instr = &fun.graph.instrs[label];
+
+        for register in uses(instr) {
+            if let Some(ident) = locals.get(register) {
+                if !state.registers.contains(register) {
+                    let source = fun.graph.sources
+                        .get(label)
+                        .cloned()
+                        .unwrap_or(Source::Local(ident.name()));
+                    reads.push(warning(fun, source));
+                }
+            }
+        }
+
+        if let Instr::ELoad(address, offset, _, _) = instr {
+            let written = state.structures
+                .get(address)
+                .and_then(|structure| state.fields.get(structure));
+
+            if let (Some(written), Some(source)) = (written, fun.graph.sources.get(label)) {
+                if !written.contains(offset) {
+                    reads.push(warning(fun, source.clone()));
+                }
+            }
+        }
+    }
+
+    reads
+}
+
+fn warning<'a>(fun: &Fun<'a>, source: Source<'a>) -> Warning<'a> {
+    match source {
+        Source::Local(ident) => Warning::new(
+            WarningCode::Uninitialised,
+            fun.name,
+            Some(ident),
+            format!("`{}` may be used uninitialised in `{}`", ident, fun.name),
+        ),
+        Source::Field(ident, field) => Warning::new(
+            WarningCode::Uninitialised,
+            fun.name,
+            Some(ident),
+            format!("field `{}` of `{}` may be read before being written in `{}`", field, ident, fun.name),
+        ),
+    }
+}
+
+/// Computes the state before each reachable instruction.
+fn fixpoint(fun: &Fun) -> HashMap<Label, State> {
+    let mut states = HashMap::new();
+    let mut pending = vec![fun.entry.clone()];
+    states.insert(fun.entry.clone(), State::default());
+
+    while let Some(label) = pending.pop() {
+        let instr = &fun.graph.instrs[&label];
+        let after = transfer(&label, instr, &states[&label]);
+
+        for successor in instr.successors() {
+            if successor == fun.exit {
+                continue;
+            }
+
+            let joined = match states.get(&successor) {
+                None => after.clone(),
+                Some(before) => before.join(&after),
+            };
+
+            if states.get(&successor) != Some(&joined) {
+                states.insert(successor.clone(), joined);
+                pending.push(successor);
+            }
+        }
+    }
+
+    states
+}
+
+fn transfer(label: &Label, instr: &Instr, state: &State) -> State {
+    let mut state = state.clone();
+
+    match instr {
+        Instr::ECall(dest, name, _, _) if *name == MALLOC => {
+            state.define(dest);
+            state.allocate(dest, label);
+        }
+        Instr::EStackAddr(_, dest, _) => {
+            state.define(dest);
+            state.allocate(dest, label);
+        }
+        Instr::ECall(dest, _, args, _) => {
+            args.iter().for_each(|arg| state.escape(arg));
+            state.define(dest);
+        }
+        Instr::ECallStar(dest, callee, args, _) => {
+            args.iter().for_each(|arg| state.escape(arg));
+            state.escape(callee);
+            state.define(dest);
+        }
+        Instr::EMBinop(Mbinop::MMov, from, to, _) => {
+            let structure = state.structures.get(from).cloned();
+            state.define(to);
+            if let Some(structure) = structure {
+                state.structures.insert(to.clone(), structure);
+            }
+        }
+        Instr::EStore(value, address, offset, _) => {
+            state.escape(value);
+            if let Some(written) = state.structures.get(address).and_then(|x| state.fields.get_mut(x)) {
+                written.insert(*offset);
+            }
+        }
+        _ => {
+            if let Some(register) = def(instr) {
+                state.define(register);
+            }
+        }
+    }
+
+    state
+}
+
+fn def<'b>(instr: &'b Instr) -> Option<&'b PseudoRegister> {
+    match instr {
+        Instr::EConst(_, r, _)
+        | Instr::ELoad(_, _, r, _)
+        | Instr::EMUnop(_, r, _)
+        | Instr::EMBinop(_, _, r, _)
+        | Instr::ECall(r, _, _, _)
+        | Instr::ECallStar(r, _, _, _)
+        | Instr::EFunAddr(_, r, _)
+        | Instr::EString(_, r, _)
+        | Instr::EStackAddr(_, r, _) => Some(r),
+        Instr::EStore(_, _, _, _)
+        | Instr::EMuBranch(_, _, _, _)
+        | Instr::EMbBranch(_, _, _, _, _)
+        | Instr::EGoto(_) => None,
+    }
+}
+
+fn uses<'b>(instr: &'b Instr) -> Vec<&'b PseudoRegister> {
+    match instr {
+        Instr::EConst(_, _, _)
+        | Instr::EFunAddr(_, _, _)
+        | Instr::EString(_, _, _)
+        | Instr::EStackAddr(_, _, _)
+        | Instr::EGoto(_) => vec![],
+        Instr::ELoad(address, _, _, _) => vec![address],
+        Instr::EStore(value, address, _, _) => vec![value, address],
+        Instr::EMUnop(_, r, _)
+        | Instr::EMuBranch(_, r, _, _) => vec![r],
+        Instr::EMBinop(Mbinop::MMov, r, _, _) => vec![r],
+        Instr::EMBinop(_, r1, r2, _)
+        | Instr::EMbBranch(_, r1, r2, _, _) => vec![r1, r2],
+        Instr::ECall(_, _, args, _) => args.iter().collect(),
+        Instr::ECallStar(_, callee, args, _) => args.iter().chain([callee]).collect(),
+    }
+}
+
+impl State {
+    fn define(&mut self, register: &PseudoRegister) {
+        self.registers.insert(register.clone());
+        self.structures.remove(register);
+    }
+
+    fn allocate(&mut self, register: &PseudoRegister, label: &Label) {
+        self.structures.insert(register.clone(), label.clone());
+        self.fields.insert(label.clone(), HashSet::new());
+    }
+
+    /// The structure may now be written by code we don't see : forget it.
+    fn escape(&mut self, register: &PseudoRegister) {
+        if let Some(structure) = self.structures.get(register) {
+            self.fields.remove(structure);
+        }
+    }
+
+    fn join(&self, other: &State) -> State {
+        let registers = self.registers
+            .intersection(&other.registers)
+            .cloned()
+            .collect();
+
+        let structures = self.structures
+            .iter()
+            .filter(|(register, structure)| other.structures.get(*register) == Some(*structure))
+            .map(|(register, structure)| (register.clone(), structure.clone()))
+            .collect();
+
+        let fields = self.fields
+            .iter()
+            .filter_map(|(structure, written)| {
+                other.fields
+                    .get(structure)
+                    .map(|other_written| (structure.clone(), written.intersection(other_written).cloned().collect()))
+            })
+            .collect();
+
+        State { registers, structures, fields }
+    }
+}
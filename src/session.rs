@@ -0,0 +1,49 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// State of one compilation, shared by its stages : the counters of the fresh labels and pseudo
+/// registers. Two sessions number their labels and registers the same way.
+#[derive(Debug)]
+pub struct Session {
+    /// `0` for the whole file, the index of the function plus one for a session forked for it.
+    scope: u32,
+    labels: AtomicU32,
+    registers: AtomicU32,
+}
+
+impl Session {
+    pub fn new() -> Session {
+        Session::with_scope(0)
+    }
+
+    fn with_scope(scope: u32) -> Session {
+        Session {
+            scope,
+            labels: AtomicU32::new(0),
+            registers: AtomicU32::new(1),
+        }
+    }
+
+    /// A session for the back end of the `index`-th function, whose names can't clash with the ones
+    /// of another function, and don't depend on the order functions are compiled in.
+    pub fn fork(&self, index: usize) -> Session {
+        Session::with_scope(index as u32 + 1)
+    }
+
+    pub fn scope(&self) -> u32 {
+        self.scope
+    }
+
+    pub fn next_label(&self) -> u32 {
+        self.labels.fetch_add(1, Ordering::Relaxed)
+    }
+
+    pub fn next_register(&self) -> u32 {
+        self.registers.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session::new()
+    }
+}
@@ -5,13 +5,17 @@ use std::ops::Deref;
 use std::rc::Rc;
 use derive_getters::Getters;
 use derive_new::new;
-use crate::common::Ident;
-use crate::typer::structure::{BlockIdent, Formal, Signature, Struct, Typ};
+use crate::common::{Ident, Value};
+use crate::warning::Warning;
+use crate::typer::structure::{BlockIdent, Definition, Formal, Reference, Signature, Struct, Typ};
 
 #[derive(new, Debug, Getters, Clone)]
 pub struct FileContext<'a> {
     structs: Rc<RefCell<HashMap<Ident<'a>, Rc<Struct<'a>>>>>,
     funs: Rc<RefCell<HashMap<Ident<'a>, Rc<Signature<'a>>>>>,
+    strings: Rc<RefCell<Vec<Ident<'a>>>>,
+    warnings: Rc<RefCell<Vec<Warning<'a>>>>,
+    references: Rc<RefCell<Vec<Reference<'a>>>>,
 }
 
 impl FileContext<'_> {
@@ -19,33 +23,57 @@ impl FileContext<'_> {
         let mut funs = HashMap::new();
 
         let putchar = Signature::putchar();
+        let getchar = Signature::getchar();
         let malloc = Signature::malloc();
+        let printf = Signature::printf();
 
         funs.insert(
             putchar.name().clone(),
             Rc::new(putchar),
         );
 
+        funs.insert(
+            *getchar.name(),
+            Rc::new(getchar),
+        );
+
         funs.insert(
             malloc.name().clone(),
             Rc::new(malloc),
         );
 
+        funs.insert(
+            *printf.name(),
+            Rc::new(printf),
+        );
+
         let structs = Rc::new(RefCell::new(HashMap::new()));
         let funs = Rc::new(RefCell::new(funs));
+        let strings = Rc::new(RefCell::new(Vec::new()));
+        let warnings = Rc::new(RefCell::new(Vec::new()));
+        let references = Rc::new(RefCell::new(Vec::new()));
 
-        FileContext::new(structs, funs)
+        FileContext::new(structs, funs, strings, warnings, references)
+    }
+}
+
+impl<'a> FileContext<'a> {
+    /// Records that `ident`, in the source, names `definition`.
+    pub fn refer(&self, ident: Ident<'a>, definition: Definition<'a>, typ: &Typ<'a>) {
+        self.references.borrow_mut().push(Reference::new(ident, definition, typ.clone()));
     }
 }
 
 pub trait ParentContext<'a>: Debug {
-    fn declare(&self, ident: BlockIdent<'a>);
+    fn declare(&self, ident: BlockIdent<'a>, typ: &Typ<'a>);
     fn typ(&self, ident: Ident<'a>) -> Option<Typ<'a>>;
     fn fresh_index(&self) -> u8;
     fn context(&self) -> Rc<FileContext<'a>>;
     fn fun_typ(&self) -> Typ<'a>;
     fn fun_name(&self) -> Ident<'a>;
     fn get_block_ident(&self, ident: Ident<'a>) -> BlockIdent<'a>;
+    fn is_const(&self, ident: Ident<'a>) -> bool;
+    fn declare_constant(&self, ident: BlockIdent<'a>, value: Value);
 }
 
 #[derive(Debug, Clone, Getters)]
@@ -55,7 +83,9 @@ pub struct FunctionContext<'a> {
     typ: Typ<'a>,
     block_counter: RefCell<u8>,
     arguments: RefCell<Vec<Formal<'a>>>,
-    locals: RefCell<HashSet<BlockIdent<'a>>>,
+    locals: RefCell<HashMap<BlockIdent<'a>, Typ<'a>>>,
+    aggregates: RefCell<HashMap<BlockIdent<'a>, Rc<Struct<'a>>>>,
+    constants: RefCell<HashMap<BlockIdent<'a>, Value>>,
 }
 
 impl<'x> FunctionContext<'x> {
@@ -69,7 +99,9 @@ impl<'x> FunctionContext<'x> {
     ) -> FunctionContext<'a> {
         let block_counter = RefCell::new(FunctionContext::ARGUMENT_BLOCK_INDEX + 1);
         let arguments = RefCell::new(arguments);
-        let locals = RefCell::new(HashSet::new());
+        let locals = RefCell::new(HashMap::new());
+        let aggregates = RefCell::new(HashMap::new());
+        let constants = RefCell::new(HashMap::new());
 
         FunctionContext {
             context,
@@ -78,6 +110,8 @@ impl<'x> FunctionContext<'x> {
             block_counter,
             arguments,
             locals,
+            aggregates,
+            constants,
         }
     }
 
@@ -92,8 +126,11 @@ impl<'x> FunctionContext<'x> {
 }
 
 impl<'a> ParentContext<'a> for FunctionContext<'a> {
-    fn declare(&self, ident: BlockIdent<'a>) {
-        self.locals.borrow_mut().insert(ident);
+    fn declare(&self, ident: BlockIdent<'a>, typ: &Typ<'a>) {
+        if let Typ::TStructValue(structure) = typ {
+            self.aggregates.borrow_mut().insert(ident.clone(), structure.clone());
+        }
+        self.locals.borrow_mut().insert(ident, typ.clone());
     }
 
     fn typ(&self, ident: Ident<'a>) -> Option<Typ<'a>> {
@@ -120,7 +157,19 @@ impl<'a> ParentContext<'a> for FunctionContext<'a> {
     }
 
     fn get_block_ident(&self, ident: Ident<'a>) -> BlockIdent<'a> {
-        self.find_one_by_ident(ident).expect("ident doesn't exist").name().clone()
+        // Keeps the identifier of the use site, to locate it in the source
+        match self.find_one_by_ident(ident).expect("ident doesn't exist").name() {
+            BlockIdent::Arg(index, _) => BlockIdent::Arg(*index, ident),
+            name => name.clone(),
+        }
+    }
+
+    fn is_const(&self, _ident: Ident<'a>) -> bool {
+        false
+    }
+
+    fn declare_constant(&self, ident: BlockIdent<'a>, value: Value) {
+        self.constants.borrow_mut().insert(ident, value);
     }
 }
 
@@ -130,36 +179,52 @@ pub struct BlockContext<'a> {
     index: u8,
     parent: Rc<dyn ParentContext<'a> + 'a>,
     vars: HashMap<Ident<'a>, Typ<'a>>,
+    constants: HashSet<Ident<'a>>,
+    declared: RefCell<HashSet<Ident<'a>>>,
 }
 
 impl BlockContext<'_> {
     pub fn new<'a>(context: Rc<FileContext<'a>>,
                    parent: Rc<dyn ParentContext<'a> + 'a>,
-                   vars: HashMap<Ident<'a>, Typ<'a>>) -> BlockContext<'a> {
+                   vars: HashMap<Ident<'a>, Typ<'a>>,
+                   constants: HashSet<Ident<'a>>) -> BlockContext<'a> {
         let index = parent.fresh_index();
 
-        for (name, _) in &vars {
-            parent.declare(BlockIdent::Local(index, name))
+        for (name, typ) in &vars {
+            parent.declare(BlockIdent::Local(index, name), typ)
         }
 
         BlockContext {
             context,
             parent,
             vars,
+            constants,
+            declared: RefCell::new(HashSet::new()),
             index,
         }
     }
 }
 
+impl<'a> BlockContext<'a> {
+    /// Makes a variable of this block visible : its scope starts at its declarator.
+    pub fn open(&self, ident: Ident<'a>) {
+        self.declared.borrow_mut().insert(ident);
+    }
+
+    fn is_declared(&self, ident: Ident<'a>) -> bool {
+        self.declared.borrow().contains(ident)
+    }
+}
+
 impl<'a> ParentContext<'a> for BlockContext<'a> {
-    fn declare(&self, ident: BlockIdent<'a>) {
-        self.parent.declare(ident)
+    fn declare(&self, ident: BlockIdent<'a>, typ: &Typ<'a>) {
+        self.parent.declare(ident, typ)
     }
 
     fn typ(&self, ident: Ident<'a>) -> Option<Typ<'a>> {
         match self.vars.get(ident) {
-            None => self.parent.typ(ident),
-            Some(x) => Some(x.clone())
+            Some(x) if self.is_declared(ident) => Some(x.clone()),
+            _ => self.parent.typ(ident),
         }
     }
 
@@ -180,7 +245,7 @@ impl<'a> ParentContext<'a> for BlockContext<'a> {
     }
 
     fn get_block_ident(&self, ident: Ident<'a>) -> BlockIdent<'a> {
-        if self.vars.contains_key(ident) {
+        if self.is_declared(ident) {
             BlockIdent::Local(
                 self.index,
                 ident,
@@ -189,4 +254,16 @@ impl<'a> ParentContext<'a> for BlockContext<'a> {
             self.parent.get_block_ident(ident)
         }
     }
+
+    fn is_const(&self, ident: Ident<'a>) -> bool {
+        if self.is_declared(ident) {
+            self.constants.contains(ident)
+        } else {
+            self.parent.is_const(ident)
+        }
+    }
+
+    fn declare_constant(&self, ident: BlockIdent<'a>, value: Value) {
+        self.parent.declare_constant(ident, value)
+    }
 }
@@ -1,8 +1,9 @@
+use std::fmt::{Display, Formatter};
 use std::rc::Rc;
 use derive_new::new;
 use derive_getters::Getters;
 use crate::common::Ident;
-use crate::typer::structure::{BlockIdent, Struct, Typ};
+use crate::typer::structure::{declaration, BlockIdent, Struct, Typ};
 
 #[allow(dead_code)]
 #[derive(new, Debug)]
@@ -24,9 +25,17 @@ pub struct DuplicateArgName<'a> {
     arg_name: BlockIdent<'a>,
 }
 
+/// Part of the source an error is about, from its first identifier to its last one : constants and
+/// punctuation aren't slices of the source, so they don't bound spans.
+#[derive(new, Debug, Clone, Copy)]
+pub struct Span<'a> {
+    pub first: Ident<'a>,
+    pub last: Ident<'a>,
+}
+
 #[derive(Debug)]
 pub enum TypError<'a> {
-    VariableDoesNotExist,
+    VariableDoesNotExist(Ident<'a>),
     StructDoesNotExist(Ident<'a>),
     DuplicateVarName(Ident<'a>),
     DuplicateFunName(Ident<'a>),
@@ -42,6 +51,101 @@ pub enum TypError<'a> {
     TooFewArguments,
     CallingANonFunctionExpression,
     AssigningToNonAssignableExpression,
+    AssigningToConstVariable(Ident<'a>),
+    AssigningToConstField(Ident<'a>),
     WrongExpressionTyp(IncompatibleTyp<'a>),
     DuplicateArgName(Ident<'a>),
+    AggregateUsedAsValue(Ident<'a>),
+    DotOnNonAggregate(Ident<'a>),
+    AddressOfNonAggregate,
+    /// An error without identifier, at the expression or statement it was found in
+    Located(Box<TypError<'a>>, Span<'a>),
+}
+
+impl<'a> TypError<'a> {
+    /// Identifier of the source the error is about, if any.
+    pub fn anchor(&self) -> Option<Ident<'a>> {
+        match self {
+            TypError::VariableDoesNotExist(ident)
+            | TypError::StructDoesNotExist(ident)
+            | TypError::DuplicateVarName(ident)
+            | TypError::DuplicateFunName(ident)
+            | TypError::DuplicateStructName(ident)
+            | TypError::DereferenceNonStructPointer(ident)
+            | TypError::FieldDoesntExist(_, ident)
+            | TypError::AccessingFieldOnNonStructTyp(_, ident)
+            | TypError::FunctionDoesntExist(ident)
+            | TypError::AssigningToConstVariable(ident)
+            | TypError::AssigningToConstField(ident)
+            | TypError::DuplicateArgName(ident)
+            | TypError::AggregateUsedAsValue(ident)
+            | TypError::DotOnNonAggregate(ident) => Some(ident),
+            TypError::DuplicateFieldName(duplicate) => Some(duplicate.field_name),
+            TypError::MissingMainFunction
+            | TypError::WrongMainFunctionSignature
+            | TypError::TooManyArguments
+            | TypError::TooFewArguments
+            | TypError::CallingANonFunctionExpression
+            | TypError::AssigningToNonAssignableExpression
+            | TypError::WrongExpressionTyp(_)
+            | TypError::AddressOfNonAggregate => None,
+            TypError::Located(error, _) => error.anchor(),
+        }
+    }
+
+    /// Part of the source the error is about, if any.
+    pub fn span(&self) -> Option<Span<'a>> {
+        match self {
+            TypError::Located(_, span) => Some(*span),
+            error => error.anchor().map(|anchor| Span::new(anchor, anchor)),
+        }
+    }
+
+    /// Locates the error at `span` if it isn't located yet.
+    pub fn at(self, span: Option<Span<'a>>) -> TypError<'a> {
+        match span {
+            Some(span) if self.span().is_none() => TypError::Located(Box::new(self), span),
+            _ => self,
+        }
+    }
+}
+
+impl Display for TypError<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypError::VariableDoesNotExist(x) => write!(f, "variable `{}` doesn't exist", x),
+            TypError::StructDoesNotExist(x) => write!(f, "struct `{}` doesn't exist", x),
+            TypError::DuplicateVarName(x) => write!(f, "variable `{}` is declared twice", x),
+            TypError::DuplicateFunName(x) => write!(f, "function `{}` is declared twice", x),
+            TypError::DuplicateStructName(x) => write!(f, "struct `{}` is declared twice", x),
+            TypError::DereferenceNonStructPointer(x) => write!(f, "`->{}` on a value which isn't a struct pointer", x),
+            TypError::FieldDoesntExist(structure, x) => write!(f, "struct `{}` has no field `{}`", structure.name(), x),
+            TypError::AccessingFieldOnNonStructTyp(typ, x) => {
+                write!(f, "`->{}` on a value of type `{}`", x, declaration(typ, ""))
+            }
+            TypError::DuplicateFieldName(duplicate) => {
+                write!(f, "field `{}` is declared twice in struct `{}`", duplicate.field_name, duplicate.struct_name)
+            }
+            TypError::FunctionDoesntExist(x) => write!(f, "function `{}` doesn't exist", x),
+            TypError::MissingMainFunction => write!(f, "missing `main` function"),
+            TypError::WrongMainFunctionSignature => write!(f, "`main` must be declared as `int main()`"),
+            TypError::TooManyArguments => write!(f, "too many arguments in call"),
+            TypError::TooFewArguments => write!(f, "too few arguments in call"),
+            TypError::CallingANonFunctionExpression => write!(f, "calling a value which isn't a function"),
+            TypError::AssigningToNonAssignableExpression => write!(f, "assigning to an expression which isn't assignable"),
+            TypError::AssigningToConstVariable(x) => write!(f, "assigning to constant variable `{}`", x),
+            TypError::AssigningToConstField(x) => write!(f, "assigning to constant field `{}`", x),
+            TypError::WrongExpressionTyp(incompatible) => write!(
+                f,
+                "expected type `{}`, found `{}`",
+                declaration(&incompatible.expected, ""),
+                declaration(&incompatible.actual, ""),
+            ),
+            TypError::DuplicateArgName(x) => write!(f, "argument `{}` is declared twice", x),
+            TypError::AggregateUsedAsValue(x) => write!(f, "struct `{}` used as a value", x),
+            TypError::DotOnNonAggregate(x) => write!(f, "`.{}` on a value which isn't a struct", x),
+            TypError::AddressOfNonAggregate => write!(f, "taking the address of a value which isn't a struct or a function"),
+            TypError::Located(error, _) => write!(f, "{}", error),
+        }
+    }
 }
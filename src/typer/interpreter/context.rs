@@ -1,15 +1,28 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
-use crate::common::{Ident, Stdout};
+use std::rc::Rc;
+use itertools::Itertools;
+use crate::common::{FUN_ADDRESS_BASE, Ident, Stdout};
+use crate::common::StackOffset;
 use crate::typer::interpreter::{interp_block, TyperInterpreterResult, Value};
-use crate::typer::structure::{BlockIdent, Fun};
+use crate::typer::interpreter::defaults::alloc;
+use crate::typer::interpreter::error::TypInterpreterError;
+use crate::typer::interpreter::observer::Observer;
+use crate::typer::structure::{BlockIdent, Field, Fun};
 
 const DEFAULT_FIELD_VALUE: Value = 0;
+const FIELD_SIZE: Value = 8;
+/// Calls nested deeper make the program overflow its stack. The interpreter recurses on the calls
+/// of the program, as well as on its statements : this depth stays within the 8 MiB stack of a
+/// default thread, even unoptimised.
+const MAX_CALL_DEPTH: usize = 200;
 pub type InterpreterFunctions<'a> = HashMap<Ident<'a>, Box<dyn TyperInterpreterFun<'a> + 'a>>;
 
 pub type InterpreterMemory<'a> = HashMap<Value, MemoryStruct<'a>>;
 
 pub enum InterpreterContext<'a> {
-    Root,
+    /// Bottom of the scopes of a function, run in that many calls, with the observer of the program.
+    Root(usize, Rc<RefCell<dyn Observer<'a> + 'a>>),
     Node(HashMap<BlockIdent<'a>, Value>, Box<InterpreterContext<'a>>)
 }
 
@@ -20,30 +33,58 @@ pub struct MemoryVar<'a> {
 
 #[derive(Debug)]
 pub struct MemoryStruct<'a> {
+    /// Number of bytes allocated.
+    pub size: Value,
     pub fields: HashMap<Ident<'a>, Value>,
 }
 
 impl<'x> InterpreterContext<'x> {
 
-    pub fn new() -> InterpreterContext<'x>{
-        return InterpreterContext::Node(HashMap::new(), Box::new(InterpreterContext::Root))
+    pub fn new(observer: Rc<RefCell<dyn Observer<'x> + 'x>>) -> InterpreterContext<'x>{
+        return InterpreterContext::Node(HashMap::new(), Box::new(InterpreterContext::Root(0, observer)))
+    }
+
+    /// Context of a function called from this one.
+    pub fn callee(&self) -> TyperInterpreterResult<'x, InterpreterContext<'x>> {
+        let depth = self.depth() + 1;
+        if depth > MAX_CALL_DEPTH {
+            return Err(TypInterpreterError::StackOverflow);
+        }
+
+        Ok(InterpreterContext::Node(HashMap::new(), Box::new(InterpreterContext::Root(depth, self.observer().clone()))))
+    }
+
+    fn depth(&self) -> usize {
+        match self {
+            InterpreterContext::Root(depth, _) => *depth,
+            InterpreterContext::Node(_, parent) => parent.depth(),
+        }
+    }
+
+    pub fn observer(&self) -> &Rc<RefCell<dyn Observer<'x> + 'x>> {
+        match self {
+            InterpreterContext::Root(_, observer) => observer,
+            InterpreterContext::Node(_, parent) => parent.observer(),
+        }
     }
 
     pub fn get(&self, ident: BlockIdent<'x>) -> Value {
+        self.lookup(ident).unwrap_or(DEFAULT_FIELD_VALUE)
+    }
+
+    pub fn lookup(&self, ident: BlockIdent<'x>) -> Option<Value> {
         match self {
-            InterpreterContext::Root => {
-                DEFAULT_FIELD_VALUE
-            },
+            InterpreterContext::Root(..) => None,
             InterpreterContext::Node(scope, parent) => {
                 scope.get(&ident)
-                .map_or_else(|| parent.get(ident),|value| *value)
+                .map_or_else(|| parent.lookup(ident), |value| Some(*value))
             }
         }
     }
 
     pub fn set(&mut self, ident: BlockIdent<'x>, value: Value) {
         match self {
-            InterpreterContext::Root => {},
+            InterpreterContext::Root(..) => {},
             InterpreterContext::Node(scope, parent) => {
                 let _ = scope.insert(ident, value);
             }
@@ -52,8 +93,23 @@ impl<'x> InterpreterContext<'x> {
 }
 
 impl<'x> MemoryStruct<'x> {
-    pub fn new<'a>() -> MemoryStruct<'a> {
-        MemoryStruct { fields: HashMap::new() }
+    pub fn new<'a>(size: Value) -> MemoryStruct<'a> {
+        MemoryStruct { size, fields: HashMap::new() }
+    }
+
+    /// The structure allocated at the address, if the field lies within it.
+    pub fn at<'m>(memory: &'m mut InterpreterMemory<'x>, address: Value, field: &Field) -> TyperInterpreterResult<'x, &'m mut MemoryStruct<'x>> {
+        if address == 0 {
+            return Err(TypInterpreterError::NullDereference);
+        }
+
+        let structure = memory.get_mut(&address).ok_or(TypInterpreterError::DanglingDereference(address))?;
+
+        if field.c_offset() as Value + FIELD_SIZE > structure.size {
+            return Err(TypInterpreterError::FieldOutOfBounds(address, field.c_offset() as StackOffset));
+        }
+
+        Ok(structure)
     }
 
     pub fn get<'a: 'x>(&self, ident: Ident<'a>) -> Value {
@@ -66,12 +122,37 @@ impl<'x> MemoryStruct<'x> {
     }
 }
 
+pub fn fun_address<'a>(functions: &InterpreterFunctions<'a>, name: Ident<'a>) -> Option<Value> {
+    functions.keys()
+        .sorted()
+        .position(|x| *x == name)
+        .map(|index| FUN_ADDRESS_BASE + index as Value)
+}
+
+pub fn fun_at<'a>(functions: &InterpreterFunctions<'a>, address: Value) -> Option<Ident<'a>> {
+    let index = usize::try_from(address - FUN_ADDRESS_BASE).ok()?;
+    functions.keys().sorted().nth(index).cloned()
+}
+
 pub trait TyperInterpreterFun<'a> {
-    fn call(&self, context: &mut InterpreterContext<'a>, functions:&InterpreterFunctions<'a>, memory: &mut InterpreterMemory<'a>, stdout:&mut Stdout) -> TyperInterpreterResult<Option<Value>>;
+    fn arguments(&self) -> Vec<BlockIdent<'a>>;
+    fn call(&self, context: &mut InterpreterContext<'a>, functions:&InterpreterFunctions<'a>, memory: &mut InterpreterMemory<'a>, stdout:&mut Stdout) -> TyperInterpreterResult<'a, Option<Value>>;
 }
 
 impl<'a> TyperInterpreterFun<'a> for &'a Fun<'a> {
-    fn call(&self, context: &mut InterpreterContext<'a>, functions:&InterpreterFunctions<'a>, memory: &mut InterpreterMemory<'a>, stdout:&mut Stdout) -> TyperInterpreterResult<Option<Value>> {
-        interp_block(context, functions, memory, stdout, self.block())
+    fn arguments(&self) -> Vec<BlockIdent<'a>> {
+        self.signature().args().iter().map(|arg| arg.name().clone()).collect()
+    }
+
+    fn call(&self, context: &mut InterpreterContext<'a>, functions:&InterpreterFunctions<'a>, memory: &mut InterpreterMemory<'a>, stdout:&mut Stdout) -> TyperInterpreterResult<'a, Option<Value>> {
+        for (aggregate, structure) in self.aggregates() {
+            let address = alloc(memory, structure.c_size());
+            context.set(aggregate.clone(), address);
+        }
+        context.observer().borrow_mut().enter(self);
+        let result = interp_block(context, functions, memory, stdout, self.block())
+            .map_err(|error| error.in_fun(self.signature().name()))?;
+        context.observer().borrow_mut().leave(self);
+        Ok(result)
     }
 }
@@ -1,34 +1,98 @@
-use std::sync::Mutex;
-use crate::common::Stdout;
+use derive_new::new;
+use crate::common::{printf, STRING_ADDRESS_BASE, Stdin, Stdout};
+use crate::typer::interpreter::error::TypInterpreterError;
 use crate::typer::interpreter::context::{InterpreterContext, MemoryStruct, TyperInterpreterFun};
 use crate::typer::interpreter::{TyperInterpreterResult, Value};
-use crate::typer::structure::BlockIdent;
+use crate::typer::structure::{BlockIdent, VARIADIC_ARG};
 
 use super::context::{InterpreterFunctions, InterpreterMemory};
 
-static MALLOC_MEMORY_INDEX: Mutex<Value> = Mutex::new(1);
+/// Address of the first structure allocated, so that `0` is never a valid one.
+const HEAP_BASE: Value = 1;
 
 pub struct Malloc();
 
 pub struct Putchar();
 
+#[derive(new)]
+pub struct Getchar {
+    stdin: Stdin,
+}
+
+#[derive(new)]
+pub struct Printf {
+    strings: Vec<String>,
+}
+
 impl<'a> TyperInterpreterFun<'a> for Putchar {
-    fn call(&self, context: &mut InterpreterContext<'a>, _functions: &InterpreterFunctions<'a>, _memory: &mut InterpreterMemory<'a>, stdout:&mut Stdout) -> TyperInterpreterResult<Option<Value>> {
+    fn arguments(&self) -> Vec<BlockIdent<'a>> {
+        vec![BlockIdent::Arg(0, "c")]
+    }
+
+    fn call(&self, context: &mut InterpreterContext<'a>, _functions: &InterpreterFunctions<'a>, _memory: &mut InterpreterMemory<'a>, stdout:&mut Stdout) -> TyperInterpreterResult<'a, Option<Value>> {
         let value = context.get(BlockIdent::Arg(0, "c"));
         stdout.putchar(value as u8 as char);
         Ok(Some(value))
     }
 }
 
-impl<'a> TyperInterpreterFun<'a> for Malloc {
-    fn call(&self, _context: &mut InterpreterContext<'a>, _functions:&InterpreterFunctions<'a>, memory: &mut InterpreterMemory<'a>, _stdout:&mut Stdout) -> TyperInterpreterResult<Option<Value>> {
-        let mut index = MALLOC_MEMORY_INDEX.lock().unwrap();
+impl<'a> TyperInterpreterFun<'a> for Getchar {
+    fn arguments(&self) -> Vec<BlockIdent<'a>> {
+        vec![]
+    }
+
+    fn call(&self, _context: &mut InterpreterContext<'a>, _functions: &InterpreterFunctions<'a>, _memory: &mut InterpreterMemory<'a>, _stdout: &mut Stdout) -> TyperInterpreterResult<'a, Option<Value>> {
+        Ok(Some(self.stdin.getchar()))
+    }
+}
+
+/// Nothing is ever freed, so structures are allocated at consecutive addresses.
+pub fn alloc(memory: &mut InterpreterMemory, size: Value) -> Value {
+    let address = HEAP_BASE + memory.len() as Value;
+
+    memory.insert(address, MemoryStruct::new(size));
 
-        memory.insert(*index, MemoryStruct::new());
+    address
+}
+
+impl<'a> TyperInterpreterFun<'a> for Printf {
+    fn arguments(&self) -> Vec<BlockIdent<'a>> {
+        vec![BlockIdent::Arg(0, "format")]
+    }
+
+    fn call(&self, context: &mut InterpreterContext<'a>, _functions: &InterpreterFunctions<'a>, _memory: &mut InterpreterMemory<'a>, stdout: &mut Stdout) -> TyperInterpreterResult<'a, Option<Value>> {
+        let string_at = |address: Value| {
+            address.checked_sub(STRING_ADDRESS_BASE)
+                .and_then(|offset| usize::try_from(offset).ok())
+                .and_then(|index| self.strings.get(index))
+                .cloned()
+        };
+
+        let format = string_at(context.get(BlockIdent::Arg(0, "format")))
+            .ok_or(TypInterpreterError::Printf("Invalid printf format"))?;
+
+        let args = (1..)
+            .map_while(|index| context.lookup(BlockIdent::Arg(index, VARIADIC_ARG)))
+            .collect::<Vec<Value>>();
+
+        let output = printf::format(&format, &args, string_at)
+            .map_err(TypInterpreterError::Printf)?;
 
-        let old_index = index.clone();
-        *index += 1;
+        for c in output.chars() {
+            stdout.putchar(c);
+        }
+
+        Ok(Some(output.len() as Value))
+    }
+}
+
+impl<'a> TyperInterpreterFun<'a> for Malloc {
+    fn arguments(&self) -> Vec<BlockIdent<'a>> {
+        vec![BlockIdent::Arg(0, "n")]
+    }
 
-        Ok(Some(old_index))
+    fn call(&self, context: &mut InterpreterContext<'a>, _functions:&InterpreterFunctions<'a>, memory: &mut InterpreterMemory<'a>, _stdout:&mut Stdout) -> TyperInterpreterResult<'a, Option<Value>> {
+        let size = context.get(BlockIdent::Arg(0, "n"));
+        Ok(Some(alloc(memory, size)))
     }
 }
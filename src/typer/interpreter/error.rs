@@ -1,2 +1,78 @@
+use std::fmt::{Display, Formatter};
+use crate::common::arith::DivisionError;
+use crate::common::{Ident, StackOffset, Value};
+
 #[derive(Debug)]
-pub enum TypInterpreterError {}
+pub enum TypInterpreterError<'a> {
+    FunctionDoesNotExist(String),
+    InvalidFunctionAddress(i64),
+    Printf(&'static str),
+    DivisionByZero,
+    /// The quotient of `Value::MIN / -1` doesn't fit, which makes `idivq` trap.
+    DivisionOverflow,
+    NullDereference,
+    /// Access to an address which was never allocated.
+    DanglingDereference(Value),
+    /// Access past the end of the structure allocated at the address.
+    FieldOutOfBounds(Value, StackOffset),
+    StackOverflow,
+    /// Error raised while running the function.
+    Runtime(String, Box<TypInterpreterError<'a>>),
+    /// Error raised while running the statement starting at the token.
+    Located(Ident<'a>, Box<TypInterpreterError<'a>>),
+    Other(&'static str),
+}
+
+impl<'a> TypInterpreterError<'a> {
+    /// Locates the error in the function which raised it, unless it was already.
+    pub fn in_fun(self, fun: &str) -> TypInterpreterError<'a> {
+        match self {
+            error @ TypInterpreterError::Runtime(..) => error,
+            error => TypInterpreterError::Runtime(String::from(fun), Box::new(error)),
+        }
+    }
+
+    /// Locates the error at the statement starting at `anchor`, unless it was already.
+    pub fn at(self, anchor: Ident<'a>) -> TypInterpreterError<'a> {
+        match self {
+            error @ (TypInterpreterError::Runtime(..) | TypInterpreterError::Located(..)) => error,
+            error => TypInterpreterError::Located(anchor, Box::new(error)),
+        }
+    }
+
+    /// First token of the statement which raised the error, if known.
+    pub fn anchor(&self) -> Option<Ident<'a>> {
+        match self {
+            TypInterpreterError::Runtime(_, error) => error.anchor(),
+            TypInterpreterError::Located(anchor, _) => Some(*anchor),
+            _ => None,
+        }
+    }
+}
+
+impl Display for TypInterpreterError<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypInterpreterError::DivisionByZero => write!(f, "division by zero"),
+            TypInterpreterError::DivisionOverflow => write!(f, "division overflow"),
+            TypInterpreterError::NullDereference => write!(f, "null pointer dereference"),
+            TypInterpreterError::DanglingDereference(address) => write!(f, "dereference of unallocated address {}", address),
+            TypInterpreterError::FieldOutOfBounds(address, offset) => {
+                write!(f, "offset {} is out of the structure allocated at {}", offset, address)
+            }
+            TypInterpreterError::StackOverflow => write!(f, "stack overflow"),
+            TypInterpreterError::Runtime(fun, error) => write!(f, "{} in function `{}`", error, fun),
+            TypInterpreterError::Located(_, error) => write!(f, "{}", error),
+            error => write!(f, "{:?}", error),
+        }
+    }
+}
+
+impl From<DivisionError> for TypInterpreterError<'_> {
+    fn from(error: DivisionError) -> Self {
+        match error {
+            DivisionError::DivisionByZero => TypInterpreterError::DivisionByZero,
+            DivisionError::Overflow => TypInterpreterError::DivisionOverflow,
+        }
+    }
+}
@@ -1,25 +1,36 @@
 pub mod defaults;
 pub mod error;
+pub mod observer;
+pub mod tracer;
 mod context;
 
+use std::cell::RefCell;
 use std::collections::HashMap;
-use context::InterpreterFunctions;
+use std::rc::Rc;
+use context::{fun_address, fun_at, InterpreterFunctions};
+use crate::common::arith::idiv;
 use crate::common::bool::{Bool, ToCBool};
-use crate::common::{MALLOC, PUTCHAR, Stdout};
+use crate::common::{Execution, GETCHAR, MAIN, MALLOC, PRINTF, printf, PUTCHAR, STRING_ADDRESS_BASE, Stdin, Stdout};
 use crate::rtl::structure::BlockIdent;
-use crate::typer::interpreter::context::InterpreterContext;
-use crate::typer::interpreter::defaults::{Malloc, Putchar};
+use crate::typer::interpreter::context::{InterpreterContext, MemoryStruct};
+use crate::typer::interpreter::defaults::{Getchar, Malloc, Printf, Putchar};
 use crate::typer::interpreter::error::TypInterpreterError;
-use crate::typer::structure::{Binop, Block, Expr, ExprNode, File, Stmt, Unop};
+use crate::typer::interpreter::observer::Observer;
+use crate::typer::structure::{Binop, Block, Expr, ExprNode, File, Stmt, Unop, VARIADIC_ARG};
 
 use self::context::InterpreterMemory;
 
 const DEFAULT_RETURN_VALUE: Value = 0;
 type Value = i64;
 
-pub type TyperInterpreterResult<T> = Result<T, TypInterpreterError>;
+pub type TyperInterpreterResult<'a, T> = Result<T, TypInterpreterError<'a>>;
 
-pub fn interp_typed_file<'a>(file: &'a File<'a>) -> TyperInterpreterResult<Stdout> {
+pub fn interp_typed_file<'a>(file: &'a File<'a>) -> TyperInterpreterResult<'a, Execution> {
+    interp_typed_file_with(file, Stdin::default(), Rc::new(RefCell::new(())))
+}
+
+/// Runs the program on the input, notifying the observer of each statement run.
+pub fn interp_typed_file_with<'a>(file: &'a File<'a>, stdin: Stdin, observer: Rc<RefCell<dyn Observer<'a> + 'a>>) -> TyperInterpreterResult<'a, Execution> {
     let mut functions: InterpreterFunctions = HashMap::new();
 
     for (name, fun) in file.funs() {
@@ -27,22 +38,28 @@ pub fn interp_typed_file<'a>(file: &'a File<'a>) -> TyperInterpreterResult<Stdou
     }
 
     functions.insert(PUTCHAR, Box::new(Putchar()));
+    functions.insert(GETCHAR, Box::new(Getchar::new(stdin)));
     functions.insert(MALLOC, Box::new(Malloc()));
+    functions.insert(PRINTF, Box::new(Printf::new(file.strings().iter().map(|x| printf::unescape(x)).collect())));
 
     let functions = functions;
 
-    let mut context = InterpreterContext::new();
+    let mut context = InterpreterContext::new(observer);
 
     let mut stdout = Stdout::new();
     let mut memory = HashMap::new();
 
-    let main =  functions.get("main").expect("No main function");
-    main.call(&mut context, &functions, &mut memory, &mut stdout)?;
+    let main = functions.get(MAIN).ok_or(TypInterpreterError::FunctionDoesNotExist(String::from(MAIN)))?;
+    let status = main.call(&mut context, &functions, &mut memory, &mut stdout)?.unwrap_or(DEFAULT_RETURN_VALUE);
 
-    Ok(stdout)
+    Ok(Execution { stdout, status })
 }
 
-fn interp_stmt<'a>(context: &mut InterpreterContext<'a>, functions:&InterpreterFunctions<'a>, memory: &mut InterpreterMemory<'a>, stdout:&mut Stdout, stmt: &Stmt<'a>) -> TyperInterpreterResult<Option<Value>> {
+fn interp_stmt<'a>(context: &mut InterpreterContext<'a>, functions:&InterpreterFunctions<'a>, memory: &mut InterpreterMemory<'a>, stdout:&mut Stdout, stmt: &Stmt<'a>) -> TyperInterpreterResult<'a, Option<Value>> {
+    if !matches!(stmt, Stmt::SBlock(_) | Stmt::SSkip) {
+        context.observer().borrow_mut().stmt(stmt)?;
+    }
+
     match stmt {
         Stmt::SSkip => Ok(None),
         Stmt::SExpr(e) => {
@@ -61,11 +78,12 @@ fn interp_stmt<'a>(context: &mut InterpreterContext<'a>, functions:&InterpreterF
 
             Ok(None)
         }
-        Stmt::SWhile(expr, stmt) => {
+        Stmt::SWhile(expr, body) => {
             while interp_expr(context, functions, memory, stdout, expr)?.to_bool() {
-                if let Some(x) = interp_stmt(context, functions, memory, stdout, stmt)? {
+                if let Some(x) = interp_stmt(context, functions, memory, stdout, body)? {
                     return Ok(Some(x));
                 }
+                context.observer().borrow_mut().stmt(stmt)?;
             }
             Ok(None)
         }
@@ -78,9 +96,10 @@ fn interp_stmt<'a>(context: &mut InterpreterContext<'a>, functions:&InterpreterF
     }
 }
 
-fn interp_block<'a>(context: &mut InterpreterContext<'a>, functions:&InterpreterFunctions<'a>, memory: &mut InterpreterMemory<'a>, stdout:&mut Stdout, block: &Block<'a>) -> TyperInterpreterResult<Option<Value>> {
-    for stmt in block.stmts() {
-        if let Some(x) = interp_stmt(context, functions, memory, stdout, stmt)? {
+fn interp_block<'a>(context: &mut InterpreterContext<'a>, functions:&InterpreterFunctions<'a>, memory: &mut InterpreterMemory<'a>, stdout:&mut Stdout, block: &Block<'a>) -> TyperInterpreterResult<'a, Option<Value>> {
+    for (stmt, anchor) in block.stmts().iter().zip(block.anchors()) {
+        let returned = interp_stmt(context, functions, memory, stdout, stmt).map_err(|error| error.at(anchor))?;
+        if let Some(x) = returned {
             return Ok(Some(x));
         }
     }
@@ -88,18 +107,16 @@ fn interp_block<'a>(context: &mut InterpreterContext<'a>, functions:&Interpreter
     Ok(None)
 }
 
-fn interp_expr<'a>(context: &mut InterpreterContext<'a>, functions:&InterpreterFunctions<'a>, memory: &mut InterpreterMemory<'a>, stdout:&mut Stdout, expr: &Expr<'a>) -> TyperInterpreterResult<Value> {
+fn interp_expr<'a>(context: &mut InterpreterContext<'a>, functions:&InterpreterFunctions<'a>, memory: &mut InterpreterMemory<'a>, stdout:&mut Stdout, expr: &Expr<'a>) -> TyperInterpreterResult<'a, Value> {
     match expr.node() {
         ExprNode::EConst(x) => Ok(*x as Value),
+        ExprNode::EString(index) => Ok(STRING_ADDRESS_BASE + *index as Value),
         ExprNode::EAccessLocal(x) => {
             Ok(context.get(x.clone()))
         }
         ExprNode::EAccessField(expr, y) => {
             let address = interp_expr(context, functions, memory, stdout, expr)?;
-            Ok(memory
-                .get(&address)
-                .expect("Address doesn't point to anything")
-                .get(y.name()))
+            Ok(MemoryStruct::at(memory, address, y)?.get(y.name()))
         }
         ExprNode::EAssignLocal(var, expr) => {
             let value = interp_expr(context, functions, memory, stdout, expr)?;
@@ -111,10 +128,7 @@ fn interp_expr<'a>(context: &mut InterpreterContext<'a>, functions:&InterpreterF
 
             let address = interp_expr(context, functions, memory, stdout, expr)?;
 
-            memory
-                .get_mut(&address)
-                .expect(&*format!("Empty address {}", address))
-                .set(field.name(), value);
+            MemoryStruct::at(memory, address, field)?.set(field.name(), value);
 
             Ok(value)
         }
@@ -122,7 +136,7 @@ fn interp_expr<'a>(context: &mut InterpreterContext<'a>, functions:&InterpreterF
             Unop::UNot => {
                 if interp_expr(context, functions, memory, stdout, expr)?.to_bool() { 0 } else { 1 }
             }
-            Unop::UMinus => -interp_expr(context, functions, memory, stdout, expr)?
+            Unop::UMinus => interp_expr(context, functions, memory, stdout, expr)?.wrapping_neg()
         }),
         ExprNode::EBinop(binop, expr_1, expr_2) => Ok(
             match binop {
@@ -134,14 +148,17 @@ fn interp_expr<'a>(context: &mut InterpreterContext<'a>, functions:&InterpreterF
                 Binop::BLe => (interp_expr(context, functions, memory, stdout, expr_1)? <= interp_expr(context, functions, memory, stdout, expr_2)?).to_minic_bool(),
                 Binop::BAnd => (interp_expr(context, functions, memory, stdout, expr_1)?.to_bool() && interp_expr(context, functions, memory, stdout, expr_2)?.to_bool()).to_minic_bool(),
                 Binop::BOr => (interp_expr(context, functions, memory, stdout, expr_1)?.to_bool() || interp_expr(context, functions, memory, stdout, expr_2)?.to_bool()).to_minic_bool(),
-                Binop::BAdd => interp_expr(context, functions, memory, stdout, expr_1)? + interp_expr(context, functions, memory, stdout, expr_2)?,
-                Binop::BSub => interp_expr(context, functions, memory, stdout, expr_1)? - interp_expr(context, functions, memory, stdout, expr_2)?,
-                Binop::BMul => interp_expr(context, functions, memory, stdout, expr_1)? * interp_expr(context, functions, memory, stdout, expr_2)?,
-                Binop::BDiv => interp_expr(context, functions, memory, stdout, expr_1)? / interp_expr(context, functions, memory, stdout, expr_2)?,
+                Binop::BAdd => interp_expr(context, functions, memory, stdout, expr_1)?.wrapping_add(interp_expr(context, functions, memory, stdout, expr_2)?),
+                Binop::BSub => interp_expr(context, functions, memory, stdout, expr_1)?.wrapping_sub(interp_expr(context, functions, memory, stdout, expr_2)?),
+                Binop::BMul => interp_expr(context, functions, memory, stdout, expr_1)?.wrapping_mul(interp_expr(context, functions, memory, stdout, expr_2)?),
+                Binop::BDiv => {
+                    let value = interp_expr(context, functions, memory, stdout, expr_1)?;
+                    idiv(value, interp_expr(context, functions, memory, stdout, expr_2)?)?.0
+                }
             }
         ),
         ExprNode::ECall(fun, args) => {
-            let mut new_context:InterpreterContext<'a> = InterpreterContext::new();
+            let mut new_context: InterpreterContext<'a> = context.callee()?;
 
             for arg in args {
                 let name: BlockIdent<'a> = arg.formal().name().clone();
@@ -154,7 +171,30 @@ fn interp_expr<'a>(context: &mut InterpreterContext<'a>, functions:&InterpreterF
 
             Ok(functions
                 .get(fun.name())
-                .expect("Function doesn't exist")
+                .ok_or(TypInterpreterError::FunctionDoesNotExist(String::from(*fun.name())))?
+                .call(&mut new_context, functions, memory, stdout)?
+                .unwrap_or(DEFAULT_RETURN_VALUE))
+        }
+        ExprNode::EFunAddr(fun) => {
+            fun_address(functions, fun.name()).ok_or(TypInterpreterError::FunctionDoesNotExist(String::from(*fun.name())))
+        }
+        ExprNode::ECallStar(callee, args) => {
+            let address = interp_expr(context, functions, memory, stdout, callee)?;
+            let fun = fun_at(functions, address)
+                .and_then(|name| functions.get(name))
+                .ok_or(TypInterpreterError::InvalidFunctionAddress(address))?;
+
+            let mut new_context: InterpreterContext<'a> = context.callee()?;
+
+            let names = fun.arguments();
+
+            for (index, arg) in args.iter().enumerate() {
+                let name = names.get(index).cloned().unwrap_or(BlockIdent::Arg(index, VARIADIC_ARG));
+                let value = interp_expr(context, functions, memory, stdout, arg)?;
+                new_context.set(name, value)
+            }
+
+            Ok(fun
                 .call(&mut new_context, functions, memory, stdout)?
                 .unwrap_or(DEFAULT_RETURN_VALUE))
         }
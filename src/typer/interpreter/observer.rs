@@ -0,0 +1,18 @@
+use crate::typer::interpreter::TyperInterpreterResult;
+use crate::typer::structure::{Fun, Stmt};
+
+/// Follows the interpreter as it runs the program.
+pub trait Observer<'a> {
+    /// The function was called, and its arguments were set.
+    fn enter(&mut self, _fun: &'a Fun<'a>) {}
+
+    /// The statement of the function last entered is about to run, or the loop to test its condition again.
+    fn stmt(&mut self, _stmt: &Stmt<'a>) -> TyperInterpreterResult<'a, ()> {
+        Ok(())
+    }
+
+    /// The function returned.
+    fn leave(&mut self, _fun: &'a Fun<'a>) {}
+}
+
+impl Observer<'_> for () {}
@@ -0,0 +1,50 @@
+use std::io::Write;
+use crate::common::Ident;
+use crate::common::profile::Profile;
+use crate::typer::interpreter::error::TypInterpreterError;
+use crate::typer::interpreter::observer::Observer;
+use crate::typer::interpreter::TyperInterpreterResult;
+use crate::typer::structure::{stmt_head, File, Fun, Stmt};
+
+/// Counts the calls and statements of each function, and writes the statements run to the trace if any.
+pub struct Tracer<'a, W: Write> {
+    strings: &'a [Ident<'a>],
+    trace: Option<W>,
+    profile: Profile<'a>,
+    /// Functions being run, the innermost last.
+    funs: Vec<&'a Fun<'a>>,
+}
+
+impl<'a, W: Write> Tracer<'a, W> {
+    pub fn new(file: &'a File<'a>, trace: Option<W>) -> Tracer<'a, W> {
+        Tracer { strings: file.strings(), trace, profile: Profile::default(), funs: vec![] }
+    }
+
+    pub fn profile(&self) -> &Profile<'a> {
+        &self.profile
+    }
+}
+
+impl<'a, W: Write> Observer<'a> for Tracer<'a, W> {
+    fn enter(&mut self, fun: &'a Fun<'a>) {
+        self.profile.call(fun.signature().name());
+        self.funs.push(fun);
+    }
+
+    fn stmt(&mut self, stmt: &Stmt<'a>) -> TyperInterpreterResult<'a, ()> {
+        let Some(fun) = self.funs.last() else {
+            return Ok(());
+        };
+
+        self.profile.step(fun.signature().name());
+        match &mut self.trace {
+            Some(trace) => writeln!(trace, "{}: {}", fun.signature().name(), stmt_head(self.strings, fun, stmt))
+                .map_err(|_| TypInterpreterError::Other("Failed to write the trace")),
+            None => Ok(()),
+        }
+    }
+
+    fn leave(&mut self, _fun: &'a Fun<'a>) {
+        self.funs.pop();
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::{HashMap, HashSet};
+use crate::common::{Ident, MAIN};
+use crate::typer::nullness::lint_null_dereferences;
+use crate::typer::returns::{describe, falls_through};
+use crate::typer::structure::{Block, Expr, ExprNode, Fun, Stmt};
+use crate::warning::{Warning, WarningCode};
+
+/// Lints the typed functions, given in the order of the source file.
+pub fn lint_funs<'a>(funs: &HashMap<Ident<'a>, Fun<'a>>, order: &[Ident<'a>]) -> Vec<Warning<'a>> {
+    let called = called_funs(funs);
+    let mut warnings = vec![];
+
+    for name in order {
+        let fun = &funs[name];
+        let mut fun_warnings = vec![];
+
+        if !called.contains(name) {
+            fun_warnings.push(Warning::new(
+                WarningCode::UnusedFunction,
+                name,
+                Some(name),
+                format!("function `{}` is never called", name),
+            ));
+        }
+
+        lint_unused(fun, &mut fun_warnings);
+        lint_block(fun, fun.block(), &mut fun_warnings);
+        lint_null_dereferences(fun, &mut fun_warnings);
+
+        fun_warnings.sort_by_key(|warning| warning.anchor().map_or(usize::MAX, |x| x.as_ptr() as usize));
+        warnings.append(&mut fun_warnings);
+    }
+
+    warnings
+}
+
+/// Functions reachable from `main`, through calls or by taking their address.
+fn called_funs<'a>(funs: &HashMap<Ident<'a>, Fun<'a>>) -> HashSet<Ident<'a>> {
+    let mut called = HashSet::from([MAIN]);
+    let mut pending = vec![MAIN];
+
+    while let Some(name) = pending.pop() {
+        let mut callees = HashSet::new();
+        if let Some(fun) = funs.get(name) {
+            visit_block(fun.block(), &mut |expr| match expr.node() {
+                ExprNode::ECall(signature, _) | ExprNode::EFunAddr(signature) => {
+                    callees.insert(*signature.name());
+                }
+                _ => {}
+            });
+        }
+
+        for callee in callees {
+            if called.insert(callee) {
+                pending.push(callee);
+            }
+        }
+    }
+
+    called
+}
+
+fn lint_unused<'a>(fun: &Fun<'a>, warnings: &mut Vec<Warning<'a>>) {
+    let name = *fun.signature().name();
+    let mut read = HashSet::new();
+    visit_block(fun.block(), &mut |expr| {
+        if let ExprNode::EAccessLocal(ident) = expr.node() {
+            read.insert(ident.clone());
+        }
+    });
+
+    for arg in fun.signature().args() {
+        if !read.contains(arg.name()) {
+            warnings.push(Warning::new(
+                WarningCode::UnusedParameter,
+                name,
+                Some(arg.name().name()),
+                format!("parameter `{}` is never read in `{}`", arg.name().name(), name),
+            ));
+        }
+    }
+
+    for local in fun.locals().keys().filter(|local| !read.contains(local)) {
+        warnings.push(Warning::new(
+            WarningCode::UnusedVariable,
+            name,
+            Some(local.name()),
+            format!("variable `{}` is never read in `{}`", local.name(), name),
+        ));
+    }
+}
+
+fn lint_block<'a>(fun: &Fun<'a>, block: &Block<'a>, warnings: &mut Vec<Warning<'a>>) {
+    let name = *fun.signature().name();
+    let mut stmts = block.stmts().iter().zip(block.anchors());
+
+    for (stmt, _) in stmts.by_ref() {
+        lint_stmt(fun, stmt, warnings);
+        if falls_through(stmt).is_none() {
+            break;
+        }
+    }
+
+    if let Some((stmt, anchor)) = stmts.find(|(stmt, _)| !matches!(stmt, Stmt::SSkip)) {
+        warnings.push(Warning::new(
+            WarningCode::UnreachableCode,
+            name,
+            Some(*anchor),
+            format!("statement `{}` is never executed in `{}`", describe(stmt), name),
+        ));
+    }
+}
+
+fn lint_stmt<'a>(fun: &Fun<'a>, stmt: &Stmt<'a>, warnings: &mut Vec<Warning<'a>>) {
+    match stmt {
+        Stmt::SSkip | Stmt::SExpr(_) | Stmt::SReturn(_) => {}
+        Stmt::SIf(condition, stmt_if, stmt_else) => {
+            lint_condition(fun, "if", condition, warnings);
+            lint_stmt(fun, stmt_if, warnings);
+            lint_stmt(fun, stmt_else, warnings);
+        }
+        Stmt::SWhile(condition, body) => {
+            // `while (1)` is the usual way to write an infinite loop
+            if !matches!(condition.node(), ExprNode::EConst(x) if *x != 0) {
+                lint_condition(fun, "while", condition, warnings);
+            }
+            lint_stmt(fun, body, warnings);
+        }
+        Stmt::SBlock(block) => lint_block(fun, block, warnings),
+    }
+}
+
+fn lint_condition<'a>(fun: &Fun<'a>, keyword: &str, condition: &Expr<'a>, warnings: &mut Vec<Warning<'a>>) {
+    let (value, anchor) = match condition.node() {
+        ExprNode::EConst(x) => (*x, None),
+        ExprNode::EAccessLocal(ident) => match fun.constants().get(ident) {
+            Some(x) => (*x, Some(ident.name())),
+            None => return,
+        },
+        _ => return,
+    };
+
+    let name = *fun.signature().name();
+    warnings.push(Warning::new(
+        WarningCode::ConstantCondition,
+        name,
+        anchor,
+        format!("condition of `{}` is always {} in `{}`", keyword, value != 0, name),
+    ));
+}
+
+fn visit_block<'a, F: FnMut(&Expr<'a>)>(block: &Block<'a>, f: &mut F) {
+    block.stmts().iter().for_each(|stmt| visit_stmt(stmt, f))
+}
+
+fn visit_stmt<'a, F: FnMut(&Expr<'a>)>(stmt: &Stmt<'a>, f: &mut F) {
+    match stmt {
+        Stmt::SSkip => {}
+        Stmt::SExpr(expr) | Stmt::SReturn(expr) => visit_expr(expr, f),
+        Stmt::SIf(condition, stmt_if, stmt_else) => {
+            visit_expr(condition, f);
+            visit_stmt(stmt_if, f);
+            visit_stmt(stmt_else, f);
+        }
+        Stmt::SWhile(condition, body) => {
+            visit_expr(condition, f);
+            visit_stmt(body, f);
+        }
+        Stmt::SBlock(block) => visit_block(block, f),
+    }
+}
+
+/// Calls `f` on `expr` and its sub-expressions, in evaluation order.
+fn visit_expr<'a, F: FnMut(&Expr<'a>)>(expr: &Expr<'a>, f: &mut F) {
+    f(expr);
+    match expr.node() {
+        ExprNode::EConst(_)
+        | ExprNode::EString(_)
+        | ExprNode::EAccessLocal(_)
+        | ExprNode::EFunAddr(_) => {}
+        ExprNode::EAccessField(expr, _)
+        | ExprNode::EAssignLocal(_, expr)
+        | ExprNode::EUnop(_, expr) => visit_expr(expr, f),
+        ExprNode::EAssignField(expr, _, value) => {
+            visit_expr(expr, f);
+            visit_expr(value, f);
+        }
+        ExprNode::EBinop(_, expr_1, expr_2) => {
+            visit_expr(expr_1, f);
+            visit_expr(expr_2, f);
+        }
+        ExprNode::ECall(_, args) => args.iter().for_each(|arg| visit_expr(arg.expr(), f)),
+        ExprNode::ECallStar(callee, args) => {
+            visit_expr(callee, f);
+            args.iter().for_each(|arg| visit_expr(arg, f));
+        }
+    }
+}
@@ -2,15 +2,21 @@ pub mod context;
 pub mod error;
 pub mod structure;
 pub mod interpreter;
+mod returns;
+mod lints;
+mod nullness;
 
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use itertools::{EitherOrBoth, enumerate, Itertools};
 use structure::*;
+use crate::common::Ident;
 use crate::parser::structure as parser;
 use crate::typer::context::{BlockContext, FileContext, FunctionContext, ParentContext};
-use crate::typer::error::{DuplicateFieldName, IncompatibleTyp, TypError};
+use crate::typer::error::{DuplicateFieldName, IncompatibleTyp, Span, TypError};
+use crate::typer::returns::check_returns;
+use crate::typer::lints::lint_funs;
 
 pub type TypResult<'a, T> = Result<T, TypError<'a>>;
 
@@ -20,23 +26,31 @@ pub fn typ_file<'a>(file: &'a parser::File<'a>) -> TypResult<'a, File<'a>> {
     for structure in file.structs() {
         let name = structure.name();
 
-        let structure = typ_struct(context.clone(), structure)?;
+        let structure = Rc::new(typ_struct(context.clone(), structure)?);
+        context.refer(name, Definition::Struct(structure.clone()), &Typ::TStructValue(structure.clone()));
 
-        if let Some(_) = context.structs().borrow_mut().insert(name, Rc::new(structure)) {
+        if let Some(_) = context.structs().borrow_mut().insert(name, structure) {
             return Err(TypError::DuplicateStructName(name));
         }
     }
 
     let mut funs = HashMap::new();
+    let mut order = vec![];
 
     for fun in file.funs() {
         let name = fun.profile().name();
 
         let fun = typ_fun(context.clone(), fun)?;
         funs.insert(name.clone(), fun);
+        order.push(*name);
     }
 
-    Ok(check_main(File::new(funs))?)
+    let strings = context.strings().borrow().clone();
+    let mut warnings = context.warnings().replace(vec![]);
+    warnings.append(&mut lint_funs(&funs, &order));
+    let references = context.references().replace(vec![]);
+
+    Ok(check_main(File::new(funs, strings, warnings, references))?)
 }
 
 fn check_main(file: File) -> TypResult<File> {
@@ -71,19 +85,22 @@ fn typ_struct<'a>(context: Rc<FileContext<'a>>, structure: &'a parser::Struct<'a
     let struct_context = Rc::new(FileContext::new(
         Rc::new(structs.into()),
         context.funs().clone(),
+        context.strings().clone(),
+        context.warnings().clone(),
+        context.references().clone(),
     ));
 
     for (index, field) in enumerate(structure.fields()) {
         let field_name = field.name();
-
-        if let Some(_) = fields.borrow_mut().insert(
-            field_name.clone(),
-            Rc::new(Field::new(
-                field_name,
-                index as u8,
-                typ_typ(struct_context.clone(), field.typ())?.clone(),
-            )),
-        ) {
+        let typed_field = Rc::new(Field::new(
+            field_name,
+            index as u8,
+            typ_typ(struct_context.clone(), field.typ())?.clone(),
+            *field.constant(),
+        ));
+        context.refer(field_name, Definition::Field(typed_field.clone()), typed_field.typ());
+
+        if let Some(_) = fields.borrow_mut().insert(field_name.clone(), typed_field) {
             return Err(TypError::DuplicateFieldName(
                 DuplicateFieldName::new(struct_name, field_name)
             ));
@@ -110,6 +127,7 @@ fn typ_fun<'a>(context: Rc<FileContext<'a>>, fun: &'a parser::Fun<'a>) -> TypRes
         let block_name = BlockIdent::Arg(index, name.clone());
         let typ = typ_typ(context.clone(), arg.typ())?;
         let formal = Formal::new(block_name.clone(), typ.clone());
+        context.refer(name, Definition::Local(fun_name, block_name.clone()), &typ);
 
         // TODO check arg names
         args_vec.push(formal);
@@ -122,20 +140,26 @@ fn typ_fun<'a>(context: Rc<FileContext<'a>>, fun: &'a parser::Fun<'a>) -> TypRes
         args_vec.clone(),
     ));
 
-    let signature = Rc::new(Signature::new(fun_name.clone(), fun_typ.clone(), args_vec));
+    let signature = Rc::new(Signature::new(fun_name.clone(), fun_typ.clone(), args_vec, *fun.variadic()));
 
     if let Some(_) = context.funs().borrow_mut().insert(fun_name.clone(), signature.clone()) {
         return Err(TypError::DuplicateFunName(fun_name));
     };
+    context.refer(fun_name, Definition::Fun(signature.clone()), &signature.fun_typ());
 
     let block = typ_block(fun_context.clone(), fun.body())?;
+    let block = check_returns(&context, fun_name, fun.body().end().0, block);
 
     let locals = fun_context.locals().borrow().clone();
+    let aggregates = fun_context.aggregates().borrow().clone();
+    let constants = fun_context.constants().borrow().clone();
 
     Ok(
         Fun::new(
             signature,
             locals,
+            aggregates,
+            constants,
             block,
         )
     )
@@ -144,26 +168,44 @@ fn typ_fun<'a>(context: Rc<FileContext<'a>>, fun: &'a parser::Fun<'a>) -> TypRes
 fn typ_block<'a, T>(context: Rc<T>, block: &'a parser::Block<'a>) -> TypResult<'a, Block<'a>>
     where T: ParentContext<'a> + 'a {
     let mut vars = HashMap::new();
+    let mut constants = HashSet::new();
 
     for var in block.vars() {
         if let Some(_) = vars.insert(var.name().clone(), typ_typ(context.context(), var.typ())?) {
             return Err(TypError::DuplicateVarName(var.name().clone()));
         }
+        if *var.constant() {
+            constants.insert(*var.name());
+        }
     }
 
     let new_context = Rc::new(BlockContext::new(
         context.context().clone(),
         context.clone(),
         vars,
+        constants,
     ));
 
-    let mut stmts = vec![];
+    for var in block.vars() {
+        let ident = BlockIdent::Local(*new_context.index(), var.name());
+        let typ = new_context.vars()[var.name()].clone();
+        context.context().refer(var.name(), Definition::Local(context.fun_name(), ident), &typ);
+    }
 
-    for stmt in block.stmts() {
-        stmts.push(typ_stmt(new_context.clone(), stmt)?)
+    let mut stmts = vec![];
+    let mut anchors = vec![];
+
+    for (stmt, anchor) in block.stmts().iter().zip(block.anchors()) {
+        match stmt {
+            parser::Stmt::SDecl(name, None) => new_context.open(name),
+            stmt => {
+                stmts.push(typ_stmt(new_context.clone(), stmt).map_err(|error| error.at(Some(Span::new(anchor.0, anchor.0))))?);
+                anchors.push(anchor.0);
+            }
+        }
     }
 
-    Ok(Block::new(stmts))
+    Ok(Block::new(stmts, anchors))
 }
 
 fn typ_stmt<'a>(context: Rc<BlockContext<'a>>, stmt: &'a parser::Stmt<'a>) -> TypResult<'a, Stmt<'a>> {
@@ -189,8 +231,29 @@ fn typ_stmt<'a>(context: Rc<BlockContext<'a>>, stmt: &'a parser::Stmt<'a>) -> Ty
             let block = typ_block(context, block)?;
             Ok(Stmt::SBlock(block))
         }
-        parser::Stmt::SReturn(expr) => {
-            let expr = typ_expr(context.clone(), expr)?;
+        parser::Stmt::SDecl(name, None) => {
+            context.open(name);
+            Ok(Stmt::SSkip)
+        }
+        parser::Stmt::SDecl(name, Some(init)) => {
+            context.open(name);
+            let expr = typ_expr(context.clone(), init)?;
+            let typ = context.typ(name).ok_or(TypError::VariableDoesNotExist(name))?;
+
+            if !typed_as(&typ, expr.typ()) {
+                return Err(TypError::WrongExpressionTyp(IncompatibleTyp::new(typ, expr.typ().clone())).at(span(init)));
+            }
+
+            let ident = context.get_block_ident(name);
+
+            if let (true, ExprNode::EConst(value)) = (context.is_const(name), expr.node()) {
+                context.declare_constant(ident.clone(), *value);
+            }
+
+            Ok(Stmt::SExpr(Expr::new(ExprNode::EAssignLocal(ident, Box::new(expr)), typ)))
+        }
+        parser::Stmt::SReturn(returned) => {
+            let expr = typ_expr(context.clone(), returned)?;
             if typed_as(expr.typ(), &context.fun_typ()) {
                 Ok(Stmt::SReturn(expr))
             } else {
@@ -200,14 +263,37 @@ fn typ_stmt<'a>(context: Rc<BlockContext<'a>>, stmt: &'a parser::Stmt<'a>) -> Ty
                             context.fun_typ(),
                             expr.typ().clone(),
                         )
-                    )
+                    ).at(span(returned))
                 )
             }
         }
     }
 }
 
+/// Types `expr`, locating the errors without identifier found in it.
 fn typ_expr<'a>(context: Rc<BlockContext<'a>>, expr: &parser::Expr<'a>) -> TypResult<'a, Expr<'a>> {
+    typ_expr_node(context, expr).map_err(|error| error.at(span(expr)))
+}
+
+/// From the first identifier of `expr` to its last one, if it has any.
+fn span<'a>(expr: &parser::Expr<'a>) -> Option<Span<'a>> {
+    let join = |first: Option<Span<'a>>, last: Option<Span<'a>>| match (first, last) {
+        (Some(first), Some(last)) => Some(Span::new(first.first, last.last)),
+        (first, last) => first.or(last),
+    };
+    let ident = |ident: Ident<'a>| Some(Span::new(ident, ident));
+
+    match expr {
+        parser::Expr::EConst(_) => None,
+        parser::Expr::EString(x) | parser::Expr::EVar(x) | parser::Expr::ESizeof(x) => ident(x),
+        parser::Expr::EArrow(expr, field) | parser::Expr::EDot(expr, field) => join(span(expr), ident(field)),
+        parser::Expr::EAddrOf(expr) | parser::Expr::EUnop(_, expr) => span(expr),
+        parser::Expr::EAssign(expr_1, expr_2) | parser::Expr::EBinop(_, expr_1, expr_2) => join(span(expr_1), span(expr_2)),
+        parser::Expr::ECall(callee, args) => args.iter().map(span).fold(span(callee), join),
+    }
+}
+
+fn typ_expr_node<'a>(context: Rc<BlockContext<'a>>, expr: &parser::Expr<'a>) -> TypResult<'a, Expr<'a>> {
     match expr {
         parser::Expr::EConst(x) => Ok(
             Expr::new(
@@ -215,22 +301,32 @@ fn typ_expr<'a>(context: Rc<BlockContext<'a>>, expr: &parser::Expr<'a>) -> TypRe
                 if *x == 0 { Typ::TTypeNull } else { Typ::TInt },
             )
         ),
+        parser::Expr::EString(x) => {
+            let mut strings = context.context().strings().borrow_mut();
+            strings.push(x);
+            Ok(Expr::new(
+                ExprNode::EString(strings.len() - 1),
+                Typ::TString,
+            ))
+        }
         parser::Expr::EVar(var_name) => {
             match context.typ(var_name) {
-                None => Err(TypError::VariableDoesNotExist),
+                None => typ_fun_address(context, var_name).ok_or(TypError::VariableDoesNotExist(var_name)),
+                Some(Typ::TStructValue(_)) => Err(TypError::AggregateUsedAsValue(var_name)),
                 Some(typ) => Ok(Expr::new(
-                    ExprNode::EAccessLocal(context.get_block_ident(var_name)),
+                    ExprNode::EAccessLocal(typ_local(&context, var_name, &typ)),
                     typ.clone(),
                 ))
             }
         }
         parser::Expr::EArrow(x, field_name) => {
-            let expr = typ_expr(context, x.as_ref())?;
+            let expr = typ_expr(context.clone(), x.as_ref())?;
             let typ = expr.typ().clone();
             match typ {
                 Typ::TStruct(structure) => {
                     match structure.fields().borrow().get(field_name) {
                         Some(field) => {
+                            typ_field(&context, field_name, field);
                             Ok(
                                 Expr::new(
                                     ExprNode::EAccessField(Box::new(expr), field.clone()),
@@ -249,19 +345,49 @@ fn typ_expr<'a>(context: Rc<BlockContext<'a>>, expr: &parser::Expr<'a>) -> TypRe
                 _ => Err(TypError::DereferenceNonStructPointer(field_name))
             }
         }
+        parser::Expr::EDot(x, field_name) => {
+            let expr = typ_aggregate_address(context.clone(), x.as_ref())
+                .ok_or(TypError::DotOnNonAggregate(field_name))?;
+            match expr.typ().clone() {
+                Typ::TStruct(structure) => {
+                    match structure.fields().borrow().get(field_name) {
+                        Some(field) => {
+                            typ_field(&context, field_name, field);
+                            Ok(Expr::new(
+                                ExprNode::EAccessField(Box::new(expr), field.clone()),
+                                field.typ().clone(),
+                            ))
+                        }
+                        None => Err(TypError::FieldDoesntExist(structure.clone(), field_name))
+                    }
+                }
+                _ => unreachable!()
+            }
+        }
+        parser::Expr::EAddrOf(x) => {
+            match x.as_ref() {
+                parser::Expr::EVar(var_name) if context.typ(var_name).is_none() => {
+                    typ_fun_address(context, var_name).ok_or(TypError::FunctionDoesntExist(var_name))
+                }
+                x => typ_aggregate_address(context, x).ok_or(TypError::AddressOfNonAggregate)
+            }
+        }
         parser::Expr::EAssign(expr_1, expr_2) => {
             let expr_2 = typ_expr(context.clone(), expr_2)?;
             let typ_2 = expr_2.typ();
             match expr_1.as_ref() {
+                parser::Expr::EVar(var_name) if context.is_const(var_name) => {
+                    Err(TypError::AssigningToConstVariable(var_name))
+                }
                 parser::Expr::EVar(var_name) => {
                     match context.typ(var_name) {
                         Some(typ_1) if typed_as(&typ_1, typ_2) => {
                             Ok(Expr::new(
-                                ExprNode::EAssignLocal(context.get_block_ident(var_name), Box::new(expr_2)),
+                                ExprNode::EAssignLocal(typ_local(&context, var_name, &typ_1), Box::new(expr_2)),
                                 typ_1,
                             ))
                         }
-                        _ => Err(TypError::VariableDoesNotExist)
+                        _ => Err(TypError::VariableDoesNotExist(var_name))
                     }
                 }
                 parser::Expr::EArrow(expr, field_name) => {
@@ -270,7 +396,11 @@ fn typ_expr<'a>(context: Rc<BlockContext<'a>>, expr: &parser::Expr<'a>) -> TypRe
                     match typ {
                         Typ::TStruct(structure) => {
                             match structure.fields().borrow().get(field_name) {
+                                Some(field) if *field.constant() => {
+                                    Err(TypError::AssigningToConstField(field_name))
+                                }
                                 Some(field) if typed_as(field.typ(), typ_2) => {
+                                    typ_field(&context, field_name, field);
                                     Ok(Expr::new(
                                         ExprNode::EAssignField(Box::new(expr), field.clone(), Box::new(expr_2)),
                                         field.typ().clone(),
@@ -292,6 +422,33 @@ fn typ_expr<'a>(context: Rc<BlockContext<'a>>, expr: &parser::Expr<'a>) -> TypRe
                         }
                     }
                 }
+                parser::Expr::EDot(expr, field_name) => {
+                    let expr = typ_aggregate_address(context.clone(), expr)
+                        .ok_or(TypError::DotOnNonAggregate(field_name))?;
+                    match expr.typ().clone() {
+                        Typ::TStruct(structure) => {
+                            match structure.fields().borrow().get(field_name) {
+                                Some(field) if *field.constant() => {
+                                    Err(TypError::AssigningToConstField(field_name))
+                                }
+                                Some(field) if typed_as(field.typ(), typ_2) => {
+                                    typ_field(&context, field_name, field);
+                                    Ok(Expr::new(
+                                        ExprNode::EAssignField(Box::new(expr), field.clone(), Box::new(expr_2)),
+                                        field.typ().clone(),
+                                    ))
+                                }
+                                Some(field) => {
+                                    Err(TypError::WrongExpressionTyp(
+                                        IncompatibleTyp::new(field.typ().clone(), typ_2.clone())
+                                    ))
+                                }
+                                None => Err(TypError::FieldDoesntExist(structure.clone(), field_name))
+                            }
+                        }
+                        _ => Err(TypError::DotOnNonAggregate(field_name))
+                    }
+                }
                 _ => Err(TypError::AssigningToNonAssignableExpression)
             }
         }
@@ -385,13 +542,14 @@ fn typ_expr<'a>(context: Rc<BlockContext<'a>>, expr: &parser::Expr<'a>) -> TypRe
                 }
             }
         }
-        parser::Expr::ECall(ident, args) => {
-            match ident.as_ref() {
-                parser::Expr::EVar(ident) => {
+        parser::Expr::ECall(callee, args) => {
+            match callee.as_ref() {
+                parser::Expr::EVar(ident) if context.typ(ident).is_none() => {
                     match context.context().funs().borrow().get(ident) {
                         Some(fun) => {
+                            context.context().refer(ident, Definition::Fun(fun.clone()), &fun.fun_typ());
                             let mut typed_args = vec![];
-                            for itered in args.into_iter().zip_longest(fun.args()) {
+                            for (index, itered) in args.into_iter().zip_longest(fun.args()).enumerate() {
                                 match itered {
                                     EitherOrBoth::Both(arg_expr, arg_formal) => {
                                         let expr = typ_expr(context.clone(), arg_expr)?;
@@ -409,6 +567,13 @@ fn typ_expr<'a>(context: Rc<BlockContext<'a>>, expr: &parser::Expr<'a>) -> TypRe
                                             );
                                         }
                                     }
+                                    EitherOrBoth::Left(arg_expr) if *fun.variadic() => {
+                                        let expr = typ_expr(context.clone(), arg_expr)?;
+                                        typed_args.push(ArgExpr::new(
+                                            Formal::new(BlockIdent::Arg(index, VARIADIC_ARG), expr.typ().clone()),
+                                            expr,
+                                        ));
+                                    }
                                     EitherOrBoth::Left(_) => {
                                         return Err(
                                             TypError::TooManyArguments
@@ -432,14 +597,40 @@ fn typ_expr<'a>(context: Rc<BlockContext<'a>>, expr: &parser::Expr<'a>) -> TypRe
                         }
                     }
                 }
-                parser::Expr::EConst(_)
-                | parser::Expr::EArrow(_, _)
-                | parser::Expr::EAssign(_, _)
-                | parser::Expr::EUnop(_, _)
-                | parser::Expr::EBinop(_, _, _)
-                | parser::Expr::ECall(_, _)
-                | parser::Expr::ESizeof(_) => {
-                    Err(TypError::CallingANonFunctionExpression)
+                callee => {
+                    let callee = typ_expr(context.clone(), callee)?;
+                    match callee.typ().clone() {
+                        Typ::TFun(fun_typ) => {
+                            let mut typed_args = vec![];
+                            for itered in args.iter().zip_longest(fun_typ.args()) {
+                                match itered {
+                                    EitherOrBoth::Both(arg_expr, arg_typ) => {
+                                        let expr = typ_expr(context.clone(), arg_expr)?;
+                                        if typed_as(arg_typ, expr.typ()) {
+                                            typed_args.push(expr);
+                                        } else {
+                                            return Err(
+                                                TypError::WrongExpressionTyp(IncompatibleTyp::new(
+                                                    arg_typ.clone(),
+                                                    expr.typ().clone(),
+                                                ))
+                                            );
+                                        }
+                                    }
+                                    EitherOrBoth::Left(arg_expr) if *fun_typ.variadic() => {
+                                        typed_args.push(typ_expr(context.clone(), arg_expr)?);
+                                    }
+                                    EitherOrBoth::Left(_) => return Err(TypError::TooManyArguments),
+                                    EitherOrBoth::Right(_) => return Err(TypError::TooFewArguments)
+                                }
+                            }
+                            Ok(Expr::new(
+                                ExprNode::ECallStar(Box::new(callee), typed_args),
+                                fun_typ.typ().clone(),
+                            ))
+                        }
+                        _ => Err(TypError::CallingANonFunctionExpression)
+                    }
                 }
             }
         }
@@ -448,26 +639,90 @@ fn typ_expr<'a>(context: Rc<BlockContext<'a>>, expr: &parser::Expr<'a>) -> TypRe
                 None => {
                     Err(TypError::StructDoesNotExist(struct_name))
                 }
-                Some(structure) => Ok(
-                    Expr::new(
+                Some(structure) => {
+                    let typ = Typ::TStructValue(structure.clone());
+                    context.context().refer(struct_name, Definition::Struct(structure.clone()), &typ);
+                    Ok(Expr::new(
                         ExprNode::EConst(structure.c_size()),
                         Typ::TInt,
-                    )
-                )
+                    ))
+                }
             }
         }
     }
 }
 
+/// Types the address of a stack-allocated aggregate, i.e. `s` in `s.f` or `&s`.
+/// The address is read from the local itself, whose register holds its frame address.
+fn typ_aggregate_address<'a>(context: Rc<BlockContext<'a>>, expr: &parser::Expr<'a>) -> Option<Expr<'a>> {
+    match expr {
+        parser::Expr::EVar(var_name) => {
+            match context.typ(var_name) {
+                Some(typ @ Typ::TStructValue(_)) => {
+                    let ident = typ_local(&context, var_name, &typ);
+                    let Typ::TStructValue(structure) = typ else { unreachable!() };
+                    Some(Expr::new(
+                        ExprNode::EAccessLocal(ident),
+                        Typ::TStruct(structure),
+                    ))
+                }
+                _ => None
+            }
+        }
+        _ => None
+    }
+}
+
+/// Resolves a use of the variable `name`, of type `typ`.
+fn typ_local<'a>(context: &BlockContext<'a>, name: Ident<'a>, typ: &Typ<'a>) -> BlockIdent<'a> {
+    let ident = context.get_block_ident(name);
+    context.context().refer(name, Definition::Local(context.fun_name(), ident.clone()), typ);
+    ident
+}
+
+/// Records a use of `field`, named `name` in the source.
+fn typ_field<'a>(context: &BlockContext<'a>, name: Ident<'a>, field: &Rc<Field<'a>>) {
+    context.context().refer(name, Definition::Field(field.clone()), field.typ());
+}
+
+fn typ_fun_address<'a>(context: Rc<BlockContext<'a>>, name: Ident<'a>) -> Option<Expr<'a>> {
+    context.context().funs().borrow().get(name).map(|signature| {
+        context.context().refer(name, Definition::Fun(signature.clone()), &signature.fun_typ());
+        Expr::new(
+            ExprNode::EFunAddr(signature.clone()),
+            signature.fun_typ(),
+        )
+    })
+}
+
 fn typ_typ<'a>(context: Rc<FileContext<'a>>, typ: &parser::Typ<'a>) -> TypResult<'a, Typ<'a>> {
     match typ {
         parser::Typ::TInt => Ok(Typ::TInt),
         parser::Typ::TStruct(name) => {
             match context.structs().borrow().get(name) {
                 None => Err(TypError::StructDoesNotExist(name)),
-                Some(structure) => Ok(Typ::TStruct(structure.clone()))
+                Some(structure) => {
+                    context.refer(name, Definition::Struct(structure.clone()), &Typ::TStructValue(structure.clone()));
+                    Ok(Typ::TStruct(structure.clone()))
+                }
             }
         }
+        parser::Typ::TStructValue(name) => {
+            match context.structs().borrow().get(name) {
+                None => Err(TypError::StructDoesNotExist(name)),
+                Some(structure) => {
+                    context.refer(name, Definition::Struct(structure.clone()), &Typ::TStructValue(structure.clone()));
+                    Ok(Typ::TStructValue(structure.clone()))
+                }
+            }
+        }
+        parser::Typ::TFun(typ, args, variadic) => {
+            let typ = typ_typ(context.clone(), typ)?;
+            let args = args.iter()
+                .map(|arg| typ_typ(context.clone(), arg))
+                .collect::<TypResult<Vec<Typ>>>()?;
+            Ok(Typ::TFun(Rc::new(FunTyp::new(typ, args, *variadic))))
+        }
     }
 }
 
@@ -480,6 +735,8 @@ fn typed_as<'a>(first: &Typ<'a>, second: &Typ<'a>) -> bool {
         (Typ::TStruct(_), Typ::TTypeNull) => true,
         (Typ::TVoidStar, Typ::TStruct(_)) => true,
         (Typ::TStruct(_), Typ::TVoidStar) => true,
+        (Typ::TTypeNull, Typ::TFun(_)) => true,
+        (Typ::TFun(_), Typ::TTypeNull) => true,
         (_, _) => false
     }
 }
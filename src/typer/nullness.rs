@@ -0,0 +1,235 @@
+use std::collections::{HashMap, HashSet};
+use crate::typer::structure::{Binop, Block, BlockIdent, Expr, ExprNode, Field, Fun, Stmt, Typ, Unop};
+use crate::warning::{Warning, WarningCode};
+
+/// Steps of a path on which a pointer is null, e.g. `p = 0`, then `p == 0` is true.
+type Path = Vec<String>;
+
+/// Struct pointers which may be null, with a path on which they are. `None` if unreachable.
+type State<'a> = Option<HashMap<BlockIdent<'a>, Path>>;
+
+/// Flow-sensitive nullness analysis : warns when a struct pointer is dereferenced
+/// while it may be `0` on some path, because it was assigned `0` or compared equal to `0`.
+pub fn lint_null_dereferences<'a>(fun: &Fun<'a>, warnings: &mut Vec<Warning<'a>>) {
+    let mut analysis = Analysis { fun, warned: HashSet::new(), warnings };
+    analysis.block(fun.block(), Some(HashMap::new()));
+}
+
+struct Analysis<'a, 'b> {
+    fun: &'b Fun<'a>,
+    warned: HashSet<*const u8>,
+    warnings: &'b mut Vec<Warning<'a>>,
+}
+
+impl<'a> Analysis<'a, '_> {
+    fn block(&mut self, block: &Block<'a>, state: State<'a>) -> State<'a> {
+        block.stmts().iter().fold(state, |state, stmt| self.stmt(stmt, state))
+    }
+
+    fn stmt(&mut self, stmt: &Stmt<'a>, state: State<'a>) -> State<'a> {
+        match stmt {
+            Stmt::SSkip => state,
+            Stmt::SExpr(expr) => self.expr(expr, state),
+            Stmt::SIf(condition, stmt_if, stmt_else) => {
+                let (state_true, state_false) = self.condition(condition, state);
+                let state_if = self.stmt(stmt_if, state_true);
+                let state_else = self.stmt(stmt_else, state_false);
+                join(state_if, state_else)
+            }
+            Stmt::SWhile(condition, body) => {
+                let mut entry = state;
+                loop {
+                    let (state_true, state_false) = self.condition(condition, entry.clone());
+                    let next = join(entry.clone(), self.stmt(body, state_true));
+                    if next == entry {
+                        return state_false;
+                    }
+                    entry = next;
+                }
+            }
+            Stmt::SBlock(block) => self.block(block, state),
+            Stmt::SReturn(expr) => {
+                self.expr(expr, state);
+                None
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr<'a>, state: State<'a>) -> State<'a> {
+        match expr.node() {
+            ExprNode::EConst(_)
+            | ExprNode::EString(_)
+            | ExprNode::EAccessLocal(_)
+            | ExprNode::EFunAddr(_) => state,
+            ExprNode::EAccessField(pointer, field) => {
+                let state = self.expr(pointer, state);
+                self.dereference(pointer, field, state)
+            }
+            ExprNode::EAssignLocal(ident, value) => {
+                let mut state = self.expr(value, state);
+                if let Some(nulls) = state.as_mut() {
+                    match null_path(ident, value, nulls) {
+                        Some(path) => nulls.insert(ident.clone(), path),
+                        None => nulls.remove(ident),
+                    };
+                }
+                state
+            }
+            ExprNode::EAssignField(pointer, field, value) => {
+                let state = self.expr(pointer, state);
+                let state = self.expr(value, state);
+                self.dereference(pointer, field, state)
+            }
+            ExprNode::EUnop(_, expr) => self.expr(expr, state),
+            ExprNode::EBinop(Binop::BAnd | Binop::BOr, _, _) => {
+                let (state_true, state_false) = self.condition(expr, state);
+                join(state_true, state_false)
+            }
+            ExprNode::EBinop(_, expr_1, expr_2) => {
+                let state = self.expr(expr_1, state);
+                self.expr(expr_2, state)
+            }
+            ExprNode::ECall(_, args) => args
+                .iter()
+                .fold(state, |state, arg| self.expr(arg.expr(), state)),
+            ExprNode::ECallStar(callee, args) => {
+                let state = self.expr(callee, state);
+                args.iter().fold(state, |state, arg| self.expr(arg, state))
+            }
+        }
+    }
+
+    /// States when `condition` is true and when it is false.
+    fn condition(&mut self, condition: &Expr<'a>, state: State<'a>) -> (State<'a>, State<'a>) {
+        match condition.node() {
+            ExprNode::EBinop(Binop::BAnd, expr_1, expr_2) => {
+                let (state_true, state_false_1) = self.condition(expr_1, state);
+                let (state_true, state_false_2) = self.condition(expr_2, state_true);
+                (state_true, join(state_false_1, state_false_2))
+            }
+            ExprNode::EBinop(Binop::BOr, expr_1, expr_2) => {
+                let (state_true_1, state_false) = self.condition(expr_1, state);
+                let (state_true_2, state_false) = self.condition(expr_2, state_false);
+                (join(state_true_1, state_true_2), state_false)
+            }
+            ExprNode::EUnop(Unop::UNot, expr) => {
+                let (state_true, state_false) = self.condition(expr, state);
+                (state_false, state_true)
+            }
+            ExprNode::EBinop(op @ (Binop::BEq | Binop::BNeq), expr_1, expr_2) => {
+                let state = self.expr(expr_1, state);
+                let state = self.expr(expr_2, state);
+                let pointer = match (pointer(expr_1), pointer(expr_2)) {
+                    (Some(ident), _) if is_zero(expr_2) => ident,
+                    (_, Some(ident)) if is_zero(expr_1) => ident,
+                    _ => return (state.clone(), state),
+                };
+                let (operator, null_when) = match op {
+                    Binop::BEq => ("==", true),
+                    _ => ("!=", false),
+                };
+                let step = format!("`{} {} 0` is {}", pointer.name(), operator, null_when);
+                let (state_null, state_not_null) = refine(pointer, step, state);
+                if null_when { (state_null, state_not_null) } else { (state_not_null, state_null) }
+            }
+            _ => {
+                let state = self.expr(condition, state);
+                match pointer(condition) {
+                    Some(ident) => {
+                        let step = format!("`{}` is false", ident.name());
+                        let (state_null, state_not_null) = refine(ident, step, state);
+                        (state_not_null, state_null)
+                    }
+                    None => (state.clone(), state),
+                }
+            }
+        }
+    }
+
+    fn dereference(&mut self, pointer: &Expr<'a>, field: &Field<'a>, state: State<'a>) -> State<'a> {
+        let (ident, mut nulls) = match (pointer.node(), state) {
+            (ExprNode::EAccessLocal(ident), Some(nulls)) => (ident, nulls),
+            (_, state) => return state,
+        };
+
+        // Past the dereference, the pointer isn't null anymore
+        if let Some(path) = nulls.remove(ident) {
+            if self.warned.insert(ident.name().as_ptr()) {
+                let fun = *self.fun.signature().name();
+                self.warnings.push(Warning::new(
+                    WarningCode::NullDereference,
+                    fun,
+                    Some(ident.name()),
+                    format!(
+                        "`{}` may be null in `{}->{}` in `{}`, after {}",
+                        ident.name(),
+                        ident.name(),
+                        field.name(),
+                        fun,
+                        path.join(", then "),
+                    ),
+                ));
+            }
+        }
+
+        Some(nulls)
+    }
+}
+
+/// Path on which `value`, assigned to `ident`, is null, if it may be.
+fn null_path<'a>(ident: &BlockIdent<'a>, value: &Expr<'a>, nulls: &HashMap<BlockIdent<'a>, Path>) -> Option<Path> {
+    if !matches!(value.typ(), Typ::TStruct(_) | Typ::TTypeNull) {
+        return None;
+    }
+
+    match value.node() {
+        ExprNode::EConst(0) => Some(vec![format!("`{} = 0`", ident.name())]),
+        ExprNode::EAccessLocal(other) => nulls.get(other).map(|path| {
+            let mut path = path.clone();
+            path.push(format!("`{} = {}`", ident.name(), other.name()));
+            path
+        }),
+        _ => None,
+    }
+}
+
+/// States where `ident` is null, reached by `step`, and where it isn't.
+fn refine<'a>(ident: &BlockIdent<'a>, step: String, state: State<'a>) -> (State<'a>, State<'a>) {
+    let Some(nulls) = state else {
+        return (None, None);
+    };
+
+    let mut state_null = nulls.clone();
+    let mut path = nulls.get(ident).cloned().unwrap_or_default();
+    path.push(step);
+    state_null.insert(ident.clone(), path);
+
+    let mut state_not_null = nulls;
+    state_not_null.remove(ident);
+
+    (Some(state_null), Some(state_not_null))
+}
+
+/// Joins two states, keeping the paths of the first one.
+fn join<'a>(state_1: State<'a>, state_2: State<'a>) -> State<'a> {
+    match (state_1, state_2) {
+        (None, state) | (state, None) => state,
+        (Some(mut nulls_1), Some(nulls_2)) => {
+            for (ident, path) in nulls_2 {
+                nulls_1.entry(ident).or_insert(path);
+            }
+            Some(nulls_1)
+        }
+    }
+}
+
+fn pointer<'a, 'b>(expr: &'b Expr<'a>) -> Option<&'b BlockIdent<'a>> {
+    match (expr.node(), expr.typ()) {
+        (ExprNode::EAccessLocal(ident), Typ::TStruct(_)) => Some(ident),
+        _ => None,
+    }
+}
+
+fn is_zero(expr: &Expr) -> bool {
+    matches!(expr.node(), ExprNode::EConst(0))
+}
@@ -0,0 +1,77 @@
+use crate::common::{Ident, MAIN};
+use crate::typer::context::FileContext;
+use crate::typer::structure::{Block, Expr, ExprNode, Stmt, Typ};
+use crate::warning::{Warning, WarningCode};
+
+/// Checks that every path through the body of a function ends with a `return`.
+/// Reaching the end of `main` returns 0, as in C99. Warnings point to `end`, the closing brace of the body.
+pub fn check_returns<'a>(context: &FileContext<'a>, name: Ident<'a>, end: Ident<'a>, block: Block<'a>) -> Block<'a> {
+    match block_falls_through(&block) {
+        None => block,
+        Some(_) if name == MAIN => Block::new(
+            vec![
+                Stmt::SBlock(block),
+                Stmt::SReturn(Expr::new(ExprNode::EConst(0), Typ::TInt)),
+            ],
+            vec![end, end],
+        ),
+        Some(last) => {
+            context.warnings().borrow_mut().push(Warning::new(
+                WarningCode::MissingReturn,
+                name,
+                Some(end),
+                format!("control reaches end of non-void function `{}` after `{}`", name, last),
+            ));
+            block
+        }
+    }
+}
+
+/// Describes the last statement of a path through `block` which doesn't return, if any.
+fn block_falls_through(block: &Block) -> Option<String> {
+    if block.stmts().iter().any(|stmt| falls_through(stmt).is_none()) {
+        None
+    } else {
+        block.stmts().last().map_or(Some(String::from("{}")), falls_through)
+    }
+}
+
+pub(super) fn falls_through(stmt: &Stmt) -> Option<String> {
+    match stmt {
+        Stmt::SReturn(_) => None,
+        Stmt::SSkip | Stmt::SExpr(_) => Some(describe(stmt)),
+        Stmt::SIf(_, stmt_if, stmt_else) => {
+            falls_through(stmt_if).or_else(|| match stmt_else.as_ref() {
+                Stmt::SSkip => Some(describe(stmt)),
+                stmt_else => falls_through(stmt_else),
+            })
+        }
+        // There is no `break`, so only a loop on a constant condition never exits
+        Stmt::SWhile(condition, _) => match condition.node() {
+            ExprNode::EConst(x) if *x != 0 => None,
+            _ => Some(describe(stmt)),
+        },
+        Stmt::SBlock(block) => block_falls_through(block),
+    }
+}
+
+pub(super) fn describe(stmt: &Stmt) -> String {
+    match stmt {
+        Stmt::SSkip => String::from(";"),
+        Stmt::SExpr(expr) => format!("{};", describe_expr(expr)),
+        Stmt::SIf(_, _, _) => String::from("if (...)"),
+        Stmt::SWhile(_, _) => String::from("while (...)"),
+        Stmt::SBlock(_) => String::from("{...}"),
+        Stmt::SReturn(_) => String::from("return ...;"),
+    }
+}
+
+fn describe_expr(expr: &Expr) -> String {
+    match expr.node() {
+        ExprNode::EAssignLocal(ident, _) => format!("{} = ...", ident.name()),
+        ExprNode::EAssignField(_, field, _) => format!("...->{} = ...", field.name()),
+        ExprNode::ECall(signature, _) => format!("{}(...)", signature.name()),
+        ExprNode::ECallStar(_, _) => String::from("(*...)(...)"),
+        _ => String::from("..."),
+    }
+}
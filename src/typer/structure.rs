@@ -1,17 +1,26 @@
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 use std::rc::Rc;
 use derive_new::new;
 use derive_getters::Getters;
-use crate::common::{Value, Ident, MALLOC, PUTCHAR, MAIN, StackOffset};
+use crate::common::{Value, Ident, MALLOC, PUTCHAR, MAIN, PRINTF, StackOffset, GETCHAR};
+use crate::parser::printer::{Layout, Shape};
+use crate::parser::structure::{prefix, write_indent, ASSIGN_PRECEDENCE, POSTFIX_PRECEDENCE, PRIMARY_PRECEDENCE, UNARY_PRECEDENCE};
+use crate::warning::Warning;
 
 pub type StructSize = Value;
 pub type Unop = crate::parser::structure::Unop;
 pub type Binop = crate::parser::structure::Binop;
 
+pub const VARIADIC_ARG: Ident = "...";
+
 #[derive(new, Debug, Getters)]
 pub struct File<'a> {
     funs: HashMap<Ident<'a>, Fun<'a>>,
+    strings: Vec<Ident<'a>>,
+    warnings: Vec<Warning<'a>>,
+    references: Vec<Reference<'a>>,
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
@@ -25,12 +34,15 @@ pub struct Signature<'a> {
     name: Ident<'a>,
     typ: Typ<'a>,
     args: Vec<Formal<'a>>,
+    variadic: bool,
 }
 
 #[derive(new, Debug, Getters)]
 pub struct Fun<'a> {
     signature: Rc<Signature<'a>>,
-    locals: HashSet<BlockIdent<'a>>,
+    locals: HashMap<BlockIdent<'a>, Typ<'a>>,
+    aggregates: HashMap<BlockIdent<'a>, Rc<Struct<'a>>>,
+    constants: HashMap<BlockIdent<'a>, Value>,
     block: Block<'a>,
 }
 
@@ -45,6 +57,7 @@ pub struct Field<'a> {
     name: Ident<'a>,
     index: u8,
     typ: Typ<'a>,
+    constant: bool,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -52,7 +65,17 @@ pub enum Typ<'a> {
     TInt,
     TVoidStar,
     TTypeNull,
+    TString,
     TStruct(Rc<Struct<'a>>),
+    TStructValue(Rc<Struct<'a>>),
+    TFun(Rc<FunTyp<'a>>),
+}
+
+#[derive(new, Debug, Getters, PartialEq)]
+pub struct FunTyp<'a> {
+    typ: Typ<'a>,
+    args: Vec<Typ<'a>>,
+    variadic: bool,
 }
 
 #[derive(new, Debug, Getters)]
@@ -61,9 +84,29 @@ pub struct Struct<'a> {
     fields: Rc<RefCell<HashMap<Ident<'a>, Rc<Field<'a>>>>>, // TODO remove refcell
 }
 
+/// What an identifier of the source names.
+#[derive(Debug, Clone)]
+pub enum Definition<'a> {
+    /// A variable or an argument of the function named by the first component.
+    Local(Ident<'a>, BlockIdent<'a>),
+    Field(Rc<Field<'a>>),
+    Fun(Rc<Signature<'a>>),
+    Struct(Rc<Struct<'a>>),
+}
+
+/// An identifier of the source, at a declaration or a use, with what it names and its type.
+#[derive(new, Debug, Getters)]
+pub struct Reference<'a> {
+    ident: Ident<'a>,
+    definition: Definition<'a>,
+    typ: Typ<'a>,
+}
+
 #[derive(new, Debug, Getters)]
 pub struct Block<'a> {
     stmts: Vec<Stmt<'a>>,
+    /// First token of each statement in the source
+    anchors: Vec<Ident<'a>>,
 }
 
 #[derive(Debug)]
@@ -91,6 +134,7 @@ pub struct ArgExpr<'a> {
 #[derive(Debug)]
 pub enum ExprNode<'a> {
     EConst(Value),
+    EString(usize),
     EAccessLocal(BlockIdent<'a>),
     EAccessField(Box<Expr<'a>>, Rc<Field<'a>>),
     EAssignLocal(BlockIdent<'a>, Box<Expr<'a>>),
@@ -98,6 +142,16 @@ pub enum ExprNode<'a> {
     EUnop(Unop, Box<Expr<'a>>),
     EBinop(Binop, Box<Expr<'a>>, Box<Expr<'a>>),
     ECall(Rc<Signature<'a>>, Vec<ArgExpr<'a>>),
+    EFunAddr(Rc<Signature<'a>>),
+    ECallStar(Box<Expr<'a>>, Vec<Expr<'a>>),
+}
+
+impl<'a> BlockIdent<'a> {
+    pub fn name(&self) -> Ident<'a> {
+        match self {
+            BlockIdent::Arg(_, name) | BlockIdent::Local(_, name) => name,
+        }
+    }
 }
 
 impl Struct<'_> {
@@ -118,6 +172,23 @@ impl<'a> File<'a> {
     pub fn into_funs(self) -> HashMap<Ident<'a>, Fun<'a>> {
         self.funs
     }
+
+    /// Identifier declaring what `reference` names, if it is in the source.
+    pub fn declaration(&self, reference: &Reference<'a>) -> Option<Ident<'a>> {
+        match &reference.definition {
+            Definition::Local(fun, BlockIdent::Arg(index, _)) => {
+                let arg = self.funs.get(fun)?.signature.args.get(*index)?;
+                Some(arg.name.name())
+            }
+            Definition::Local(fun, ident) => {
+                let (declared, _) = self.funs.get(fun)?.locals.get_key_value(ident)?;
+                Some(declared.name())
+            }
+            Definition::Field(field) => Some(field.name),
+            Definition::Fun(signature) => Some(signature.name),
+            Definition::Struct(structure) => Some(structure.name),
+        }
+    }
 }
 
 impl PartialEq<Self> for Struct<'_> {
@@ -126,16 +197,296 @@ impl PartialEq<Self> for Struct<'_> {
     }
 }
 
-impl Signature<'_> {
+impl<'x> Signature<'x> {
+    pub fn fun_typ(&self) -> Typ<'x> {
+        Typ::TFun(Rc::new(FunTyp::new(
+            self.typ.clone(),
+            self.args.iter().map(|arg| arg.typ().clone()).collect(),
+            self.variadic,
+        )))
+    }
+
     pub fn main<'a>() -> Signature<'a> {
-        Signature::new(MAIN, Typ::TInt, vec![])
+        Signature::new(MAIN, Typ::TInt, vec![], false)
     }
 
     pub fn putchar<'a>() -> Signature<'a> {
-        Signature::new(PUTCHAR, Typ::TInt, vec![Formal::new(BlockIdent::Arg(0, "c"), Typ::TInt)])
+        Signature::new(PUTCHAR, Typ::TInt, vec![Formal::new(BlockIdent::Arg(0, "c"), Typ::TInt)], false)
+    }
+
+    pub fn getchar<'a>() -> Signature<'a> {
+        Signature::new(GETCHAR, Typ::TInt, vec![], false)
     }
 
     pub fn malloc<'a>() -> Signature<'a> {
-        Signature::new(MALLOC, Typ::TVoidStar, vec![Formal::new(BlockIdent::Arg(0, "n"), Typ::TInt)])
+        Signature::new(MALLOC, Typ::TVoidStar, vec![Formal::new(BlockIdent::Arg(0, "n"), Typ::TInt)], false)
+    }
+
+    pub fn printf<'a>() -> Signature<'a> {
+        Signature::new(PRINTF, Typ::TInt, vec![Formal::new(BlockIdent::Arg(0, "format"), Typ::TString)], true)
+    }
+}
+
+/// Prints the typed functions as mini-C, with the scopes of locals shown as `x_2` if `scopes`.
+/// Otherwise, only the locals sharing their name with another local or an argument are renamed,
+/// since all locals are declared at the top of the function.
+struct Printer<'a, 'b> {
+    output: String,
+    strings: &'b [Ident<'a>],
+    fun: &'b Fun<'a>,
+    scopes: bool,
+    shadowed: HashSet<Ident<'a>>,
+}
+
+impl<'a, 'b> Printer<'a, 'b> {
+    fn new(strings: &'b [Ident<'a>], fun: &'b Fun<'a>, scopes: bool) -> Printer<'a, 'b> {
+        let mut names = fun.signature.args.iter().map(|arg| arg.name.name()).collect::<HashSet<_>>();
+        let shadowed = fun.locals
+            .keys()
+            .map(|ident| ident.name())
+            .filter(|name| !names.insert(name))
+            .collect();
+        Printer { output: String::new(), strings, fun, scopes, shadowed }
+    }
+
+    fn name(&self, ident: &BlockIdent<'a>) -> String {
+        match ident {
+            BlockIdent::Local(depth, name) if self.scopes || self.shadowed.contains(name) => {
+                format!("{}_{}", name, depth)
+            }
+            ident => ident.name().to_string(),
+        }
+    }
+
+    fn fun(mut self) -> String {
+        self.line(&format!("{} {{", self.fun.signature));
+
+        let mut locals = self.fun.locals
+            .iter()
+            .filter(|(ident, _)| !self.fun.constants.contains_key(ident))
+            .collect::<Vec<_>>();
+        locals.sort_by_key(|(ident, _)| (ident.name().as_ptr(), self.name(ident)));
+        for (ident, typ) in locals {
+            self.indent(1);
+            self.line(&format!("{};", declaration(typ, &self.name(ident))));
+        }
+
+        for stmt in &self.fun.block.stmts {
+            self.indent(1);
+            self.stmt(stmt, 1, false);
+        }
+        self.output.push('}');
+        self.output
+    }
+
+    /// First line of `stmt`, without the statements it nests.
+    fn head(&self, stmt: &Stmt<'a>) -> String {
+        match stmt {
+            Stmt::SSkip => String::from(";"),
+            Stmt::SExpr(Expr { node: ExprNode::EAssignLocal(ident, value), .. }) if self.fun.constants.contains_key(ident) => {
+                format!("const int {} = {};", self.name(ident), self.expr(value, ASSIGN_PRECEDENCE))
+            }
+            Stmt::SExpr(expr) => format!("{};", self.expr(expr, ASSIGN_PRECEDENCE)),
+            Stmt::SReturn(expr) => format!("return {};", self.expr(expr, ASSIGN_PRECEDENCE)),
+            Stmt::SBlock(_) => String::from("{"),
+            Stmt::SWhile(condition, _) => format!("while ({})", self.expr(condition, ASSIGN_PRECEDENCE)),
+            Stmt::SIf(condition, _, _) => format!("if ({})", self.expr(condition, ASSIGN_PRECEDENCE)),
+        }
+    }
+
+    /// Prints `expr`, in parentheses if it binds looser than `precedence`.
+    fn expr(&self, expr: &Expr<'a>, precedence: u8) -> String {
+        let (printed, expr_precedence) = match &expr.node {
+            ExprNode::EConst(x) if *x < 0 => (format!("({})", x), PRIMARY_PRECEDENCE),
+            ExprNode::EConst(x) => (x.to_string(), PRIMARY_PRECEDENCE),
+            ExprNode::EString(index) => (format!("\"{}\"", self.strings[*index]), PRIMARY_PRECEDENCE),
+            // Structures held by value are accessed through their address
+            ExprNode::EAccessLocal(ident) if self.fun.aggregates.contains_key(ident) => {
+                (format!("&{}", self.name(ident)), UNARY_PRECEDENCE)
+            }
+            ExprNode::EAccessLocal(ident) => (self.name(ident), PRIMARY_PRECEDENCE),
+            ExprNode::EAccessField(expr, field) => (self.field(expr, field), POSTFIX_PRECEDENCE),
+            ExprNode::EAssignLocal(ident, value) => (
+                format!("{} = {}", self.name(ident), self.expr(value, ASSIGN_PRECEDENCE)),
+                ASSIGN_PRECEDENCE,
+            ),
+            ExprNode::EAssignField(expr, field, value) => (
+                format!("{} = {}", self.field(expr, field), self.expr(value, ASSIGN_PRECEDENCE)),
+                ASSIGN_PRECEDENCE,
+            ),
+            ExprNode::EUnop(op, expr) => (prefix(&op.to_string(), self.expr(expr, UNARY_PRECEDENCE)), UNARY_PRECEDENCE),
+            ExprNode::EBinop(op, expr_1, expr_2) => (
+                format!("{} {} {}", self.expr(expr_1, op.precedence()), op, self.expr(expr_2, op.precedence() + 1)),
+                op.precedence(),
+            ),
+            ExprNode::ECall(signature, args) => (
+                format!("{}({})", signature.name, self.args(args.iter().map(|arg| &arg.expr))),
+                POSTFIX_PRECEDENCE,
+            ),
+            ExprNode::EFunAddr(signature) => (format!("&{}", signature.name), UNARY_PRECEDENCE),
+            ExprNode::ECallStar(callee, args) => (
+                format!("{}({})", self.expr(callee, POSTFIX_PRECEDENCE), self.args(args.iter())),
+                POSTFIX_PRECEDENCE,
+            ),
+        };
+
+        if expr_precedence < precedence {
+            format!("({})", printed)
+        } else {
+            printed
+        }
+    }
+
+    fn field(&self, expr: &Expr<'a>, field: &Field<'a>) -> String {
+        match &expr.node {
+            ExprNode::EAccessLocal(ident) if self.fun.aggregates.contains_key(ident) => {
+                format!("{}.{}", self.name(ident), field.name)
+            }
+            _ => format!("{}->{}", self.expr(expr, POSTFIX_PRECEDENCE), field.name),
+        }
+    }
+
+    fn args<'c>(&self, args: impl Iterator<Item=&'c Expr<'a>>) -> String where 'a: 'c {
+        args.map(|arg| self.expr(arg, ASSIGN_PRECEDENCE)).collect::<Vec<_>>().join(", ")
     }
 }
+
+impl<'a> Layout for Printer<'a, '_> {
+    type Block = Block<'a>;
+    type Stmt = Stmt<'a>;
+
+    fn output(&mut self) -> &mut String {
+        &mut self.output
+    }
+
+    fn shape<'s>(&self, stmt: &'s Stmt<'a>) -> Shape<'s, Block<'a>, Stmt<'a>> {
+        match stmt {
+            Stmt::SSkip => Shape::Skip,
+            Stmt::SExpr(_) | Stmt::SReturn(_) => Shape::Line(self.head(stmt)),
+            Stmt::SBlock(block) => Shape::Block(block),
+            Stmt::SWhile(_, body) => Shape::While(self.head(stmt), body),
+            Stmt::SIf(_, stmt_if, stmt_else) => Shape::If(self.head(stmt), stmt_if, stmt_else),
+        }
+    }
+
+    fn block(&mut self, block: &Block<'a>, indent: usize) {
+        self.line("{");
+        for stmt in &block.stmts {
+            self.indent(indent + 1);
+            self.stmt(stmt, indent + 1, false);
+        }
+        self.indent(indent);
+        self.output.push('}');
+    }
+}
+
+/// First line of the statement of the function, as printed with the program.
+pub fn stmt_head<'a>(strings: &[Ident<'a>], fun: &Fun<'a>, stmt: &Stmt<'a>) -> String {
+    Printer::new(strings, fun, false).head(stmt)
+}
+
+/// Declaration of `name` with type `typ`, as in `struct S *p` or `int (*f)(int)`.
+pub fn declaration(typ: &Typ, name: &str) -> String {
+    let separated = |typ: &str| if name.is_empty() {
+        typ.to_string()
+    } else {
+        format!("{} {}", typ, name)
+    };
+
+    match typ {
+        Typ::TInt | Typ::TTypeNull => separated("int"),
+        Typ::TVoidStar => format!("void *{}", name),
+        Typ::TString => format!("char *{}", name),
+        Typ::TStruct(s) => format!("struct {} *{}", s.name, name),
+        Typ::TStructValue(s) => separated(&format!("struct {}", s.name)),
+        Typ::TFun(fun) => {
+            let args = fun.args
+                .iter()
+                .map(|arg| declaration(arg, ""))
+                .chain(fun.variadic.then(|| String::from("...")))
+                .collect::<Vec<_>>();
+            declaration(&fun.typ, &format!("(*{})({})", name, args.join(", ")))
+        }
+    }
+}
+
+/// Adds the structures used by `typ` to `structs`.
+fn collect_structs<'a>(typ: &Typ<'a>, structs: &mut Vec<Rc<Struct<'a>>>) {
+    match typ {
+        Typ::TStruct(s) | Typ::TStructValue(s) if !structs.iter().any(|x| x.name == s.name) => {
+            structs.push(s.clone());
+            let fields = s.fields.borrow();
+            for field in fields.values() {
+                collect_structs(&field.typ, structs);
+            }
+        }
+        Typ::TFun(fun) => {
+            collect_structs(&fun.typ, structs);
+            fun.args.iter().for_each(|arg| collect_structs(arg, structs));
+        }
+        _ => {}
+    }
+}
+
+impl Display for Signature<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let args = self.args
+            .iter()
+            .map(|arg| declaration(&arg.typ, arg.name.name()))
+            .chain(self.variadic.then(|| String::from("...")))
+            .collect::<Vec<_>>();
+        write!(f, "{}({})", declaration(&self.typ, self.name), args.join(", "))
+    }
+}
+
+impl Display for Field<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.constant {
+            write!(f, "const ")?;
+        }
+        write!(f, "{}", declaration(&self.typ, self.name))
+    }
+}
+
+impl Display for Struct<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let fields = self.fields.borrow();
+        let mut fields = fields.values().collect::<Vec<_>>();
+        fields.sort_by_key(|field| field.index);
+
+        writeln!(f, "struct {} {{", self.name)?;
+        for field in fields {
+            write_indent(f, 1)?;
+            writeln!(f, "{};", field)?;
+        }
+        write!(f, "}};")
+    }
+}
+
+impl Display for File<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut funs = self.funs.values().collect::<Vec<_>>();
+        funs.sort_by_key(|fun| fun.signature.name.as_ptr());
+
+        let mut structs = vec![];
+        for fun in &funs {
+            collect_structs(&fun.signature.typ, &mut structs);
+            fun.signature.args.iter().for_each(|arg| collect_structs(&arg.typ, &mut structs));
+            fun.locals.values().for_each(|typ| collect_structs(typ, &mut structs));
+        }
+        structs.sort_by_key(|s| s.name.as_ptr());
+
+        for structure in structs {
+            writeln!(f, "{}", structure)?;
+            writeln!(f)?;
+        }
+        for (index, fun) in funs.into_iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{}", Printer::new(&self.strings, fun, f.alternate()).fun())?;
+        }
+        Ok(())
+    }
+}
+
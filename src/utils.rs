@@ -1,10 +1,10 @@
-use std::collections::{HashSet};
+use std::collections::BTreeSet;
 use std::fmt::{Display, Formatter};
 
 
 pub struct DisplayableVec<'a, T>(pub &'a Vec<T>);
 
-pub struct DisplayableSet<'a, T>(pub &'a HashSet<T>);
+pub struct DisplayableSet<'a, T>(pub &'a BTreeSet<T>);
 
 impl<T: Display> Display for DisplayableSet<'_, T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use derive_new::new;
+use derive_getters::Getters;
+use crate::common::{Ident, Location};
+
+/// Kinds of warnings. Codes and names are stable : they are used on the command line.
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
+pub enum WarningCode {
+    MissingReturn,
+    Uninitialised,
+    UnusedVariable,
+    UnusedParameter,
+    UnusedFunction,
+    UnreachableCode,
+    ConstantCondition,
+    NullDereference,
+}
+
+#[derive(new, Debug, Getters)]
+pub struct Warning<'a> {
+    code: WarningCode,
+    /// Function the warning is about.
+    fun: Ident<'a>,
+    /// Identifier of the source the warning points to, if any.
+    anchor: Option<Ident<'a>>,
+    message: String,
+}
+
+/// A warning located in the source text it comes from.
+#[derive(new)]
+pub struct DisplayableWarning<'a, 'b> {
+    warning: &'b Warning<'a>,
+    source: &'b str,
+}
+
+#[derive(Debug)]
+pub enum WarningFlagError {
+    UnknownWarning(String),
+}
+
+/// Which warnings are reported, set by `-W<name>`, `-Wno-<name>` and `-Werror`.
+#[derive(Debug, Clone, Default)]
+pub struct WarningOptions {
+    disabled: HashSet<WarningCode>,
+    error: bool,
+}
+
+impl WarningCode {
+    pub const ALL: [WarningCode; 8] = [
+        WarningCode::MissingReturn,
+        WarningCode::Uninitialised,
+        WarningCode::UnusedVariable,
+        WarningCode::UnusedParameter,
+        WarningCode::UnusedFunction,
+        WarningCode::UnreachableCode,
+        WarningCode::ConstantCondition,
+        WarningCode::NullDereference,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            WarningCode::MissingReturn => "W0001",
+            WarningCode::Uninitialised => "W0002",
+            WarningCode::UnusedVariable => "W0003",
+            WarningCode::UnusedParameter => "W0004",
+            WarningCode::UnusedFunction => "W0005",
+            WarningCode::UnreachableCode => "W0006",
+            WarningCode::ConstantCondition => "W0007",
+            WarningCode::NullDereference => "W0008",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WarningCode::MissingReturn => "missing-return",
+            WarningCode::Uninitialised => "uninitialized",
+            WarningCode::UnusedVariable => "unused-variable",
+            WarningCode::UnusedParameter => "unused-parameter",
+            WarningCode::UnusedFunction => "unused-function",
+            WarningCode::UnreachableCode => "unreachable-code",
+            WarningCode::ConstantCondition => "constant-condition",
+            WarningCode::NullDereference => "null-dereference",
+        }
+    }
+
+    /// Finds a warning by its name or its code.
+    pub fn find(name: &str) -> Option<WarningCode> {
+        WarningCode::ALL
+            .into_iter()
+            .find(|code| code.name() == name || code.code() == name)
+    }
+}
+
+impl WarningOptions {
+    /// Applies a `-W` flag, given without its leading `-W`.
+    pub fn set(&mut self, flag: &str) -> Result<(), WarningFlagError> {
+        if flag == "error" {
+            self.error = true;
+            return Ok(());
+        }
+        if flag == "all" {
+            self.disabled.clear();
+            return Ok(());
+        }
+
+        let (enabled, name) = match flag.strip_prefix("no-") {
+            Some(name) => (false, name),
+            None => (true, flag),
+        };
+        let code = WarningCode::find(name)
+            .ok_or_else(|| WarningFlagError::UnknownWarning(flag.to_string()))?;
+
+        if enabled {
+            self.disabled.remove(&code);
+        } else {
+            self.disabled.insert(code);
+        }
+        Ok(())
+    }
+
+    pub fn is_enabled(&self, code: WarningCode) -> bool {
+        !self.disabled.contains(&code)
+    }
+
+    /// Whether reported warnings make the compilation fail.
+    pub fn is_error(&self) -> bool {
+        self.error
+    }
+}
+
+impl Display for WarningCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl Display for Warning<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "warning[{}]: {} [-W{}]", self.code, self.message, self.code.name())
+    }
+}
+
+impl Display for DisplayableWarning<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let warning = self.warning;
+        write!(f, "warning[{}]: ", warning.code)?;
+        if let Some(location) = warning.anchor.and_then(|anchor| Location::of(self.source, anchor)) {
+            write!(f, "{}: ", location)?;
+        }
+        write!(f, "{} [-W{}]", warning.message, warning.code.name())
+    }
+}
+
+impl Display for WarningFlagError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WarningFlagError::UnknownWarning(flag) => write!(f, "unknown warning option `-W{}`", flag),
+        }
+    }
+}
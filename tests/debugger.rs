@@ -0,0 +1,123 @@
+use std::fs::read_to_string;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use rust_mini_c::parser::parse_file;
+
+/// Runs the program under the debugger with the commands, and gives what it printed.
+fn debug(path: &str, commands: &str) -> String {
+    let file = read_to_string(path).expect("Failed to read file");
+    let parsed = parse_file(&file).expect("Failed to parse");
+    let typed = parsed.minic_typ().expect("Failed to typ file");
+    let rtl = typed.minic_rtl().expect("Failed to rtl file");
+
+    let mut output = vec![];
+    let _ = rtl.minic_debug(commands.as_bytes(), &mut output);
+    String::from_utf8(output).expect("Invalid output")
+}
+
+#[test]
+fn stops_at_main() {
+    let output = debug("tests/source/exec/fact_rec.c", "s\ns\nq\n");
+
+    assert!(output.starts_with("main L"), "{}", output);
+    assert_eq!(2, output.matches("main L").count(), "{}", output);
+    assert_eq!(1, output.matches("(minic) fact_rec L").count(), "{}", output);
+    assert!(output.ends_with("Program stopped\n"), "{}", output);
+}
+
+#[test]
+fn continue_to_end() {
+    let output = debug("tests/source/exec/fact_rec.c", "c\n");
+
+    assert!(output.ends_with("(minic) 123\nProgram exited normally\n"), "{}", output);
+}
+
+#[test]
+fn break_on_function() {
+    let output = debug("tests/source/exec/fact_rec.c", "b fact_rec\nc\nc\nc\np n\nbt\nq\n");
+
+    assert!(output.contains("(minic) n = 5\n"), "{}", output);
+    assert!(output.contains("#0 fact_rec at L"), "{}", output);
+    assert!(output.contains("#1 main at L"), "{}", output);
+}
+
+#[test]
+fn break_on_unknown_place() {
+    let output = debug("tests/source/exec/fact_rec.c", "b L999\nb nope\nc\n");
+
+    assert!(output.contains("(minic) No function or label `L999`\n"), "{}", output);
+    assert!(output.contains("(minic) No function or label `nope`\n"), "{}", output);
+    assert!(output.ends_with("Program exited normally\n"), "{}", output);
+}
+
+#[test]
+fn locals() {
+    let output = debug("tests/source/exec/call2.c", "b f\nc\nc\nlocals\nq\n");
+
+    assert!(output.contains("(minic) x = 66\ny = 67\nz = 0\nt = 65\n"), "{}", output);
+}
+
+#[test]
+fn print_register() {
+    let output = debug("tests/source/exec/call2.c", "b f\nc\nc\np #2\np #999\nq\n");
+
+    assert!(output.contains("(minic) #2 = 66\n"), "{}", output);
+    assert!(output.contains("(minic) #999 wasn't set\n"), "{}", output);
+}
+
+#[test]
+fn break_on_label() {
+    let first = debug("tests/source/exec/fact_rec.c", "b fact_rec\nc\nq\n");
+    let stop = first.lines().nth(2).expect("No stop in fact_rec");
+    let label = stop.split_whitespace().nth(2).expect("No label").trim_end_matches(':');
+
+    let output = debug("tests/source/exec/fact_rec.c", &format!("b {}\nc\nd {}\nc\n", label, label));
+
+    assert!(output.contains(&format!("(minic) fact_rec {}:", label)), "{}", output);
+    assert!(output.ends_with("Program exited normally\n"), "{}", output);
+}
+
+#[test]
+fn heap() {
+    let output = debug("tests/source/exec/local_struct1.c", "b depth\nc\nheap\nq\n");
+
+    assert!(output.contains("1 [16 bytes] {0: 1, 8: 65}\n2 [16 bytes] {0: 65, 8: 0}\n"), "{}", output);
+}
+
+#[test]
+fn runtime_error() {
+    let output = debug("tests/source/exec-fail/deref_null.c", "c\n");
+
+    assert!(output.ends_with("Program failed : null pointer dereference in function `main` at L3\n"), "{}", output);
+}
+
+#[test]
+fn cli() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rust-mini-c"))
+        .arg("debug")
+        .arg("tests/source/exec/hello_world.c")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to start the debugger");
+
+    child.stdin.take().expect("No stdin").write_all(b"c\n").expect("Failed to write commands");
+    let output = child.wait_with_output().expect("Failed to run the debugger");
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with("Program exited normally\n"));
+}
+
+#[test]
+fn cli_error() {
+    let output = Command::new(env!("CARGO_BIN_EXE_rust-mini-c"))
+        .arg("debug")
+        .arg("tests/source/typing/bad/testfile-arith-1.c")
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to start the debugger");
+
+    assert_eq!(Some(1), output.status.code());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("error: "));
+}
@@ -0,0 +1,226 @@
+use std::fmt::{Display, Formatter};
+use std::fs::{File, read_to_string};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use rust_mini_c::common::Execution;
+use rust_mini_c::parser::parse_file;
+
+macro_rules! test_differential {
+    ($($name:ident: $path:literal,)*) => {
+        $(
+        #[test]
+        fn $name() {
+            std::thread::Builder::new()
+                .stack_size(INTERPRETER_STACK_SIZE)
+                .spawn(|| _test_differential($path))
+                .expect("Failed to start test")
+                .join()
+                .unwrap_or_else(|err| std::panic::resume_unwind(err));
+        }
+        )*
+
+    };
+}
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// The interpreters are recursive, and run deep loops out of the default stack of test threads.
+const INTERPRETER_STACK_SIZE: usize = 1 << 30;
+
+#[derive(Debug)]
+enum Status {
+    Exited(i32),
+    Failed(String),
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Exited(code) => write!(f, "exited with status {}", code),
+            Status::Failed(reason) => write!(f, "failed : {}", reason),
+        }
+    }
+}
+
+/// What a stage observed when running the program.
+#[derive(Debug)]
+struct Behaviour {
+    stdout: String,
+    status: Status,
+}
+
+impl Behaviour {
+    /// The interpreters stop without output on errors, and the exit status keeps the low byte of what `main` returns.
+    fn interpreted<E: std::fmt::Debug>(result: Result<Execution, E>) -> Behaviour {
+        match result {
+            Ok(execution) => Behaviour {
+                stdout: execution.stdout.to_string(),
+                status: Status::Exited(execution.status as u8 as i32),
+            },
+            Err(err) => Behaviour { stdout: String::new(), status: Status::Failed(format!("{:?}", err)) },
+        }
+    }
+
+    fn agrees_with(&self, other: &Behaviour) -> bool {
+        match (&self.status, &other.status) {
+            (Status::Exited(code_1), Status::Exited(code_2)) => code_1 == code_2 && self.stdout == other.stdout,
+            (Status::Failed(_), Status::Failed(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Display for Behaviour {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, with output {:?}", self.status, self.stdout)
+    }
+}
+
+fn native(base_path: &str, assembly: String) -> Behaviour {
+    let stem = base_path.rsplit('/').next().unwrap();
+    let base = std::env::temp_dir().join(format!("minic-differential-{}-{}", stem, std::process::id()));
+    let (source, binary, output) = (base.with_extension("s"), base.with_extension("o"), base.with_extension("out"));
+
+    let mut file = File::create(&source).expect("Failed to create assembly file");
+    writeln!(&mut file, "{}", assembly).expect("Failed to write to assembly");
+
+    let compilation = Command::new("gcc")
+        .arg(&source)
+        .arg("-o")
+        .arg(&binary)
+        .output()
+        .expect("Failed to start compilation");
+    assert!(compilation.status.success(), "Compilation failed : {}", String::from_utf8_lossy(&compilation.stderr));
+
+    let mut child = Command::new(&binary)
+        .stdin(Stdio::null())
+        .stdout(File::create(&output).expect("Failed to create output file"))
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to execute");
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().expect("Failed to wait") {
+            break match status.code() {
+                Some(code) => Status::Exited(code),
+                None => Status::Failed(status.to_string()),
+            };
+        }
+        if start.elapsed() > TIMEOUT {
+            child.kill().expect("Failed to kill");
+            child.wait().expect("Failed to wait");
+            break Status::Failed(String::from("Timed out"));
+        }
+        sleep(Duration::from_millis(10));
+    };
+    let stdout = String::from_utf8_lossy(&std::fs::read(&output).expect("Failed to read output")).to_string();
+
+    for path in [source, binary, output] {
+        let _ = std::fs::remove_file(path);
+    }
+
+    Behaviour { stdout, status }
+}
+
+/// Runs the program at each stage, and reports the first one which doesn't behave as the one before.
+fn _test_differential(base_path: &str) {
+    println!("File {}", base_path);
+
+    let file = read_to_string(format!("{}.c", base_path)).expect("Failed to read source file");
+    let parsed = parse_file(&file).expect("Failed to parse file");
+    let typed = parsed.minic_typ().expect("Failed to typ file");
+    let rtl = typed.minic_rtl().expect("Failed to rtl file");
+    let ertl = rtl.minic_ertl().expect("Failed to ertl file");
+    let ltl = ertl.minic_ltl().expect("Failed to ltl file");
+    let x86 = ltl.minic_linearise().expect("Failed to linearise file");
+
+    let stages = [
+        ("typer", Behaviour::interpreted(typed.minic_interp())),
+        ("rtl", Behaviour::interpreted(rtl.minic_interp())),
+        ("native", native(base_path, x86.to_string())),
+    ];
+
+    for window in stages.windows(2) {
+        let ((before, expected), (stage, actual)) = (&window[0], &window[1]);
+        assert!(
+            actual.agrees_with(expected),
+            "Stage {} diverges from {} :\n{}\ninstead of\n{}", stage, before, actual, expected
+        );
+    }
+}
+
+
+test_differential!(
+    abr: "tests/source/exec/abr",
+    and_1: "tests/source/exec/and1",
+    and_2: "tests/source/exec/and2",
+    exec_arith_1: "tests/source/exec/arith1",
+    exec_assign_1: "tests/source/exec/assign1",
+    exec_assign_2: "tests/source/exec/assign2",
+    exec_assign_3: "tests/source/exec/assign3",
+    exec_assign_6: "tests/source/exec/assign6",
+    exec_assign_7: "tests/source/exec/assign7",
+    block_1: "tests/source/exec/block1",
+    block_2: "tests/source/exec/block2",
+    exec_call_1: "tests/source/exec/call1",
+    exec_call_2: "tests/source/exec/call2",
+    char_1: "tests/source/exec/char1",
+    char_2: "tests/source/exec/char2",
+    char_3: "tests/source/exec/char3",
+    char_4: "tests/source/exec/char4",
+    comp_1: "tests/source/exec/comp1",
+    comp_2: "tests/source/exec/comp2",
+    const_1: "tests/source/exec/const1",
+    decl_init_1: "tests/source/exec/decl_init1",
+    dllist: "tests/source/exec/dllist",
+    fact: "tests/source/exec/fact",
+    fact_imp: "tests/source/exec/fact_imp",
+    fact_rec: "tests/source/exec/fact_rec",
+    field_2: "tests/source/exec/field2",
+    field_4: "tests/source/exec/field4",
+    field_7: "tests/source/exec/field7",
+    field_8: "tests/source/exec/field8",
+    for_1: "tests/source/exec/for1",
+    for_2: "tests/source/exec/for2",
+    for_3: "tests/source/exec/for3",
+    for_4: "tests/source/exec/for4",
+    for_5: "tests/source/exec/for5",
+    for_6: "tests/source/exec/for6",
+    fun_pointer_1: "tests/source/exec/fun_pointer1",
+    getchar_1: "tests/source/exec/getchar1",
+    getchar_2: "tests/source/exec/getchar2",
+    hello_world: "tests/source/exec/hello_world",
+    if_1: "tests/source/exec/if1",
+    init_1: "tests/source/exec/init1",
+    josephus: "tests/source/exec/josephus",
+    lazy_1: "tests/source/exec/lazy1",
+    local_1: "tests/source/exec/local1",
+    local_2: "tests/source/exec/local2",
+    local_3: "tests/source/exec/local3",
+    local_struct_1: "tests/source/exec/local_struct1",
+    mandelbrot: "tests/source/exec/mandelbrot",
+    many: "tests/source/exec/many",
+    not_1: "tests/source/exec/not1",
+    not_2: "tests/source/exec/not2",
+    or_1: "tests/source/exec/or1",
+    pascal: "tests/source/exec/pascal",
+    print_int: "tests/source/exec/print_int",
+    printf_1: "tests/source/exec/printf1",
+    putchar_octal: "tests/source/exec/putchar-octal1",
+    putchar: "tests/source/exec/putchar1",
+    putchar_hexa: "tests/source/exec/putchar_hexa1",
+    return_1: "tests/source/exec/return1",
+    return_2: "tests/source/exec/return2",
+    shadow_1: "tests/source/exec/shadow1",
+    sizeof_2: "tests/source/exec/sizeof2",
+    spilled_1: "tests/source/exec/spilled1",
+    exec_uminus_1: "tests/source/exec/uminus1",
+    while_1: "tests/source/exec/while1",
+    while_2: "tests/source/exec/while2",
+    while_3: "tests/source/exec/while3",
+    while_4: "tests/source/exec/while4",
+    wrapping_1: "tests/source/exec/wrapping1",
+);
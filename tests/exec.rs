@@ -1,7 +1,7 @@
 #![feature(exit_status_error)]
 
 use std::fs::{File, read_to_string};
-use std::process::Command;
+use std::process::{Command, Stdio};
 use std::io::Write;
 
 use rust_mini_c::parser::parse_file;
@@ -103,6 +103,7 @@ fn _test_good(base_path: &str) {
     }
 
     let exec = Command::new(format!("{}.o", base_path))
+        .stdin(Stdio::null())
         .output()
         .expect("Failed to execute");
 
@@ -139,6 +140,9 @@ mod good {
         char_3: "tests/source/exec/char3",
         char_4: "tests/source/exec/char4",
         comp_1: "tests/source/exec/comp1",
+        comp_2: "tests/source/exec/comp2",
+        const_1: "tests/source/exec/const1",
+        decl_init_1: "tests/source/exec/decl_init1",
         dllist: "tests/source/exec/dllist",
         fact: "tests/source/exec/fact",
         field_2: "tests/source/exec/field2",
@@ -150,6 +154,9 @@ mod good {
         for_4: "tests/source/exec/for4",
         for_5: "tests/source/exec/for5",
         for_6: "tests/source/exec/for6",
+        fun_pointer_1: "tests/source/exec/fun_pointer1",
+        getchar_1: "tests/source/exec/getchar1",
+        getchar_2: "tests/source/exec/getchar2",
         hello_world: "tests/source/exec/hello_world",
         if_1: "tests/source/exec/if1",
         init_1: "tests/source/exec/init1",
@@ -158,11 +165,13 @@ mod good {
         local_1: "tests/source/exec/local1",
         local_2: "tests/source/exec/local2",
         local_3: "tests/source/exec/local3",
+        local_struct_1: "tests/source/exec/local_struct1",
         many: "tests/source/exec/many",
         not_1: "tests/source/exec/not1",
         not_2: "tests/source/exec/not2",
         or_1: "tests/source/exec/or1",
         print_int: "tests/source/exec/print_int",
+        printf_1: "tests/source/exec/printf1",
         putchar_octal: "tests/source/exec/putchar-octal1",
         putchar: "tests/source/exec/putchar1",
         putchar_hexa: "tests/source/exec/putchar_hexa1",
@@ -176,6 +185,7 @@ mod good {
         while_2: "tests/source/exec/while2",
         while_3: "tests/source/exec/while3",
         while_4: "tests/source/exec/while4",
+        wrapping_1: "tests/source/exec/wrapping1",
     );
 }
 
@@ -186,5 +196,7 @@ mod bad {
     test_bad!(
         deref_null: "tests/source/exec-fail/deref_null.c",
         division_by_zero: "tests/source/exec-fail/division_by_zero1.c",
+        division_overflow: "tests/source/exec-fail/division_overflow.c",
+        field_out_of_bounds: "tests/source/exec-fail/field_out_of_bounds.c",
     );
 }
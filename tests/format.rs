@@ -0,0 +1,30 @@
+use std::fs::read_to_string;
+
+use rust_mini_c::parser::printer::format_source;
+
+macro_rules! test_format {
+    ($($name:ident: $path:literal, $expected:literal,)*) => {
+        $(
+        #[test]
+        fn $name() {
+            _test_format($path, $expected);
+        }
+        )*
+
+    };
+}
+
+fn _test_format(path: &str, expected: &str) {
+    println!("File {}", path);
+
+    let source = read_to_string(path).expect("Failed to read file");
+    let expected = read_to_string(expected).expect("Failed to read file").replace("\r\n", "\n");
+
+    let formatted = format_source(&source).expect("Failed to format").replace("\r\n", "\n");
+    assert_eq!(formatted, expected);
+}
+
+test_format!(
+    comments_1: "tests/source/format/testfile-comments-1.c", "tests/source/format/testfile-comments-1.out",
+    comments_2: "tests/source/format/testfile-comments-2.c", "tests/source/format/testfile-comments-2.out",
+);
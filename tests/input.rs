@@ -0,0 +1,118 @@
+use std::fs::{read_to_string, remove_file};
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use rust_mini_c::common::{Execution, Stdin};
+use rust_mini_c::linearise::emulator::DEFAULT_MAX_STEPS;
+use rust_mini_c::parser::parse_file;
+use rust_mini_c::rtl::interpreter::Limits;
+
+const CAT: &str = "tests/source/exec/getchar1.c";
+const INPUT: &str = "hello";
+
+/// Runs the command with the input, and gives how it ended.
+fn run(command: &mut Command, input: &str) -> Output {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start");
+    child.stdin.take().expect("No input").write_all(input.as_bytes()).expect("Failed to write the input");
+    child.wait_with_output().expect("Failed to wait")
+}
+
+fn assert_copied(execution: Execution) {
+    assert_eq!("hello!\n", execution.stdout.to_string());
+    assert_eq!(INPUT.len() as i64, execution.status);
+}
+
+#[test]
+fn stages_read_the_input() {
+    let file = read_to_string(CAT).expect("Failed to read file");
+    let parsed = parse_file(&file).expect("Failed to parse");
+    let typed = parsed.minic_typ().expect("Failed to typ file");
+    let rtl = typed.minic_rtl().expect("Failed to rtl file");
+    let ertl = rtl.minic_ertl().expect("Failed to ertl file");
+    let ltl = ertl.minic_ltl().expect("Failed to ltl file");
+    let program = ltl.minic_linearise().expect("Failed to linearise file");
+
+    assert_copied(typed.minic_interp_with(Stdin::new(INPUT)).expect("Failed to interp typed file"));
+    assert_copied(rtl.minic_interp_with(Stdin::new(INPUT), Limits::default()).expect("Failed to interp RTL"));
+    assert_copied(ertl.minic_interp_with(Stdin::new(INPUT)).expect("Failed to interp ERTL"));
+    assert_copied(ltl.minic_interp_with(Stdin::new(INPUT)).expect("Failed to interp LTL"));
+    assert_copied(program.minic_emulate_with(Stdin::new(INPUT), DEFAULT_MAX_STEPS).expect("Failed to emulate"));
+}
+
+#[test]
+fn empty_input() {
+    let file = read_to_string(CAT).expect("Failed to read file");
+    let parsed = parse_file(&file).expect("Failed to parse");
+    let typed = parsed.minic_typ().expect("Failed to typ file");
+
+    let execution = typed.minic_interp().expect("Failed to interp typed file");
+    assert_eq!("!\n", execution.stdout.to_string());
+    assert_eq!(0, execution.status);
+}
+
+#[test]
+fn cli_interp() {
+    for stage in ["--interp", "--interp=typer"] {
+        let output = run(Command::new(env!("CARGO_BIN_EXE_rust-mini-c")).arg(stage).arg(CAT), INPUT);
+
+        assert_eq!("hello!\n", String::from_utf8_lossy(&output.stdout));
+        assert_eq!(Some(INPUT.len() as i32), output.status.code());
+    }
+}
+
+#[test]
+fn cli_doesnt_wait_for_unread_input() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rust-mini-c"))
+        .arg("--interp")
+        .arg("tests/source/exec/fact_rec.c")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .expect("Failed to start the compiler");
+
+    // The input stays open while the program runs
+    let _input = child.stdin.take();
+    let start = Instant::now();
+    while child.try_wait().expect("Failed to wait").is_none() {
+        if start.elapsed() > Duration::from_secs(10) {
+            child.kill().expect("Failed to kill");
+            panic!("The interpreter waited for the input");
+        }
+        sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn native() {
+    let base = std::env::temp_dir().join(format!("minic-input-{}", std::process::id()));
+    let (source, binary) = (base.with_extension("s"), base.with_extension("o"));
+
+    let compilation = Command::new(env!("CARGO_BIN_EXE_rust-mini-c"))
+        .arg(CAT)
+        .arg(&source)
+        .output()
+        .expect("Failed to start the compiler");
+    assert!(compilation.status.success(), "{}", String::from_utf8_lossy(&compilation.stderr));
+    let assembly = Command::new("gcc")
+        .arg("-no-pie")
+        .arg(&source)
+        .arg("-o")
+        .arg(&binary)
+        .output()
+        .expect("Failed to start gcc");
+    assert!(assembly.status.success(), "{}", String::from_utf8_lossy(&assembly.stderr));
+
+    let output = run(&mut Command::new(&binary), INPUT);
+    let _ = remove_file(source);
+    let _ = remove_file(binary);
+
+    assert_eq!("hello!\n", String::from_utf8_lossy(&output.stdout));
+    assert_eq!(Some(INPUT.len() as i32), output.status.code());
+}
@@ -60,6 +60,9 @@ test_interp_typing_good!(
     char_3: "tests/source/exec/char3.c", "tests/source/exec/char3.out",
     char_4: "tests/source/exec/char4.c", "tests/source/exec/char4.out",
     comp_1: "tests/source/exec/comp1.c", "tests/source/exec/comp1.out",
+    comp_2: "tests/source/exec/comp2.c", "tests/source/exec/comp2.out",
+    const_1: "tests/source/exec/const1.c", "tests/source/exec/const1.out",
+    decl_init_1: "tests/source/exec/decl_init1.c", "tests/source/exec/decl_init1.out",
     dllist: "tests/source/exec/dllist.c", "tests/source/exec/dllist.out",
     fact: "tests/source/exec/fact.c", "tests/source/exec/fact.out",
     fact_imp: "tests/source/exec/fact_imp.c", "tests/source/exec/fact_imp.out",
@@ -74,6 +77,9 @@ test_interp_typing_good!(
     for_4: "tests/source/exec/for4.c", "tests/source/exec/for4.out",
     for_5: "tests/source/exec/for5.c", "tests/source/exec/for5.out",
     for_6: "tests/source/exec/for6.c", "tests/source/exec/for6.out",
+    fun_pointer_1: "tests/source/exec/fun_pointer1.c", "tests/source/exec/fun_pointer1.out",
+    getchar_1: "tests/source/exec/getchar1.c", "tests/source/exec/getchar1.out",
+    getchar_2: "tests/source/exec/getchar2.c", "tests/source/exec/getchar2.out",
     hello_world: "tests/source/exec/hello_world.c", "tests/source/exec/hello_world.out",
     if_1: "tests/source/exec/if1.c", "tests/source/exec/if1.out",
     init_1: "tests/source/exec/init1.c", "tests/source/exec/init1.out",
@@ -82,6 +88,7 @@ test_interp_typing_good!(
     local_1: "tests/source/exec/local1.c", "tests/source/exec/local1.out",
     local_2: "tests/source/exec/local2.c", "tests/source/exec/local2.out",
     local_3: "tests/source/exec/local3.c", "tests/source/exec/local3.out",
+    local_struct_1: "tests/source/exec/local_struct1.c", "tests/source/exec/local_struct1.out",
     mandelbrot: "tests/source/exec/mandelbrot.c", "tests/source/exec/mandelbrot.out",
     many: "tests/source/exec/many.c", "tests/source/exec/many.out",
     not_1: "tests/source/exec/not1.c", "tests/source/exec/not1.out",
@@ -89,6 +96,7 @@ test_interp_typing_good!(
     or_1: "tests/source/exec/or1.c", "tests/source/exec/or1.out",
     pascal: "tests/source/exec/pascal.c", "tests/source/exec/pascal.out",
     print_int: "tests/source/exec/print_int.c", "tests/source/exec/print_int.out",
+    printf_1: "tests/source/exec/printf1.c", "tests/source/exec/printf1.out",
     putchar_octal: "tests/source/exec/putchar-octal1.c", "tests/source/exec/putchar-octal1.out",
     putchar: "tests/source/exec/putchar1.c", "tests/source/exec/putchar1.out",
     putchar_hexa: "tests/source/exec/putchar_hexa1.c", "tests/source/exec/putchar_hexa1.out",
@@ -102,4 +110,5 @@ test_interp_typing_good!(
     while_2: "tests/source/exec/while2.c", "tests/source/exec/while2.out",
     while_3: "tests/source/exec/while3.c", "tests/source/exec/while3.out",
     while_4: "tests/source/exec/while4.c", "tests/source/exec/while4.out",
+    wrapping_1: "tests/source/exec/wrapping1.c", "tests/source/exec/wrapping1.out",
 );
\ No newline at end of file
@@ -0,0 +1,219 @@
+use std::fs::read_to_string;
+use std::io::{BufReader, Write};
+use std::process::{Command, Stdio};
+
+use serde_json::{json, Value};
+use rust_mini_c::lsp::protocol::read_message;
+
+const URI: &str = "file:///testfile.c";
+
+/// Runs `minic-lsp` on a session : initialisation, opening `path`, `requests`, then shutdown.
+/// Returns the messages sent back by the server, but the ones of the initialisation.
+fn _test_session(path: &str, requests: Vec<Value>) -> Vec<Value> {
+    _test_session_contents(path, requests.iter().map(Value::to_string).collect())
+}
+
+/// Same as `_test_session`, with requests given as the contents of their messages.
+fn _test_session_contents(path: &str, requests: Vec<String>) -> Vec<Value> {
+    println!("File {}", path);
+
+    let text = read_to_string(path).expect("Failed to read file");
+    let messages = vec![
+        json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {} }),
+        json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+            "textDocument": { "uri": URI, "languageId": "c", "version": 1, "text": text }
+        }}),
+    ];
+    let mut messages = messages.iter().map(Value::to_string).collect::<Vec<_>>();
+    messages.extend(requests);
+    messages.push(json!({ "jsonrpc": "2.0", "id": 1000, "method": "shutdown" }).to_string());
+    messages.push(json!({ "jsonrpc": "2.0", "method": "exit" }).to_string());
+
+    let mut server = Command::new(env!("CARGO_BIN_EXE_minic-lsp"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to start server");
+
+    let mut stdin = server.stdin.take().unwrap();
+    for content in messages {
+        write!(stdin, "Content-Length: {}\r\n\r\n{}", content.len(), content).unwrap();
+    }
+    drop(stdin);
+
+    let mut stdout = BufReader::new(server.stdout.take().unwrap());
+    let mut responses = vec![];
+    while let Some(message) = read_message(&mut stdout).expect("Failed to read message") {
+        responses.push(message);
+    }
+    assert!(server.wait().unwrap().success());
+
+    assert_eq!(responses[0]["id"], 0);
+    assert_eq!(responses[0]["result"]["capabilities"]["hoverProvider"], true);
+    assert_eq!(responses.last().unwrap(), &json!({ "jsonrpc": "2.0", "id": 1000, "result": null }));
+    responses[1..responses.len() - 1].to_vec()
+}
+
+fn request(id: u64, method: &str, line: u64, character: u64) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": {
+        "textDocument": { "uri": URI },
+        "position": { "line": line, "character": character },
+    }})
+}
+
+fn change(text: String) -> Value {
+    json!({ "jsonrpc": "2.0", "method": "textDocument/didChange", "params": {
+        "textDocument": { "uri": URI, "version": 2 },
+        "contentChanges": [{ "text": text }],
+    }})
+}
+
+fn save() -> Value {
+    json!({ "jsonrpc": "2.0", "method": "textDocument/didSave", "params": { "textDocument": { "uri": URI } } })
+}
+
+fn range(line: u64, start: u64, end: u64) -> Value {
+    json!({ "start": { "line": line, "character": start }, "end": { "line": line, "character": end } })
+}
+
+fn diagnostics(message: &Value) -> Vec<(Value, Value, String)> {
+    assert_eq!(message["method"], "textDocument/publishDiagnostics");
+    message["params"]["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| (x["range"].clone(), x["severity"].clone(), x["message"].as_str().unwrap().to_string()))
+        .collect()
+}
+
+fn result(messages: &[Value], id: u64) -> &Value {
+    &messages.iter().find(|x| x["id"] == id).expect("Missing response")["result"]
+}
+
+#[test]
+fn diagnostics_on_open_and_save() {
+    let path = "tests/source/lsp/testfile-list-1.c";
+    let text = read_to_string(path).unwrap().replace("n = n + 1;", "n = n + list;");
+
+    let messages = _test_session(path, vec![change(text), save()]);
+    assert_eq!(messages.len(), 2);
+    assert_eq!(diagnostics(&messages[0]), vec![
+        (range(17, 8, 14), json!(2), String::from("variable `unused` is never read in `main`")),
+    ]);
+    assert_eq!(diagnostics(&messages[1]), vec![
+        (range(9, 12, 20), json!(1), String::from("expected type `int`, found `struct cell *`")),
+    ]);
+}
+
+#[test]
+fn diagnostics_without_identifier() {
+    let path = "tests/source/lsp/testfile-list-1.c";
+    let text = read_to_string(path).unwrap().replace("putchar(48);", "&48;");
+
+    let messages = _test_session(path, vec![change(text), save()]);
+    assert_eq!(diagnostics(&messages[1]), vec![
+        (range(21, 4, 5), json!(1), String::from("taking the address of a value which isn't a struct or a function")),
+    ]);
+}
+
+#[test]
+fn diagnostics_syntax_error() {
+    let path = "tests/source/lsp/testfile-list-1.c";
+    let text = read_to_string(path).unwrap().replace("return n;", "return n");
+
+    let messages = _test_session(path, vec![change(text), save()]);
+    assert_eq!(diagnostics(&messages[1]), vec![
+        (range(12, 4, 10), json!(1), String::from("syntax error at `return`")),
+    ]);
+}
+
+#[test]
+fn definition() {
+    let messages = _test_session("tests/source/lsp/testfile-list-1.c", vec![
+        // `next` in `list->next`
+        request(1, "textDocument/definition", 10, 22),
+        // `n` in `n = n + 1`
+        request(2, "textDocument/definition", 9, 12),
+        // `list` in `while (list)`
+        request(3, "textDocument/definition", 8, 11),
+        // `length` in `return length(c)`
+        request(4, "textDocument/definition", 22, 13),
+        // `cell` in `sizeof(struct cell)`
+        request(5, "textDocument/definition", 18, 30),
+        // `putchar` isn't in the source
+        request(6, "textDocument/definition", 21, 4),
+        // `while` names nothing
+        request(7, "textDocument/definition", 8, 5),
+    ]);
+
+    let location = |range| json!({ "uri": URI, "range": range });
+    assert_eq!(result(&messages, 1), &location(range(2, 17, 21)));
+    assert_eq!(result(&messages, 2), &location(range(6, 8, 9)));
+    assert_eq!(result(&messages, 3), &location(range(5, 24, 28)));
+    assert_eq!(result(&messages, 4), &location(range(5, 4, 10)));
+    assert_eq!(result(&messages, 5), &location(range(0, 7, 11)));
+    assert_eq!(result(&messages, 6), &Value::Null);
+    assert_eq!(result(&messages, 7), &Value::Null);
+}
+
+#[test]
+fn hover() {
+    let messages = _test_session("tests/source/lsp/testfile-list-1.c", vec![
+        request(1, "textDocument/hover", 10, 16),
+        request(2, "textDocument/hover", 22, 13),
+        request(3, "textDocument/hover", 20, 8),
+        request(4, "textDocument/hover", 21, 4),
+    ]);
+
+    let hover = |id| result(&messages, id)["contents"]["value"].as_str().unwrap().to_string();
+    assert_eq!(hover(1), "```c\nstruct cell *list\n```");
+    assert_eq!(hover(2), "```c\nint length(struct cell *list)\n```");
+    assert_eq!(hover(3), "```c\nstruct cell *next\n```");
+    assert_eq!(hover(4), "```c\nint putchar(int c)\n```");
+}
+
+#[test]
+fn completion() {
+    let path = "tests/source/lsp/testfile-list-1.c";
+    let text = read_to_string(path).unwrap();
+
+    let messages = _test_session(path, vec![
+        // `c->next = 0;` is being typed
+        change(text.replace("c->next = 0;", "c->")),
+        request(1, "textDocument/completion", 20, 7),
+        change(text.replace("list = list->next;", "list = list->ne")),
+        request(2, "textDocument/completion", 10, 23),
+        // No `->` before the position
+        request(3, "textDocument/completion", 9, 12),
+    ]);
+
+    let fields = json!([
+        { "label": "value", "kind": 5, "detail": "int value" },
+        { "label": "next", "kind": 5, "detail": "struct cell *next" },
+    ]);
+    assert_eq!(result(&messages, 1), &fields);
+    assert_eq!(result(&messages, 2), &fields);
+    assert_eq!(result(&messages, 3), &json!([]));
+}
+
+#[test]
+fn unknown_request() {
+    let messages = _test_session("tests/source/lsp/testfile-list-1.c", vec![
+        request(1, "textDocument/rename", 0, 0),
+    ]);
+
+    let response = messages.iter().find(|x| x["id"] == 1).unwrap();
+    assert_eq!(response["error"]["code"], -32601);
+}
+
+#[test]
+fn malformed_message() {
+    let messages = _test_session_contents("tests/source/lsp/testfile-list-1.c", vec![
+        String::from("{ \"jsonrpc\": \"2.0\", \"id\": 1, "),
+        request(2, "textDocument/hover", 22, 12).to_string(),
+    ]);
+    let error = messages.iter().find(|x| x["id"].is_null() && x.get("error").is_some()).expect("Missing error");
+    assert_eq!(error["error"]["code"], -32700);
+    assert!(result(&messages, 2)["contents"]["value"].as_str().unwrap().contains("int length(struct cell *list)"));
+}
@@ -0,0 +1,143 @@
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+
+use rust_mini_c::parser::parse_file;
+
+macro_rules! test_deterministic {
+    ($($name:ident: $path:literal,)*) => {
+        $(
+        #[test]
+        fn $name() {
+            _test_deterministic($path);
+        }
+        )*
+    };
+}
+
+fn compile(path: &str, output: &Path) -> String {
+    compile_with(path, output, &[])
+}
+
+fn compile_with(path: &str, output: &Path, flags: &[&str]) -> String {
+    let status = Command::new(env!("CARGO_BIN_EXE_rust-mini-c"))
+        .args(flags)
+        .arg(path)
+        .arg(output)
+        .status()
+        .expect("Failed to start the compiler");
+    assert!(status.success(), "Failed to compile {}", path);
+
+    read_to_string(output).expect("Failed to read assembly file")
+}
+
+/// Every stage, printed after each other.
+fn stages(path: &str) -> String {
+    let source = read_to_string(path).expect("Failed to read source file");
+    let parsed = parse_file(&source).expect("Failed to parse file");
+    let typed = parsed.minic_typ().expect("Failed to typ file");
+    let rtl = typed.minic_rtl().expect("Failed to rtl file");
+    let ertl = rtl.minic_ertl().expect("Failed to ertl file");
+    let ltl = ertl.minic_ltl().expect("Failed to ltl file");
+    let x86 = ltl.minic_linearise().expect("Failed to linearise file");
+
+    format!("{}\n{}\n{}\n{}", rtl, ertl, ltl, x86)
+}
+
+/// Compiles the file twice, in separate processes, and checks the assembly is the same
+/// and declares the functions in the order of the source.
+fn _test_deterministic(path: &str) {
+    println!("File {}", path);
+
+    let stem = PathBuf::from(path).file_stem().expect("No file name").to_string_lossy().to_string();
+    let base = std::env::temp_dir().join(format!("minic-output-{}-{}", stem, std::process::id()));
+    let outputs = [base.with_extension("1.s"), base.with_extension("2.s")];
+    let first = compile(path, &outputs[0]);
+    let second = compile(path, &outputs[1]);
+    for output in outputs {
+        let _ = std::fs::remove_file(output);
+    }
+    assert_eq!(first, second);
+
+    let source = read_to_string(path).expect("Failed to read source file");
+    let declared = first
+        .lines()
+        .filter_map(|line| line.strip_suffix(':'))
+        .filter(|label| !label.starts_with('.') && !label.starts_with('L'))
+        .collect::<Vec<_>>();
+    let defined = declared
+        .iter()
+        .map(|name| source.find(&format!(" {}(", name)).expect("Function not in the source"))
+        .collect::<Vec<_>>();
+    assert!(defined.windows(2).all(|x| x[0] < x[1]), "Functions out of order : {:?}", declared);
+}
+
+test_deterministic!(
+    abr: "tests/source/exec/abr.c",
+    dllist: "tests/source/exec/dllist.c",
+    fact_rec: "tests/source/exec/fact_rec.c",
+    josephus: "tests/source/exec/josephus.c",
+    mandelbrot: "tests/source/exec/mandelbrot.c",
+);
+
+#[test]
+fn same_process() {
+    let path = "tests/source/exec/abr.c";
+    let first = stages(path);
+    let _other = stages("tests/source/exec/fact_rec.c");
+    assert_eq!(first, stages(path));
+}
+
+#[test]
+fn parallel() {
+    let path = "tests/source/exec/josephus.c";
+    let expected = stages(path);
+    let threads = (0..4)
+        .map(|_| thread::spawn(move || stages(path)))
+        .collect::<Vec<_>>();
+
+    for thread in threads {
+        assert_eq!(expected, thread.join().expect("Compilation panicked"));
+    }
+}
+
+/// A program of `count` functions, each calling the previous one.
+fn generate(count: usize) -> String {
+    let mut source = String::from("int f0(int x) { return x; }\n");
+    for index in 1..count {
+        source.push_str(&format!(
+            "int f{}(int x) {{ int y; int z; y = x * 3 + {}; z = f{}(y / 2); if (z > y) return z - y; return y - z; }}\n",
+            index, index, index - 1,
+        ));
+    }
+    source.push_str(&format!("int main() {{ f{}(1); return 0; }}\n", count - 1));
+    source
+}
+
+/// Compiling on one thread and on all of them gives the same assembly.
+#[test]
+fn many_functions() {
+    let base = std::env::temp_dir().join(format!("minic-many-{}", std::process::id()));
+    let source = base.with_extension("c");
+    let outputs = [base.with_extension("1.s"), base.with_extension("n.s")];
+    write(&source, generate(1000)).expect("Failed to write source file");
+    let path = source.to_str().expect("Invalid path");
+
+    let sequential = compile_with(path, &outputs[0], &["-j", "1"]);
+    let parallel = compile_with(path, &outputs[1], &[]);
+
+    for path in [&source, &outputs[0], &outputs[1]] {
+        let _ = std::fs::remove_file(path);
+    }
+    assert_eq!(sequential, parallel);
+}
+
+#[test]
+fn back_end_is_send() {
+    fn send<T: Send + Sync>() {}
+    send::<rust_mini_c::rtl::structure::File>();
+    send::<rust_mini_c::ertl::structure::File>();
+    send::<rust_mini_c::ltl::structure::File>();
+    send::<rust_mini_c::linearise::x86_64::Program>();
+}
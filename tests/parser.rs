@@ -1,6 +1,9 @@
 use std::fs::read_to_string;
 
 use rust_mini_c::parser::parse_file;
+use rust_mini_c::parser::lexer::comments;
+use rust_mini_c::parser::printer::format_source;
+use rust_mini_c::parser::structure::File;
 
 macro_rules! test_syntax_bad {
     ($($name:ident: $path:literal,)*) => {
@@ -44,7 +47,10 @@ fn _test_syntax_good(path: &str) {
 
     let file = read_to_string(path).expect("Failed to read file");
     match parse_file(&file) {
-        Ok(_) => {}
+        Ok(parsed) => {
+            _test_syntax_round_trip(&parsed);
+            _test_syntax_format(&file, &parsed);
+        }
         Err(err) => {
             println!("{:?}", err);
             assert!(false);
@@ -52,6 +58,27 @@ fn _test_syntax_good(path: &str) {
     }
 }
 
+/// Formatting keeps the file and its comments, and formatting again changes nothing.
+fn _test_syntax_format(source: &str, file: &File) {
+    let formatted = format_source(source).expect("Failed to format");
+    println!("{}", formatted);
+
+    assert_eq!(&parse_file(&formatted).expect("Failed to parse the formatted file"), file);
+    assert_eq!(format_source(&formatted).expect("Failed to format again"), formatted);
+
+    let texts = |source| comments(source).into_iter().map(|x| x.text).collect::<Vec<_>>();
+    assert_eq!(texts(&formatted), texts(source));
+}
+
+/// Parsing the printed file gives back the same file.
+fn _test_syntax_round_trip(file: &File) {
+    let printed = file.to_string();
+    println!("{}", printed);
+
+    let reparsed = parse_file(&printed).expect("Failed to parse the printed file");
+    assert_eq!(&reparsed, file);
+}
+
 
 mod good {
     use crate::_test_syntax_good;
@@ -65,6 +92,7 @@ mod good {
     subtype_1: "tests/source/typing/good/testfile-subtype-1.c",
     subtype_2: "tests/source/typing/good/testfile-subtype-2.c",
     unique_1: "tests/source/typing/good/testfile-unique-1.c",
+    aggregate_1: "tests/source/typing/bad/testfile-aggregate-1.c",
     arith_1: "tests/source/typing/bad/testfile-arith-1.c",
     arith_2: "tests/source/typing/bad/testfile-arith-2.c",
     arith_3: "tests/source/typing/bad/testfile-arith-3.c",
@@ -74,6 +102,24 @@ mod good {
     arrow_4: "tests/source/typing/bad/testfile-arrow-4.c",
     call_1: "tests/source/typing/bad/testfile-call-1.c",
     call_2: "tests/source/typing/bad/testfile-call-2.c",
+    typing_const_1: "tests/source/typing/bad/testfile-const-1.c",
+    typing_const_2: "tests/source/typing/bad/testfile-const-2.c",
+    typing_const_3: "tests/source/typing/bad/testfile-const-3.c",
+    typing_const_4: "tests/source/typing/bad/testfile-const-4.c",
+    typing_decl_init_1: "tests/source/typing/bad/testfile-decl_init-1.c",
+    typing_decl_init_2: "tests/source/typing/bad/testfile-decl_init-2.c",
+    typing_decl_init_3: "tests/source/typing/bad/testfile-decl_init-3.c",
+    typing_missing_return_1: "tests/source/typing/warning/testfile-missing_return-1.c",
+    typing_missing_return_2: "tests/source/typing/warning/testfile-missing_return-2.c",
+    typing_constant_condition_1: "tests/source/typing/warning/testfile-constant_condition-1.c",
+    typing_unreachable_1: "tests/source/typing/warning/testfile-unreachable-1.c",
+    typing_null_deref_1: "tests/source/typing/warning/testfile-null_deref-1.c",
+    typing_unused_1: "tests/source/typing/warning/testfile-unused-1.c",
+    typing_fun_pointer_1: "tests/source/typing/bad/testfile-fun_pointer-1.c",
+    typing_fun_pointer_2: "tests/source/typing/bad/testfile-fun_pointer-2.c",
+    typing_fun_pointer_3: "tests/source/typing/bad/testfile-fun_pointer-3.c",
+    typing_printf_1: "tests/source/typing/bad/testfile-printf-1.c",
+    typing_printf_2: "tests/source/typing/bad/testfile-printf-2.c",
     missing_main_1: "tests/source/typing/bad/testfile-missing_main-1.c",
     missing_main_2: "tests/source/typing/bad/testfile-missing_main-2.c",
     redef_1: "tests/source/typing/bad/testfile-redef-1.c",
@@ -118,6 +164,9 @@ mod good {
     char_3: "tests/source/exec/char3.c",
     char_4: "tests/source/exec/char4.c",
     comp_1: "tests/source/exec/comp1.c",
+    comp_2: "tests/source/exec/comp2.c",
+    const_1: "tests/source/exec/const1.c",
+    decl_init_1: "tests/source/exec/decl_init1.c",
     dllist: "tests/source/exec/dllist.c",
     fact: "tests/source/exec/fact.c",
     fact_imp: "tests/source/exec/fact_imp.c",
@@ -132,6 +181,9 @@ mod good {
     for_4: "tests/source/exec/for4.c",
     for_5: "tests/source/exec/for5.c",
     for_6: "tests/source/exec/for6.c",
+    fun_pointer_1: "tests/source/exec/fun_pointer1.c",
+    getchar_1: "tests/source/exec/getchar1.c",
+    getchar_2: "tests/source/exec/getchar2.c",
     hello_world: "tests/source/exec/hello_world.c",
     if_1: "tests/source/exec/if1.c",
     init_1: "tests/source/exec/init1.c",
@@ -140,6 +192,7 @@ mod good {
     local_1: "tests/source/exec/local1.c",
     local_2: "tests/source/exec/local2.c",
     local_3: "tests/source/exec/local3.c",
+    local_struct_1: "tests/source/exec/local_struct1.c",
     mandelbrot: "tests/source/exec/mandelbrot.c",
     many: "tests/source/exec/many.c",
     not_1: "tests/source/exec/not1.c",
@@ -147,6 +200,7 @@ mod good {
     or_1: "tests/source/exec/or1.c",
     pascal: "tests/source/exec/pascal.c",
     print_int: "tests/source/exec/print_int.c",
+    printf_1: "tests/source/exec/printf1.c",
     putchar_octal: "tests/source/exec/putchar-octal1.c",
     putchar: "tests/source/exec/putchar1.c",
     putchar_hexa: "tests/source/exec/putchar_hexa1.c",
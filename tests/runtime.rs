@@ -0,0 +1,229 @@
+use std::fs::read_to_string;
+use std::process::Command;
+
+use rust_mini_c::common::{Execution, Location, Stdin};
+use rust_mini_c::parser::parse_file;
+use rust_mini_c::rtl::interpreter::{Limits, RtlInterpreterResult};
+
+macro_rules! test_runtime_error {
+    ($($name:ident: $path:literal => $message:literal at $location:literal,)*) => {
+        $(
+        #[test]
+        fn $name() {
+            std::thread::Builder::new()
+                .stack_size(INTERPRETER_STACK_SIZE)
+                .spawn(|| _test_runtime_error($path, $message, $location))
+                .expect("Failed to start test")
+                .join()
+                .unwrap_or_else(|err| std::panic::resume_unwind(err));
+        }
+        )*
+
+    };
+}
+
+/// The interpreters recurse on the calls of the program, up to the stack overflow.
+const INTERPRETER_STACK_SIZE: usize = 1 << 30;
+/// Stack of the main thread on most systems.
+const DEFAULT_STACK_SIZE: usize = 8 << 20;
+
+/// Both interpreters stop with the same error, located in the function by the statement of the source
+/// for the typer, and by the label for RTL.
+fn _test_runtime_error(path: &str, message: &str, location: &str) {
+    println!("File {}", path);
+
+    let file = read_to_string(path).expect("Failed to read file");
+    let parsed = parse_file(&file).expect("Failed to parse");
+    let typed = parsed.minic_typ().expect("Failed to typ file");
+    let rtl = typed.minic_rtl().expect("Failed to rtl file");
+
+    let error = typed.minic_interp().expect_err("The typer interpreter didn't fail");
+    assert_eq!(message, error.to_string());
+    let statement = error.anchor().and_then(|anchor| Location::of(&file, anchor)).expect("Missing location");
+    assert_eq!(location, statement.to_string());
+
+    let error = rtl.minic_interp().expect_err("The RTL interpreter didn't fail").to_string();
+    assert!(error.starts_with(&format!("{} at L", message)), "Unexpected error : {}", error);
+}
+
+
+test_runtime_error!(
+    deref_null: "tests/source/exec-fail/deref_null.c" => "null pointer dereference in function `main`" at "6:3",
+    division_by_zero: "tests/source/exec-fail/division_by_zero1.c" => "division by zero in function `main`" at "2:3",
+    division_overflow: "tests/source/exec-fail/division_overflow.c" => "division overflow in function `main`" at "10:3",
+    field_out_of_bounds: "tests/source/exec-fail/field_out_of_bounds.c" => "offset 8 is out of the structure allocated at 1 in function `main`" at "7:3",
+    stack_overflow: "tests/source/exec-fail/stack_overflow.c" => "stack overflow in function `f`" at "2:3",
+);
+
+/// The address given to `%s` is so far below the strings that its offset from them overflows.
+#[test]
+fn printf_string_out_of_range() {
+    let file = read_to_string("tests/source/exec-fail/printf_string.c").expect("Failed to read file");
+    let parsed = parse_file(&file).expect("Failed to parse");
+    let typed = parsed.minic_typ().expect("Failed to typ file");
+    let rtl = typed.minic_rtl().expect("Failed to rtl file");
+
+    let error = typed.minic_interp().expect_err("The typer interpreter didn't fail").to_string();
+    assert!(error.contains("Invalid printf string argument"), "Unexpected error : {}", error);
+
+    let error = rtl.minic_interp().expect_err("The RTL interpreter didn't fail").to_string();
+    assert!(error.contains("Invalid printf string argument"), "Unexpected error : {}", error);
+
+    let ertl = rtl.minic_ertl().expect("Failed to ertl file");
+    let error = ertl.minic_interp().expect_err("The ERTL interpreter didn't fail").to_string();
+    assert!(error.contains("Invalid printf string argument"), "Unexpected error : {}", error);
+
+    let ltl = ertl.minic_ltl().expect("Failed to ltl file");
+    let error = ltl.minic_interp().expect_err("The LTL interpreter didn't fail").to_string();
+    assert!(error.contains("Invalid printf string argument"), "Unexpected error : {}", error);
+
+    let program = ltl.minic_linearise().expect("Failed to linearise file");
+    let error = program.minic_emulate().expect_err("The emulator didn't fail").to_string();
+    assert!(error.contains("Invalid printf string argument"), "Unexpected error : {}", error);
+}
+
+/// Runs the RTL interpreter on the stack of the test thread.
+fn interp_rtl(path: &str, limits: Limits) -> RtlInterpreterResult<Execution> {
+    let file = read_to_string(path).expect("Failed to read file");
+    let parsed = parse_file(&file).expect("Failed to parse");
+    let typed = parsed.minic_typ().expect("Failed to typ file");
+    let rtl = typed.minic_rtl().expect("Failed to rtl file");
+
+    rtl.minic_interp_with(Stdin::default(), limits)
+}
+
+#[test]
+fn out_of_fuel() {
+    let limits = Limits { fuel: 10_000, ..Limits::default() };
+    let error = interp_rtl("tests/source/limits/loop_forever.c", limits).expect_err("The loop didn't stop").to_string();
+    assert!(error.starts_with("out of fuel after 10000 instructions in function `main` at L"), "Unexpected error : {}", error);
+}
+
+#[test]
+fn call_depth_limit() {
+    let limits = Limits { call_depth: 3, ..Limits::default() };
+    let error = interp_rtl("tests/source/exec/fact_rec.c", limits).expect_err("The calls didn't stop").to_string();
+    assert!(error.starts_with("stack overflow in function `fact_rec` at L"), "Unexpected error : {}", error);
+}
+
+#[test]
+fn heap_limit() {
+    let limits = Limits { heap_size: 16, ..Limits::default() };
+    let error = interp_rtl("tests/source/exec/abr.c", limits).expect_err("The allocations didn't stop").to_string();
+    assert!(error.starts_with("out of memory, more than 16 bytes allocated in function `make` at L"), "Unexpected error : {}", error);
+}
+
+/// The structure local to a function is freed when it returns, so the calls only need room for one.
+#[test]
+fn heap_limit_stack_structures() {
+    let limits = Limits { heap_size: 16, ..Limits::default() };
+    let execution = interp_rtl("tests/source/limits/struct_calls.c", limits).expect("Failed to interp RTL");
+    assert_eq!("!\n", execution.stdout.to_string());
+}
+
+#[test]
+fn stack_overflow_on_default_stack() {
+    let error = std::thread::Builder::new()
+        .stack_size(DEFAULT_STACK_SIZE)
+        .spawn(|| {
+            let file = read_to_string("tests/source/exec-fail/stack_overflow.c").expect("Failed to read file");
+            let parsed = parse_file(&file).expect("Failed to parse");
+            let typed = parsed.minic_typ().expect("Failed to typ file");
+            typed.minic_interp().expect_err("The typer interpreter didn't fail").to_string()
+        })
+        .expect("Failed to start test")
+        .join()
+        .expect("The typer interpreter overflowed the stack of the thread");
+    assert_eq!("stack overflow in function `f`", error);
+
+    let error = std::thread::Builder::new()
+        .stack_size(DEFAULT_STACK_SIZE)
+        .spawn(|| {
+            interp_rtl("tests/source/exec-fail/stack_overflow.c", Limits::default())
+                .expect_err("The RTL interpreter didn't fail")
+                .to_string()
+        })
+        .expect("Failed to start test")
+        .join()
+        .expect("The RTL interpreter overflowed the stack of the thread");
+    assert!(error.starts_with("stack overflow in function `f` at L"), "Unexpected error : {}", error);
+}
+
+#[test]
+fn long_loop_within_default_limits() {
+    let execution = interp_rtl("tests/source/limits/long_loop.c", Limits::default()).expect("Failed to interp RTL");
+    assert_eq!("!\n", execution.stdout.to_string());
+}
+
+#[test]
+fn cli_fuel() {
+    let output = Command::new(env!("CARGO_BIN_EXE_rust-mini-c"))
+        .arg("--interp")
+        .arg("--fuel")
+        .arg("10000")
+        .arg("tests/source/limits/loop_forever.c")
+        .output()
+        .expect("Failed to start the compiler");
+
+    assert_eq!(Some(1), output.status.code());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("error: out of fuel after 10000 instructions in function `main` at L"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rust-mini-c"))
+        .arg("--interp=typer")
+        .arg("--fuel")
+        .arg("10000")
+        .arg("tests/source/limits/loop_forever.c")
+        .output()
+        .expect("Failed to start the compiler");
+
+    assert_eq!(Some(2), output.status.code());
+}
+
+#[test]
+fn cli_max_call_depth() {
+    let output = Command::new(env!("CARGO_BIN_EXE_rust-mini-c"))
+        .arg("--interp")
+        .arg("--max-call-depth")
+        .arg("100000")
+        .arg("tests/source/exec-fail/stack_overflow.c")
+        .output()
+        .expect("Failed to start the compiler");
+
+    assert_eq!(Some(1), output.status.code());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("error: stack overflow in function `f` at L"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rust-mini-c"))
+        .arg("--interp")
+        .arg("--max-call-depth")
+        .arg("100001")
+        .arg("tests/source/exec-fail/stack_overflow.c")
+        .output()
+        .expect("Failed to start the compiler");
+
+    assert_eq!(Some(2), output.status.code());
+}
+
+#[test]
+fn cli_exit_code() {
+    let output = Command::new(env!("CARGO_BIN_EXE_rust-mini-c"))
+        .arg("--interp")
+        .arg("tests/source/exec-fail/division_by_zero1.c")
+        .output()
+        .expect("Failed to start the compiler");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("error: division by zero in function `main` at L"));
+}
+
+#[test]
+fn cli_interp() {
+    let output = Command::new(env!("CARGO_BIN_EXE_rust-mini-c"))
+        .arg("--interp")
+        .arg("tests/source/exec/hello_world.c")
+        .output()
+        .expect("Failed to start the compiler");
+
+    assert!(output.status.success());
+    let expected = read_to_string("tests/source/exec/hello_world.out").expect("Failed to read result file");
+    assert_eq!(expected.replace("\r", ""), String::from_utf8_lossy(&output.stdout));
+}
@@ -0,0 +1,12 @@
+int main() {
+  int min;
+  int i;
+  min = 1;
+  i = 0;
+  while (i < 63) {
+    min = min * 2;
+    i = i + 1;
+  }
+  putchar(min / -1);
+  return 0;
+}
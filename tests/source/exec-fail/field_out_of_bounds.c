@@ -0,0 +1,9 @@
+struct A { int a; };
+struct B { int a; int b; };
+
+int main() {
+  struct B *p;
+  p = malloc(sizeof(struct A));
+  p->b = 1;
+  return 0;
+}
@@ -0,0 +1,6 @@
+int main() {
+  int m;
+  m = -9223372036854775807 - 1;
+  printf("%s\n", m);
+  return 0;
+}
@@ -0,0 +1,7 @@
+int f(int n) {
+  return f(n + 1);
+}
+
+int main() {
+  return f(0);
+}
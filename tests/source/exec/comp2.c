@@ -0,0 +1,16 @@
+// The result of a comparison is 0 or 1 in all the bits of the register,
+// even when the operands don't fit in a byte
+
+int main() {
+  int a, b, c;
+  a = 1000;
+  b = 2000;
+  if (b < a)  {putchar('A');} else {putchar('a');} // a
+  if (a >= b) {putchar('B');} else {putchar('b');} // b
+  if (a == b) {putchar('C');} else {putchar('c');} // c
+  if (a != 1000) {putchar('D');} else {putchar('d');} // d
+  c = a > b;
+  if (c) {putchar('E');} else {putchar('e');} // e
+  putchar(10);
+  return 0;
+}
@@ -0,0 +1,22 @@
+struct P {
+  const int id;
+  int x;
+};
+
+int main() {
+  const int a = 'A', b;
+  const int c = a + 2;
+  struct P *p;
+  p = malloc(sizeof(struct P));
+  p->x = a + 1;
+  putchar(a);
+  putchar(p->x);
+  putchar(c);
+  {
+    const int a = 'Z';
+    putchar(a);
+  }
+  putchar(a);
+  putchar(10);
+  return 0;
+}
@@ -0,0 +1,33 @@
+struct S {
+  int a;
+  struct S *next;
+};
+
+int twice(int x) {
+  return 2 * x;
+}
+
+int main() {
+  int x = 'A', y = twice(x) - x + 1;
+  struct S *p = malloc(sizeof(struct S)), *q = p;
+  int (*f)(int) = twice;
+  putchar(x);
+  putchar(y);
+  q->a = 'C';
+  putchar(p->a);
+  {
+    int z = x + 3;
+    int x = z + 1;
+    putchar(z);
+    putchar(x);
+  }
+  putchar(f(x) - x + 5);
+  {
+    int y = x;
+    int x = 'Z';
+    putchar(y);
+    putchar(x);
+  }
+  putchar(10);
+  return 0;
+}
@@ -0,0 +1,50 @@
+struct Op {
+  int (*apply)(int, int);
+  struct Op *next;
+};
+
+int add(int a, int b) { return a + b; }
+int sub(int a, int b) { return a - b; }
+
+int fold(int (*f)(int x, int y), int init, int n) {
+  int acc;
+  acc = init;
+  while (n > 0) {
+    acc = f(acc, n);
+    n = n - 1;
+  }
+  return acc;
+}
+
+int many(int a, int b, int c, int d, int e, int f, int g, int h) {
+  return a + b + c + d + e + f + g + h;
+}
+
+int main() {
+  int (*print)(int);
+  int (*eight)(int, int, int, int, int, int, int, int);
+  struct Op *ops;
+  struct Op *o;
+  print = &putchar;
+  print('A');
+  print(fold(add, 'A', 3));
+  print(fold(&sub, 'Z', 3));
+  eight = many;
+  print(eight(8, 8, 8, 8, 8, 8, 8, 9));
+  ops = malloc(sizeof(struct Op));
+  ops->apply = add;
+  ops->next = malloc(sizeof(struct Op));
+  ops->next->apply = sub;
+  ops->next->next = 0;
+  o = ops;
+  while (o) {
+    print(o->apply('M', 2));
+    o = o->next;
+  }
+  if (print == putchar) print('=');
+  if (eight != 0) print('!');
+  eight = 0;
+  if (!eight) print('0');
+  print(10);
+  return 0;
+}
@@ -0,0 +1,15 @@
+/* copies the input, and gives the number of characters read */
+int main() {
+  int c;
+  int n;
+  n = 0;
+  c = getchar();
+  while (c != -1) {
+    putchar(c);
+    n = n + 1;
+    c = getchar();
+  }
+  if (c < 0) putchar('!');
+  putchar(10);
+  return n;
+}
@@ -0,0 +1,12 @@
+/* reads the end of the input through a pointer to getchar, which must still give -1 */
+int main() {
+  int (*read)();
+  int c;
+  read = &getchar;
+  c = read();
+  if (c == -1) putchar('!');
+  if (c < 0) putchar('<');
+  if (read == getchar) putchar('=');
+  putchar(10);
+  return 0;
+}
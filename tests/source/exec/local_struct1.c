@@ -0,0 +1,29 @@
+struct P { int x; int y; };
+
+int sum(struct P *p) {
+  return p->x + p->y;
+}
+
+int depth(int n) {
+  struct P cell;
+  cell.x = n;
+  cell.y = '0';
+  if (n > 0) depth(n - 1);
+  putchar(cell.x + cell.y);
+  return 0;
+}
+
+int main() {
+  struct P a;
+  struct P b;
+  a.x = 'A';
+  a.y = 0;
+  b.x = 1;
+  b.y = 'A';
+  putchar(a.x + a.y);
+  putchar(sum(&b));
+  putchar(10);
+  depth(3);
+  putchar(10);
+  return 0;
+}
@@ -0,0 +1,19 @@
+int first(int n, ...) {
+  return n;
+}
+
+int main() {
+  int (*p)(int, ...);
+  int n;
+  n = printf("%d %d %d\n", 42, -7, 0);
+  printf("%d\n", n);
+  printf("%c%c%c\n", 'a', 'b', 10 + 'a');
+  printf("[%s] [%s]\n", "hello", "");
+  printf("%x %x %%\n", 255, 3054);
+  printf("%d %d %d %d %d %d %d\n", 1, 2, 3, 4, 5, 6, 7);
+  printf("%d %d %d %d %d %d %d %d\n", 1, 2, 3, 4, 5, 6, 7, 8);
+  p = first;
+  printf("%d %d\n", first(1, 2, 3), p(4, 5, 6, 7, 8, 9, 10));
+  printf("tab\there\n");
+  return 0;
+}
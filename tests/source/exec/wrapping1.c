@@ -0,0 +1,22 @@
+int main() {
+  int min;
+  int max;
+  int i;
+  min = 1;
+  i = 0;
+  while (i < 63) {
+    min = min * 2;
+    i = i + 1;
+  }
+  max = min - 1;
+  if (min < 0) putchar('a');
+  if (max > 0) putchar('b');
+  if (max + 1 == min) putchar('c');
+  if (-min == min) putchar('d');
+  if (min * 2 == 0) putchar('e');
+  if (max * max == 1) putchar('f');
+  if (min / 1 == min) putchar('g');
+  if (max / -1 == -max) putchar('h');
+  putchar(10);
+  return 0;
+}
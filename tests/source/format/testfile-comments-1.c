@@ -0,0 +1,16 @@
+/* Header
+   comment */
+struct S { int a; // first field
+  int b; /* second */ };
+
+// main function
+int main() { // opening
+  int x = 1; /* x */ int y;
+  // before if
+  if (x) { y = 2; } // after if
+  else y = 3;
+  x = x + /* inside */ 1;
+  return 0;
+  // before end
+}
+// trailing file comment
@@ -0,0 +1,12 @@
+int f(int a, /* b */ int b) {
+  int x /* first */, y; // xy
+  while (a) // loop
+    a = a - 1;
+  if (a) x = 1; // then
+  else { // else
+    x = (2 /* two */);
+  }
+  x = f(1,
+    // second
+    2);
+  return (x); /* end */ }
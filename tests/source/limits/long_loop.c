@@ -0,0 +1,10 @@
+int main() {
+  int i;
+  int limit;
+  i = 0;
+  limit = 1000 * 1000;
+  while (i < limit) i = i + 1;
+  if (i == limit) putchar(33);
+  putchar(10);
+  return 0;
+}
@@ -0,0 +1,22 @@
+struct S { int a; int b; };
+
+int f(int x) {
+  struct S s;
+  s.a = x;
+  s.b = 1;
+  return s.a + s.b;
+}
+
+int main() {
+  int i;
+  int sum;
+  i = 0;
+  sum = 0;
+  while (i < 1000) {
+    sum = sum + f(i);
+    i = i + 1;
+  }
+  if (sum == 500500) putchar(33);
+  putchar(10);
+  return 0;
+}
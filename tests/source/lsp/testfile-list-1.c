@@ -0,0 +1,24 @@
+struct cell {
+    int value;
+    struct cell *next;
+};
+
+int length(struct cell *list) {
+    int n;
+    n = 0;
+    while (list) {
+        n = n + 1;
+        list = list->next;
+    }
+    return n;
+}
+
+int main() {
+    struct cell *c;
+    int unused;
+    c = malloc(sizeof(struct cell));
+    c->value = 1;
+    c->next = 0;
+    putchar(48);
+    return length(c);
+}
@@ -0,0 +1,5 @@
+struct S { int a; };
+int main() {
+  struct S s;
+  return s;
+}
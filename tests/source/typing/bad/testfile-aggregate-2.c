@@ -0,0 +1,6 @@
+struct S { int a; };
+int main() {
+  struct S s;
+  s.a = &s;
+  return 0;
+}
@@ -0,0 +1,10 @@
+struct S {
+  const int a;
+};
+
+int main() {
+  struct S *s;
+  s = malloc(sizeof(struct S));
+  s->a = 1;
+  return 0;
+}
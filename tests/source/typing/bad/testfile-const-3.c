@@ -0,0 +1,9 @@
+struct S {
+  int a;
+};
+
+int main() {
+  struct S *p;
+  const int x = p;
+  return 0;
+}
@@ -0,0 +1,9 @@
+struct S {
+  const int a;
+};
+
+int main() {
+  struct S s;
+  s.a = 1;
+  return 0;
+}
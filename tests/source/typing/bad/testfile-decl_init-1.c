@@ -0,0 +1,5 @@
+int main() {
+  x = 1;
+  int x;
+  return 0;
+}
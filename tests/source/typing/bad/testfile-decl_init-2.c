@@ -0,0 +1,8 @@
+struct S {
+  int a;
+};
+
+int main() {
+  struct S *p = 'a';
+  return 0;
+}
@@ -0,0 +1,4 @@
+int main() {
+  int x = 1, x = 2;
+  return 0;
+}
@@ -0,0 +1,6 @@
+int f(int a) { return a; }
+int main() {
+  int (*g)(int, int);
+  g = f;
+  return 0;
+}
@@ -0,0 +1,5 @@
+int main() {
+  int (*g)(int);
+  g = &putchar;
+  return g(1, 2);
+}
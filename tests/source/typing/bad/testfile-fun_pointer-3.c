@@ -0,0 +1,5 @@
+int main() {
+  int x;
+  x = 1;
+  return x(1);
+}
@@ -0,0 +1,4 @@
+int main() {
+  printf(42);
+  return 0;
+}
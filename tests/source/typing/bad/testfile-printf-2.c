@@ -0,0 +1,5 @@
+int main() {
+  int x;
+  x = "x";
+  return 0;
+}
@@ -0,0 +1,15 @@
+int main() {
+  const int debug = 0;
+  int x;
+  x = 1;
+  if (debug)
+    putchar(68);
+  if (1)
+    putchar(65);
+  while (0)
+    putchar(66);
+  while (1) {
+    if (x)
+      return 0;
+  }
+}
@@ -0,0 +1,30 @@
+int f(int x) {
+  while (x) {
+    return 1;
+  }
+}
+
+int g() {
+  while (1) {
+  }
+}
+
+int h(int x) {
+  if (x)
+    return 1;
+  else {
+    x = 2;
+  }
+}
+
+int k(int x) {
+  if (x)
+    return 1;
+  else
+    return 2;
+  putchar('a');
+}
+
+int main() {
+  return 0;
+}
@@ -0,0 +1,30 @@
+struct L { int x; struct L *next; };
+
+int length(struct L *l) {
+  int n;
+  n = 0;
+  while (l != 0) {
+    n = n + 1;
+    l = l->next;
+  }
+  return n;
+}
+
+int first(struct L *l) {
+  if (l == 0)
+    putchar(l->x);
+  if (!l || l->x)
+    return 0;
+  return l->x;
+}
+
+int main() {
+  struct L *l;
+  struct L *m;
+  l = 0;
+  if (length(l))
+    l = malloc(sizeof(struct L));
+  m = l;
+  m->next = 0;
+  return first(m);
+}
@@ -0,0 +1,18 @@
+int f(int x) {
+  if (x) {
+    return 1;
+    x = 2;
+  }
+  while (x) {
+    return 2;
+    ;
+  }
+  return 0;
+  putchar(x);
+}
+
+int main() {
+  putchar(65);
+  return f(0);
+  putchar(66);
+}
@@ -0,0 +1,21 @@
+int f(int a, int b) {
+  int x;
+  int y;
+  x = a;
+  y = 1;
+  return x;
+}
+
+int g(int c) {
+  return g(c - 1);
+}
+
+int k() {
+  return 0;
+}
+
+int main() {
+  int (*p)();
+  p = &k;
+  return f(1, 2) + p();
+}
@@ -0,0 +1,10 @@
+struct S { int a; int b; };
+
+int main() {
+  struct S *p;
+  p = malloc(sizeof(struct S));
+  p->a = 1;
+  if (p->a)
+    p->b = 2;
+  return p->a + p->b;
+}
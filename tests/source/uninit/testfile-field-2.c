@@ -0,0 +1,15 @@
+struct S { int a; int b; };
+
+int init(struct S *p) {
+  p->b = 0;
+  return 0;
+}
+
+int main() {
+  struct S s;
+  struct S *p;
+  p = malloc(sizeof(struct S));
+  putchar(65 + s.a);
+  init(p);
+  return p->b;
+}
@@ -0,0 +1,20 @@
+int f(int c) {
+  int x;
+  int y;
+  if (c)
+    x = 1;
+  y = 2;
+  return x + y;
+}
+
+int main() {
+  int i;
+  int s;
+  s = 0;
+  i = 0;
+  while (i < 10) {
+    s = s + i;
+    i = i + 1;
+  }
+  return f(s);
+}
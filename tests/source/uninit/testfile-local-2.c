@@ -0,0 +1,12 @@
+int main() {
+  int x;
+  int y;
+  while (x) {
+    y = 1;
+  }
+  if (y)
+    x = 1;
+  else
+    x = 2;
+  return x;
+}
@@ -0,0 +1,142 @@
+use std::fs::read_to_string;
+use std::process::Command;
+
+use rust_mini_c::common::Stdin;
+use rust_mini_c::parser::parse_file;
+use rust_mini_c::rtl::interpreter::Limits;
+
+macro_rules! test_calls {
+    ($($name:ident: $path:literal,)*) => {
+        $(
+        #[test]
+        fn $name() {
+            _test_calls($path)
+        }
+        )*
+
+    };
+}
+
+/// Both interpreters call the functions as many times, each statement running a few instructions at least.
+fn _test_calls(path: &str) {
+    println!("File {}", path);
+
+    let file = read_to_string(path).expect("Failed to read file");
+    let parsed = parse_file(&file).expect("Failed to parse");
+    let typed = parsed.minic_typ().expect("Failed to typ file");
+    let rtl = typed.minic_rtl().expect("Failed to rtl file");
+
+    let (result, typer_profile) = typed.minic_trace(Stdin::default(), None::<Vec<u8>>);
+    result.expect("The typer interpreter failed");
+    let (result, rtl_profile) = rtl.minic_trace(Stdin::default(), Limits::default(), None::<Vec<u8>>);
+    result.expect("The RTL interpreter failed");
+
+    assert_eq!(typer_profile.funs().len(), rtl_profile.funs().len());
+    for (name, fun) in typer_profile.funs() {
+        assert_eq!(fun.calls, rtl_profile.fun(name).calls, "Calls of {} differ", name);
+        assert!(fun.steps <= rtl_profile.fun(name).steps, "Fewer instructions than statements in {}", name);
+    }
+}
+
+/// Runs the program with both interpreters, and gives their traces.
+fn traces(path: &str) -> (String, String) {
+    let file = read_to_string(path).expect("Failed to read file");
+    let parsed = parse_file(&file).expect("Failed to parse");
+    let typed = parsed.minic_typ().expect("Failed to typ file");
+    let rtl = typed.minic_rtl().expect("Failed to rtl file");
+
+    let mut typer_trace = vec![];
+    let (result, _) = typed.minic_trace(Stdin::default(), Some(&mut typer_trace));
+    result.expect("The typer interpreter failed");
+    let mut rtl_trace = vec![];
+    let (result, rtl_profile) = rtl.minic_trace(Stdin::default(), Limits::default(), Some(&mut rtl_trace));
+    result.expect("The RTL interpreter failed");
+    let steps = rtl_profile.steps();
+
+    let rtl_trace = String::from_utf8(rtl_trace).expect("Invalid trace");
+    assert_eq!(steps, rtl_trace.lines().count() as u64);
+    (String::from_utf8(typer_trace).expect("Invalid trace"), rtl_trace)
+}
+
+test_calls!(
+    call_1: "tests/source/exec/call1.c",
+    fact_imp: "tests/source/exec/fact_imp.c",
+    fact_rec: "tests/source/exec/fact_rec.c",
+    fun_pointer_1: "tests/source/exec/fun_pointer1.c",
+    pascal: "tests/source/exec/pascal.c",
+    print_int: "tests/source/exec/print_int.c",
+    while_1: "tests/source/exec/while1.c",
+);
+
+#[test]
+fn profile_fact_rec() {
+    let file = read_to_string("tests/source/exec/fact_rec.c").expect("Failed to read file");
+    let parsed = parse_file(&file).expect("Failed to parse");
+    let typed = parsed.minic_typ().expect("Failed to typ file");
+
+    let (_, profile) = typed.minic_trace(Stdin::default(), None::<Vec<u8>>);
+
+    assert_eq!(1, profile.fun("main").calls);
+    assert_eq!(7, profile.fun("fact_rec").calls);
+    // One test, and a return, per call
+    assert_eq!(14, profile.fun("fact_rec").steps);
+    assert_eq!(0, profile.fun("putchar").calls);
+}
+
+#[test]
+fn trace_statements() {
+    let (typer_trace, _) = traces("tests/source/exec/fact_rec.c");
+    let lines = typer_trace.lines().collect::<Vec<_>>();
+
+    assert_eq!(Some(&"main: if (fact_rec(0) == 1)"), lines.first());
+    assert_eq!(Some(&"fact_rec: if (n <= 1)"), lines.get(1));
+    assert_eq!(Some(&"fact_rec: return 1;"), lines.get(2));
+    assert_eq!(Some(&"main: return 0;"), lines.last());
+}
+
+#[test]
+fn trace_instructions() {
+    let (_, rtl_trace) = traces("tests/source/exec/fact_rec.c");
+
+    assert!(rtl_trace.starts_with("main L"), "{}", rtl_trace);
+    assert!(rtl_trace.contains(" call "), "{}", rtl_trace);
+    assert!(rtl_trace.lines().all(|line| line.starts_with("main L") || line.starts_with("fact_rec L")), "{}", rtl_trace);
+}
+
+#[test]
+fn trace_loops() {
+    let (typer_trace, _) = traces("tests/source/exec/while1.c");
+    let tests = typer_trace.lines().filter(|line| line.contains("while (")).count();
+    let runs = typer_trace.lines().count();
+
+    assert!(tests > 1, "{}", typer_trace);
+    assert!(runs > tests, "{}", typer_trace);
+}
+
+#[test]
+fn cli_profile() {
+    let output = Command::new(env!("CARGO_BIN_EXE_rust-mini-c"))
+        .arg("--interp=typer")
+        .arg("--profile")
+        .arg("tests/source/exec/fact_rec.c")
+        .output()
+        .expect("Failed to start the compiler");
+
+    assert!(output.status.success());
+    assert_eq!("123\n", String::from_utf8_lossy(&output.stdout));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let row = stderr.lines().find(|line| line.starts_with("fact_rec")).expect("No profile of fact_rec");
+    assert_eq!(vec!["fact_rec", "7", "14"], row.split_whitespace().collect::<Vec<_>>());
+}
+
+#[test]
+fn cli_trace_requires_interp() {
+    let output = Command::new(env!("CARGO_BIN_EXE_rust-mini-c"))
+        .arg("--trace")
+        .arg("tests/source/exec/fact_rec.c")
+        .arg("a.s")
+        .output()
+        .expect("Failed to start the compiler");
+
+    assert!(!output.status.success());
+}
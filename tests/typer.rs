@@ -3,6 +3,8 @@
 use std::fs::read_to_string;
 
 use rust_mini_c::parser::parse_file;
+use rust_mini_c::typer::structure::File;
+use rust_mini_c::warning::DisplayableWarning;
 
 macro_rules! test_typing_bad {
     ($($name:ident: $path:literal,)*) => {
@@ -50,7 +52,7 @@ fn _test_typing_good(path: &str) {
     match parse_file(&file)
         .expect("Failed to parse")
         .minic_typ() {
-        Ok(_) => {}
+        Ok(file) => _test_typing_round_trip(&file),
         Err(err) => {
             println!("{:?}", err);
             assert!(false);
@@ -58,6 +60,43 @@ fn _test_typing_good(path: &str) {
     }
 }
 
+/// The typed file printed, with or without its scopes, types again to the same file.
+fn _test_typing_round_trip(file: &File) {
+    for printed in [format!("{:#}", file), format!("{}", file)] {
+        println!("{}", printed);
+
+        let reparsed = parse_file(&printed).expect("Failed to parse the printed file");
+        let retyped = reparsed.minic_typ().expect("Failed to type the printed file");
+        assert_eq!(retyped.to_string(), printed);
+    }
+}
+
+macro_rules! test_typing_warning {
+    ($($name:ident: $path:literal -> $warnings:expr,)*) => {
+        $(
+        #[test]
+        fn $name() {
+            _test_typing_warning($path, $warnings);
+        }
+        )*
+
+    };
+}
+
+fn _test_typing_warning(path: &str, expected: Vec<&str>) {
+    println!("File {}", path);
+
+    let content = read_to_string(path).expect("Failed to read file");
+    let file = parse_file(&content).expect("Failed to parse");
+    let file = file.minic_typ().expect("Failed to type");
+
+    let warnings: Vec<String> = file
+        .warnings()
+        .iter()
+        .map(|x| DisplayableWarning::new(x, &content).to_string())
+        .collect();
+    assert_eq!(warnings, expected);
+}
 
 mod good {
     use crate::_test_typing_good;
@@ -88,6 +127,9 @@ mod good {
     char_3: "tests/source/exec/char3.c",
     char_4: "tests/source/exec/char4.c",
     comp_1: "tests/source/exec/comp1.c",
+    comp_2: "tests/source/exec/comp2.c",
+    const_1: "tests/source/exec/const1.c",
+    decl_init_1: "tests/source/exec/decl_init1.c",
     dllist: "tests/source/exec/dllist.c",
     fact: "tests/source/exec/fact.c",
     fact_imp: "tests/source/exec/fact_imp.c",
@@ -102,6 +144,9 @@ mod good {
     for_4: "tests/source/exec/for4.c",
     for_5: "tests/source/exec/for5.c",
     for_6: "tests/source/exec/for6.c",
+    fun_pointer_1: "tests/source/exec/fun_pointer1.c",
+    getchar_1: "tests/source/exec/getchar1.c",
+    getchar_2: "tests/source/exec/getchar2.c",
     hello_world: "tests/source/exec/hello_world.c",
     if_1: "tests/source/exec/if1.c",
     init_1: "tests/source/exec/init1.c",
@@ -110,6 +155,7 @@ mod good {
     local_1: "tests/source/exec/local1.c",
     local_2: "tests/source/exec/local2.c",
     local_3: "tests/source/exec/local3.c",
+    local_struct_1: "tests/source/exec/local_struct1.c",
     mandelbrot: "tests/source/exec/mandelbrot.c",
     many: "tests/source/exec/many.c",
     not_1: "tests/source/exec/not1.c",
@@ -117,6 +163,7 @@ mod good {
     or_1: "tests/source/exec/or1.c",
     pascal: "tests/source/exec/pascal.c",
     print_int: "tests/source/exec/print_int.c",
+    printf_1: "tests/source/exec/printf1.c",
     putchar_octal: "tests/source/exec/putchar-octal1.c",
     putchar: "tests/source/exec/putchar1.c",
     putchar_hexa: "tests/source/exec/putchar_hexa1.c",
@@ -137,6 +184,8 @@ mod bad {
     use crate::_test_typing_bad;
 
     test_typing_bad!(
+    aggregate_1: "tests/source/typing/bad/testfile-aggregate-1.c",
+    aggregate_2: "tests/source/typing/bad/testfile-aggregate-2.c",
     arith_1: "tests/source/typing/bad/testfile-arith-1.c",
     arith_2: "tests/source/typing/bad/testfile-arith-2.c",
     arith_3: "tests/source/typing/bad/testfile-arith-3.c",
@@ -146,8 +195,20 @@ mod bad {
     arrow_4: "tests/source/typing/bad/testfile-arrow-4.c",
     call_1: "tests/source/typing/bad/testfile-call-1.c",
     call_2: "tests/source/typing/bad/testfile-call-2.c",
+    const_1: "tests/source/typing/bad/testfile-const-1.c",
+    const_2: "tests/source/typing/bad/testfile-const-2.c",
+    const_3: "tests/source/typing/bad/testfile-const-3.c",
+    const_4: "tests/source/typing/bad/testfile-const-4.c",
+    decl_init_1: "tests/source/typing/bad/testfile-decl_init-1.c",
+    decl_init_2: "tests/source/typing/bad/testfile-decl_init-2.c",
+    decl_init_3: "tests/source/typing/bad/testfile-decl_init-3.c",
+    fun_pointer_1: "tests/source/typing/bad/testfile-fun_pointer-1.c",
+    fun_pointer_2: "tests/source/typing/bad/testfile-fun_pointer-2.c",
+    fun_pointer_3: "tests/source/typing/bad/testfile-fun_pointer-3.c",
     missing_main_1: "tests/source/typing/bad/testfile-missing_main-1.c",
     missing_main_2: "tests/source/typing/bad/testfile-missing_main-2.c",
+    printf_1: "tests/source/typing/bad/testfile-printf-1.c",
+    printf_2: "tests/source/typing/bad/testfile-printf-2.c",
     redef_1: "tests/source/typing/bad/testfile-redef-1.c",
     redef_2: "tests/source/typing/bad/testfile-redef-2.c",
     redef_3: "tests/source/typing/bad/testfile-redef-3.c",
@@ -171,4 +232,45 @@ mod bad {
     undef_var_2: "tests/source/typing/bad/testfile-undef_var-2.c",
     undef_var_3: "tests/source/typing/bad/testfile-undef_var-3.c",
 );
-}
\ No newline at end of file
+}
+
+mod warning {
+    use crate::_test_typing_warning;
+
+    test_typing_warning!(
+    constant_condition_1: "tests/source/typing/warning/testfile-constant_condition-1.c" -> vec![
+        "warning[W0007]: 5:7: condition of `if` is always false in `main` [-Wconstant-condition]",
+        "warning[W0007]: condition of `if` is always true in `main` [-Wconstant-condition]",
+        "warning[W0007]: condition of `while` is always false in `main` [-Wconstant-condition]",
+    ],
+    missing_return_1: "tests/source/typing/warning/testfile-missing_return-1.c" -> vec![
+        "warning[W0001]: 4:1: control reaches end of non-void function `f` after `if (...)` [-Wmissing-return]",
+    ],
+    missing_return_2: "tests/source/typing/warning/testfile-missing_return-2.c" -> vec![
+        "warning[W0001]: 5:1: control reaches end of non-void function `f` after `while (...)` [-Wmissing-return]",
+        "warning[W0001]: 18:1: control reaches end of non-void function `h` after `x = ...;` [-Wmissing-return]",
+        "warning[W0005]: 1:5: function `f` is never called [-Wunused-function]",
+        "warning[W0005]: 7:5: function `g` is never called [-Wunused-function]",
+        "warning[W0005]: 12:5: function `h` is never called [-Wunused-function]",
+        "warning[W0005]: 20:5: function `k` is never called [-Wunused-function]",
+        "warning[W0006]: 25:3: statement `putchar(...);` is never executed in `k` [-Wunreachable-code]",
+    ],
+    null_deref_1: "tests/source/typing/warning/testfile-null_deref-1.c" -> vec![
+        "warning[W0008]: 15:13: `l` may be null in `l->x` in `first`, after `l == 0` is true [-Wnull-dereference]",
+        "warning[W0008]: 28:3: `m` may be null in `m->next` in `main`, after `l = 0`, then `m = l` [-Wnull-dereference]",
+    ],
+    exec_fail_deref_null: "tests/source/exec-fail/deref_null.c" -> vec![
+        "warning[W0008]: 6:11: `p` may be null in `p->a` in `main`, after `p = 0` [-Wnull-dereference]",
+    ],
+    unreachable_1: "tests/source/typing/warning/testfile-unreachable-1.c" -> vec![
+        "warning[W0006]: 4:5: statement `x = ...;` is never executed in `f` [-Wunreachable-code]",
+        "warning[W0006]: 11:3: statement `putchar(...);` is never executed in `f` [-Wunreachable-code]",
+        "warning[W0006]: 17:3: statement `putchar(...);` is never executed in `main` [-Wunreachable-code]",
+    ],
+    unused_1: "tests/source/typing/warning/testfile-unused-1.c" -> vec![
+        "warning[W0004]: 1:18: parameter `b` is never read in `f` [-Wunused-parameter]",
+        "warning[W0003]: 3:7: variable `y` is never read in `f` [-Wunused-variable]",
+        "warning[W0005]: 9:5: function `g` is never called [-Wunused-function]",
+    ],
+);
+}
@@ -0,0 +1,57 @@
+use std::fs::read_to_string;
+
+use rust_mini_c::parser::parse_file;
+use rust_mini_c::warning::DisplayableWarning;
+
+macro_rules! test_uninit {
+    ($($name:ident: $path:literal -> $warnings:expr,)*) => {
+        $(
+        #[test]
+        fn $name() {
+            _test_uninit($path, $warnings);
+        }
+        )*
+
+    };
+}
+
+fn _test_uninit(path: &str, expected: Vec<&str>) {
+    println!("File {}", path);
+
+    let content = read_to_string(path).expect("Failed to read file");
+    let file = parse_file(&content).expect("Failed to parse");
+    let file = file.minic_typ().expect("Failed to type");
+    let file = file.minic_rtl().expect("Failed to rtl");
+
+    let warnings: Vec<String> = file
+        .minic_uninit()
+        .iter()
+        .map(|x| DisplayableWarning::new(x, &content).to_string())
+        .collect();
+    assert_eq!(warnings, expected);
+}
+
+test_uninit!(
+    field_1: "tests/source/uninit/testfile-field-1.c" -> vec![
+        "warning[W0002]: 9:17: field `b` of `p` may be read before being written in `main` [-Wuninitialized]",
+    ],
+    field_2: "tests/source/uninit/testfile-field-2.c" -> vec![
+        "warning[W0002]: 12:16: field `a` of `s` may be read before being written in `main` [-Wuninitialized]",
+    ],
+    local_1: "tests/source/uninit/testfile-local-1.c" -> vec![
+        "warning[W0002]: 7:10: `x` may be used uninitialised in `f` [-Wuninitialized]",
+    ],
+    local_2: "tests/source/uninit/testfile-local-2.c" -> vec![
+        "warning[W0002]: 4:10: `x` may be used uninitialised in `main` [-Wuninitialized]",
+        "warning[W0002]: 7:7: `y` may be used uninitialised in `main` [-Wuninitialized]",
+    ],
+    exec_init_1: "tests/source/exec/init1.c" -> vec![
+        "warning[W0002]: 5:7: `x` may be used uninitialised in `main` [-Wuninitialized]",
+        "warning[W0002]: 5:12: `x` may be used uninitialised in `main` [-Wuninitialized]",
+    ],
+    exec_lazy_1: "tests/source/exec/lazy1.c" -> vec![
+        "warning[W0002]: 3:27: `x` may be used uninitialised in `any` [-Wuninitialized]",
+    ],
+    exec_abr: "tests/source/exec/abr.c" -> vec![],
+    exec_local_struct_1: "tests/source/exec/local_struct1.c" -> vec![],
+);